@@ -0,0 +1,105 @@
+use crate::data::data::Data;
+use crate::fits::{Diagnostic, FITS, HDU};
+use crate::header::Header;
+use crate::reader::{HDUEntry, HeaderScan, Layout};
+
+use futures_util::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+use std::io::{self, SeekFrom};
+
+/// Asynchronous counterpart of [`FitsReader`](crate::reader::FitsReader).
+///
+/// Headers are read when the file is opened, so they can be shown before any data has been
+/// fetched. Works with any `futures` `AsyncRead + AsyncSeek` source; tokio types can be
+/// adapted with `tokio_util::compat`.
+pub struct AsyncFitsReader<R: AsyncRead + AsyncSeek + Unpin> {
+    reader: R,
+    layout: Layout,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncFitsReader<R> {
+    pub async fn new(mut reader: R) -> io::Result<AsyncFitsReader<R>> {
+        let length = reader.seek(SeekFrom::End(0)).await?;
+        let mut scan = HeaderScan::new(0, length);
+        let mut chunk = [0u8; 2880];
+        while let Some(offset) = scan.next_block() {
+            reader.seek(SeekFrom::Start(offset)).await?;
+            reader.read_exact(&mut chunk).await?;
+            scan.push_block(chunk);
+        }
+        let (entries, diagnostics) = scan.finish();
+        Ok(AsyncFitsReader {
+            reader,
+            layout: Layout { length, entries },
+            diagnostics,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.layout.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layout.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &Vec<HDUEntry> {
+        &self.layout.entries
+    }
+
+    pub fn header(&self, index: usize) -> &Header {
+        &self.layout.entries[index].header
+    }
+
+    /// Position of the first HDU with the given EXTNAME and, if given, EXTVER, without
+    /// reading any data. See [`FITS::hdu_index`].
+    pub fn hdu_index(&self, name: &str, version: Option<i64>) -> Result<usize, String> {
+        self.layout.hdu_index(name, version)
+    }
+
+    pub fn list_headers(&self) -> Vec<String> {
+        self.layout.list_headers()
+    }
+
+    /// Read the data section of one HDU as fitsblocks, zero-filling anything missing.
+    pub async fn read_fitsblocks(&mut self, index: usize) -> io::Result<Vec<[u8; 2880]>> {
+        let (offset, mut fitsblocks, available) = self.layout.fitsblocks(index)?;
+        self.reader.seek(SeekFrom::Start(offset)).await?;
+        self.reader
+            .read_exact(&mut fitsblocks.as_flattened_mut()[..available])
+            .await?;
+        Ok(fitsblocks)
+    }
+
+    /// Read `length` bytes starting `offset` bytes into the data section of one HDU.
+    pub async fn read_data_bytes(&mut self, index: usize, offset: u64, length: usize) -> io::Result<Vec<u8>> {
+        let start = self.layout.data_bytes(index, offset, length)?;
+        let mut bytes = vec![0u8; length];
+        self.reader.seek(SeekFrom::Start(start)).await?;
+        self.reader.read_exact(&mut bytes).await?;
+        Ok(bytes)
+    }
+
+    pub async fn read_data(&mut self, index: usize) -> io::Result<Data> {
+        let fitsblocks = self.read_fitsblocks(index).await?;
+        Ok(self.layout.data(index, fitsblocks))
+    }
+
+    pub async fn read_hdu(&mut self, index: usize) -> io::Result<HDU> {
+        let fitsblocks = self.read_fitsblocks(index).await?;
+        Ok(self.layout.hdu(index, fitsblocks))
+    }
+
+    /// Read every HDU into memory.
+    pub async fn read_all(&mut self) -> io::Result<FITS> {
+        let mut hdus: Vec<HDU> = Vec::with_capacity(self.len());
+        for i in 0..self.len() {
+            hdus.push(self.read_hdu(i).await?);
+        }
+        Ok(Layout::fits(hdus, &self.diagnostics))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
@@ -0,0 +1,214 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use rustfits::diff::{self, DiffOptions};
+use rustfits::fits::FITS;
+use rustfits::info;
+use rustfits::reader::FitsReader;
+use rustfits::verify::{self, Severity};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "rustfits", version, about = "Inspect FITS files")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the HDUs of each file with their type, dimensions and format
+    Info {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Print header cards
+    Header {
+        file: PathBuf,
+        /// HDU index, or EXTNAME of the first HDU with that name; all HDUs when omitted
+        #[arg(long)]
+        hdu: Option<String>,
+        /// Only print cards whose keyword matches this glob, may be repeated
+        #[arg(short, long = "keyword")]
+        keywords: Vec<String>,
+        #[arg(short, long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Compare two files, exiting with 1 if they differ
+    Diff {
+        a: PathBuf,
+        b: PathBuf,
+        /// Keyword glob left out of the header comparison, may be repeated
+        #[arg(short = 'k', long = "ignore-keyword")]
+        ignore_keywords: Vec<String>,
+        /// Do not compare card comments
+        #[arg(long)]
+        ignore_comments: bool,
+        /// Relative tolerance for pixel and table values
+        #[arg(long, default_value_t = 0.0)]
+        rtol: f64,
+        /// Absolute tolerance for pixel and table values
+        #[arg(long, default_value_t = 0.0)]
+        atol: f64,
+        /// Number of differing pixels or rows listed per HDU or column
+        #[arg(long, default_value_t = 10)]
+        max_reported: usize,
+    },
+    /// Check files against the FITS standard, exiting with 1 if any has errors
+    Verify {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Only print errors
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Copy a file, or the part selected by an extended filename such as
+    /// 'in.fits[EVENTS][PI > 100][col X;Y]' or 'in.fits[1][100:200,300:400]'
+    Copy {
+        input: String,
+        /// Output file; a leading '!' overwrites it, as with cfitsio
+        output: String,
+        /// Overwrite the output file if it exists
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+fn open(path: &Path) -> Result<FitsReader<File>, String> {
+    let file = File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    FITS::open(file).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+fn read(path: &Path) -> Result<FITS, String> {
+    open(path)?.read_all().map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+fn info(files: &[PathBuf]) -> Result<ExitCode, String> {
+    for (i, path) in files.iter().enumerate() {
+        let reader = open(path)?;
+        let headers = reader.entries().iter().map(|entry| &entry.header);
+        let summaries = info::summaries(reader.list_headers(), headers);
+        if i > 0 {
+            println!();
+        }
+        println!("Filename: {}", path.display());
+        print!("{}", info::format_summaries(&summaries));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn header(path: &Path, hdu: Option<&str>, keywords: &[String], format: Format) -> Result<ExitCode, String> {
+    let reader = open(path)?;
+    let indices: Vec<usize> = match hdu {
+        None => (0..reader.len()).collect(),
+        Some(hdu) => match hdu.parse::<usize>() {
+            Ok(index) if index < reader.len() => vec![index],
+            Ok(index) => return Err(format!("HDU {} out of range, the file has {} HDUs", index, reader.len())),
+            Err(_) => vec![reader.hdu_index(hdu, None)?],
+        },
+    };
+    let cards: Vec<(usize, Vec<String>)> = indices
+        .into_iter()
+        .map(|index| (index, info::select_cards(reader.header(index), keywords)))
+        .collect();
+    match format {
+        Format::Text => print!("{}", info::cards_text(&cards)),
+        Format::Json => print!("{}", info::cards_json(&cards)),
+        Format::Csv => print!("{}", info::cards_csv(&cards)),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn compare(a: &Path, b: &Path, options: &DiffOptions) -> Result<ExitCode, String> {
+    let report = diff::compare(&read(a)?, &read(b)?, options);
+    println!("a: {}", a.display());
+    println!("b: {}", b.display());
+    print!("{}", report);
+    Ok(if report.is_identical() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    })
+}
+
+fn check(files: &[PathBuf], quiet: bool) -> Result<ExitCode, String> {
+    let mut failed = false;
+    for path in files {
+        let bytes = std::fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let issues = verify::verify(&bytes);
+        let errors = issues.iter().filter(|issue| issue.severity == Severity::Error).count();
+        for issue in issues.iter().filter(|issue| !quiet || issue.severity == Severity::Error) {
+            println!("{}: {}", path.display(), issue);
+        }
+        println!("{}: {} errors, {} warnings", path.display(), errors, issues.len() - errors);
+        failed |= errors > 0;
+    }
+    Ok(if failed { ExitCode::from(1) } else { ExitCode::SUCCESS })
+}
+
+fn copy(input: &str, output: &str, overwrite: bool) -> Result<ExitCode, String> {
+    let (output, overwrite) = match output.strip_prefix('!') {
+        Some(output) => (output, true),
+        None => (output, overwrite),
+    };
+    if !overwrite && Path::new(output).exists() {
+        return Err(format!("{} exists, use --overwrite or a leading '!' to replace it", output));
+    }
+    let fits = FITS::open_extended(input).map_err(|err| format!("{}: {}", input, err))?;
+    let mut file = File::create(output).map_err(|err| format!("{}: {}", output, err))?;
+    fits.write(&mut file).map_err(|err| format!("{}: {}", output, err))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match &cli.command {
+        Command::Info { files } => info(files),
+        Command::Header {
+            file,
+            hdu,
+            keywords,
+            format,
+        } => header(file, hdu.as_deref(), keywords, *format),
+        Command::Diff {
+            a,
+            b,
+            ignore_keywords,
+            ignore_comments,
+            rtol,
+            atol,
+            max_reported,
+        } => {
+            let options = DiffOptions {
+                ignore_keywords: ignore_keywords.clone(),
+                ignore_comments: *ignore_comments,
+                rtol: *rtol,
+                atol: *atol,
+                max_reported: *max_reported,
+            };
+            compare(a, b, &options)
+        }
+        Command::Verify { files, quiet } => check(files, *quiet),
+        Command::Copy {
+            input,
+            output,
+            overwrite,
+        } => copy(input, output, *overwrite),
+    };
+    // Exit codes follow diff(1): 0 for success or identical files, 1 for differences
+    // and 2 for errors
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("rustfits: {}", err);
+            ExitCode::from(2)
+        }
+    }
+}
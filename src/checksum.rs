@@ -0,0 +1,139 @@
+//! The FITS checksum convention: 32-bit ones-complement sums of the data (`DATASUM`) and
+//! of the whole HDU (`CHECKSUM`, ASCII encoded so the HDU sums to negative zero).
+
+use crate::fits::HDU;
+use crate::header::Card;
+use std::convert::TryInto;
+
+/// Card written in place of CHECKSUM while the header sum is computed.
+const ZERO_CHECKSUM: &str = "CHECKSUM= '0000000000000000'   / HDU checksum";
+
+/// 32-bit ones-complement sum of big-endian words, continuing from `initial`. The length
+/// of `bytes` must be a multiple of 4, as whole fitsblocks are.
+pub fn checksum(bytes: &[u8], initial: u32) -> u32 {
+    let mut sum = initial as u64;
+    for word in bytes.chunks_exact(4) {
+        sum += u32::from_be_bytes(word.try_into().unwrap()) as u64;
+        if sum > u32::MAX as u64 {
+            sum = (sum & 0xFFFF_FFFF) + (sum >> 32);
+        }
+    }
+    sum as u32
+}
+
+fn blocks_checksum(blocks: &[[u8; 2880]], initial: u32) -> u32 {
+    blocks.iter().fold(initial, |sum, block| checksum(block, sum))
+}
+
+/// Encode a sum as the 16 ASCII characters of a CHECKSUM value. Each byte is spread over
+/// four alphanumeric characters, and the string is rotated by one to line up with the
+/// value starting in column 12 of the card.
+pub fn encode(value: u32) -> String {
+    const EXCLUDE: [u8; 13] = [
+        0x3a, 0x3b, 0x3c, 0x3d, 0x3e, 0x3f, 0x40, 0x5b, 0x5c, 0x5d, 0x5e, 0x5f, 0x60,
+    ];
+    let mut interleaved = [0u8; 16];
+    for (i, byte) in value.to_be_bytes().iter().enumerate() {
+        let mut chars = [byte / 4 + 0x30; 4];
+        chars[0] += byte % 4;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for excluded in EXCLUDE {
+                for j in [0, 2] {
+                    if chars[j] == excluded || chars[j + 1] == excluded {
+                        chars[j] += 1;
+                        chars[j + 1] -= 1;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        for (j, c) in chars.iter().enumerate() {
+            interleaved[4 * j + i] = *c;
+        }
+    }
+    (0..16).map(|i| interleaved[(i + 15) % 16] as char).collect()
+}
+
+/// Sum of the data section of an HDU, as stored in DATASUM.
+pub fn datasum(hdu: &HDU) -> u32 {
+    blocks_checksum(hdu.data.get_fitsblocks(), 0)
+}
+
+/// State of one checksum keyword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The keyword is not in the header.
+    Absent,
+    Valid,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Verification {
+    pub datasum: Status,
+    pub checksum: Status,
+}
+
+impl Verification {
+    pub fn is_valid(&self) -> bool {
+        self.datasum != Status::Invalid && self.checksum != Status::Invalid
+    }
+}
+
+/// Check DATASUM and CHECKSUM of an HDU against its header and data blocks.
+pub fn verify(hdu: &HDU) -> Verification {
+    verify_bytes(
+        hdu.header.get_fitsblocks().as_flattened(),
+        hdu.data.get_fitsblocks().as_flattened(),
+        &hdu.header.cards(),
+    )
+}
+
+/// Like [`verify`] on the raw header and data bytes of whole fitsblocks, given the header
+/// cards.
+pub(crate) fn verify_bytes(header: &[u8], data: &[u8], cards: &[String]) -> Verification {
+    let value = |keyword: &str| {
+        cards
+            .iter()
+            .map(|card| Card::parse(card))
+            .find(|card| card.keyword == keyword)
+            .and_then(|card| card.value_text())
+    };
+    let (datasum_value, checksum_value) = (value("DATASUM"), value("CHECKSUM"));
+    if datasum_value.is_none() && checksum_value.is_none() {
+        return Verification {
+            datasum: Status::Absent,
+            checksum: Status::Absent,
+        };
+    }
+    let datasum = checksum(data, 0);
+    let datasum_status = match datasum_value {
+        None => Status::Absent,
+        Some(text) if text.trim().parse::<u64>().ok() == Some(datasum as u64) => Status::Valid,
+        Some(_) => Status::Invalid,
+    };
+    let checksum_status = match checksum_value {
+        None => Status::Absent,
+        // The encoded CHECKSUM makes the whole HDU sum to negative zero
+        Some(_) if checksum(header, datasum) == u32::MAX => Status::Valid,
+        Some(_) => Status::Invalid,
+    };
+    Verification {
+        datasum: datasum_status,
+        checksum: checksum_status,
+    }
+}
+
+/// Write DATASUM and CHECKSUM into the header of an HDU, replacing any previous values.
+/// Must be called after the last change to the header or data.
+pub fn update(hdu: &mut HDU) {
+    let datasum = datasum(hdu);
+    hdu.header
+        .set_card(&format!("DATASUM = {:<20} / data unit checksum", format!("'{}'", datasum)));
+    hdu.header.set_card(ZERO_CHECKSUM);
+    let sum = blocks_checksum(hdu.header.get_fitsblocks(), datasum);
+    hdu.header
+        .set_card(&format!("CHECKSUM= '{}'   / HDU checksum", encode(!sum)));
+}
@@ -1,6 +1,7 @@
 use crate::data::data::Precision;
+use crate::data::view::ImageView;
+use std::ops::Range;
 use crate::header;
-use byteorder::{BigEndian, ByteOrder};
 use ndarray::Array;
 
 pub enum ArrayType {
@@ -22,7 +23,7 @@ pub struct ArrayData {
 
 impl ArrayData {
     pub fn new(
-        fitsblocks: &Vec<[u8; 2880]>,
+        fitsblocks: Vec<[u8; 2880]>,
         header: &header::Header,
         pcount: Option<usize>,
         gcount: Option<usize>,
@@ -38,16 +39,10 @@ impl ArrayData {
                 .unwrap();
             naxisn.push(naxisn_i);
         }
-        let pcount = match pcount {
-            Some(pcount) => pcount as usize,
-            None => 0,
-        };
-        let gcount = match gcount {
-            Some(gcount) => gcount as usize,
-            None => 1,
-        };
+        let pcount = pcount.unwrap_or(0);
+        let gcount = gcount.unwrap_or(1);
         ArrayData {
-            fitsblocks: fitsblocks.to_vec(),
+            fitsblocks,
             bitpix,
             naxis,
             naxisn,
@@ -60,54 +55,49 @@ impl ArrayData {
         self.gcount * (self.pcount + self.naxisn.iter().product::<usize>())
     }
 
+    pub fn get_bitpix(&self) -> i8 {
+        self.bitpix
+    }
+
+    pub fn get_naxisn(&self) -> &Vec<usize> {
+        &self.naxisn
+    }
+
+    /// Borrow the image bytes without decoding them.
+    pub fn view(&self) -> ImageView<'_> {
+        let bytes = self.fitsblocks.as_flattened();
+        let n_bytes = (self.n_entries() * (self.bitpix.unsigned_abs() as usize) / 8).min(bytes.len());
+        ImageView::new(&bytes[..n_bytes], self.bitpix, &self.naxisn)
+    }
+
     pub fn format_data(&self) -> Array<Precision, ndarray::IxDyn> {
-        let fitsblocks_flat: Vec<u8> = self.fitsblocks.iter().flatten().cloned().collect();
-        let mut local_vec: Vec<Precision> = Vec::new();
-        match self.bitpix {
-            8 => {
-                for i in 0..fitsblocks_flat.len() {
-                    local_vec.push(Precision::U8(fitsblocks_flat[i]));
-                }
-            }
-            16 => {
-                for i in 0..fitsblocks_flat.len() / 2 {
-                    local_vec.push(Precision::I16(BigEndian::read_i16(
-                        &fitsblocks_flat[i * 2..(i + 1) * 2],
-                    )));
-                }
-            }
-            32 => {
-                for i in 0..fitsblocks_flat.len() / 4 {
-                    local_vec.push(Precision::I32(BigEndian::read_i32(
-                        &fitsblocks_flat[i * 4..(i + 1) * 4],
-                    )));
-                }
-            }
-            64 => {
-                for i in 0..fitsblocks_flat.len() / 8 {
-                    local_vec.push(Precision::I64(BigEndian::read_i64(
-                        &fitsblocks_flat[i * 8..(i + 1) * 8],
-                    )));
-                }
-            }
-            -32 => {
-                for i in 0..fitsblocks_flat.len() / 4 {
-                    local_vec.push(Precision::F32(BigEndian::read_f32(
-                        &fitsblocks_flat[i * 4..(i + 1) * 4],
-                    )));
-                }
-            }
-            -64 => {
-                for i in 0..fitsblocks_flat.len() / 8 {
-                    local_vec.push(Precision::F64(BigEndian::read_f64(
-                        &fitsblocks_flat[i * 8..(i + 1) * 8],
-                    )));
-                }
-            }
-            _ => {
-                panic!("Unsupported bitpix value: {}", self.bitpix);
-            }
+        let fitsblocks_flat: &[u8] = self.fitsblocks.as_flattened();
+        let size = (self.bitpix.unsigned_abs() / 8) as usize;
+        if size == 0 || !matches!(self.bitpix, 8 | 16 | 32 | 64 | -32 | -64) {
+            panic!("Unsupported bitpix value: {}", self.bitpix);
         }
+        let local_vec: Vec<Precision> = fitsblocks_flat
+            .chunks_exact(size)
+            .take(self.n_entries())
+            .map(|bytes| Precision::from_bytes(bytes, self.bitpix))
+            .collect();
         Array::from_shape_vec(self.naxisn.clone(), local_vec[..self.n_entries()].to_vec()).unwrap()
     }
+
+    /// Decode only the pixels inside `ranges`, one range per axis in the order of `format_data`.
+    ///
+    /// Equivalent to slicing the output of `format_data`, without decoding the whole image.
+    /// Fails if the ranges do not fit in the image or the data is truncated.
+    pub fn read_section(&self, ranges: &[Range<usize>]) -> Result<Array<Precision, ndarray::IxDyn>, String> {
+        self.read_section_strided(ranges, &vec![1; ranges.len()])
+    }
+
+    /// Same as [`ArrayData::read_section`], keeping every `steps[i]`-th pixel along axis `i`.
+    pub fn read_section_strided(
+        &self,
+        ranges: &[Range<usize>],
+        steps: &[usize],
+    ) -> Result<Array<Precision, ndarray::IxDyn>, String> {
+        self.view().read_section_strided(ranges, steps)
+    }
 }
@@ -0,0 +1,173 @@
+use crate::data::data::Data;
+use crate::data::tables::BinaryTable;
+use crate::fits::HDU;
+use crate::header::Header;
+use crate::wcs::table::image_keywords;
+
+/// Optional settings of [`BinaryTable::bin_columns`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BinOptions {
+    /// Column summed into each pixel instead of counting rows. The image is then written
+    /// with BITPIX = -32 rather than 32.
+    pub weight: Option<String>,
+    /// Only rows whose value in the column lies in `[min, max]` are binned.
+    pub filters: Vec<(String, f64, f64)>,
+}
+
+/// Keywords copied from the table header to the binned image when present.
+const COPIED_KEYWORDS: [&str; 8] = [
+    "OBJECT", "TELESCOP", "INSTRUME", "OBSERVER", "DATE-OBS", "DATE-END", "MJD-OBS", "EXPOSURE",
+];
+
+impl BinaryTable {
+    /// Bin two columns into a 2D image HDU, as done to turn an event list into a sky image.
+    ///
+    /// Column values are pixel coordinates with pixel centres at integer values, so bin `i`
+    /// of the x axis covers `[min + i * bin_size, min + (i + 1) * bin_size)` with `min` taken
+    /// as the lower edge of `range`. Without a range, TLMINn - 0.5 and TLMAXn + 0.5 are used,
+    /// or the extent of the data if those are missing. The header carries the image WCS
+    /// derived from the columns' pixel-list keywords, for the primary and every alternate
+    /// description. `header` is the header of the table HDU.
+    pub fn bin_columns(
+        &self,
+        header: &Header,
+        x: &str,
+        y: &str,
+        bin_size: f64,
+        range: Option<[(f64, f64); 2]>,
+        options: &BinOptions,
+    ) -> Result<HDU, String> {
+        if bin_size.partial_cmp(&0.0) != Some(std::cmp::Ordering::Greater) {
+            return Err(format!("Invalid bin size {}", bin_size));
+        }
+        let columns = [self.numeric_column(x)?, self.numeric_column(y)?];
+        let weights = match &options.weight {
+            Some(name) => Some(self.numeric_column(name)?.1),
+            None => None,
+        };
+        let filters = options
+            .filters
+            .iter()
+            .map(|(name, min, max)| Ok((self.numeric_column(name)?.1, *min, *max)))
+            .collect::<Result<Vec<(Vec<f64>, f64, f64)>, String>>()?;
+
+        let range = match range {
+            Some(range) => range,
+            None => {
+                let limits = |(index, values): &(usize, Vec<f64>)| {
+                    let n = index + 1;
+                    match (header.get_f64(&format!("TLMIN{}", n)), header.get_f64(&format!("TLMAX{}", n))) {
+                        (Some(min), Some(max)) => (min - 0.5, max + 0.5),
+                        _ => {
+                            let finite = values.iter().filter(|value| value.is_finite());
+                            let min = finite.clone().fold(f64::INFINITY, |a, b| a.min(*b));
+                            let max = finite.fold(f64::NEG_INFINITY, |a, b| a.max(*b));
+                            (min.floor() - 0.5, max.ceil() + 0.5)
+                        }
+                    }
+                };
+                [limits(&columns[0]), limits(&columns[1])]
+            }
+        };
+        let shape: Vec<usize> = range
+            .iter()
+            .map(|(min, max)| ((max - min) / bin_size).ceil().max(0.0) as usize)
+            .collect();
+        if shape.contains(&0) {
+            return Err(format!("Empty binning range {:?}", range));
+        }
+
+        let mut image = vec![0.0f64; shape[0] * shape[1]];
+        for row in 0..self.n_rows() {
+            if filters.iter().any(|(values, min, max)| !(values[row] >= *min && values[row] <= *max)) {
+                continue;
+            }
+            let i = (columns[0].1[row] - range[0].0) / bin_size;
+            let j = (columns[1].1[row] - range[1].0) / bin_size;
+            if !(i >= 0.0 && j >= 0.0 && (i as usize) < shape[0] && (j as usize) < shape[1]) {
+                continue;
+            }
+            image[j as usize * shape[0] + i as usize] += weights.as_ref().map_or(1.0, |weights| weights[row]);
+        }
+
+        let bitpix = if weights.is_some() { -32 } else { 32 };
+        let mut cards = vec![
+            Header::format_card("SIMPLE", "T"),
+            Header::format_card("BITPIX", &bitpix.to_string()),
+            Header::format_card("NAXIS", "2"),
+            Header::format_card("NAXIS1", &shape[0].to_string()),
+            Header::format_card("NAXIS2", &shape[1].to_string()),
+        ];
+        for keyword in COPIED_KEYWORDS.iter() {
+            if let Some(value) = header.get_value(keyword) {
+                cards.push(Header::format_card(keyword, value));
+            }
+        }
+        if weights.is_none() {
+            cards.push(Header::format_card("BUNIT", "count"));
+        }
+        let numbers = [columns[0].0 + 1, columns[1].0 + 1];
+        for alt in std::iter::once("").chain(table_alternates(header, numbers[0]).iter().map(|alt| alt.as_str())) {
+            for (keyword, value) in image_keywords(header, &numbers, alt) {
+                let value = binned_value(&keyword, &value, alt, bin_size, &range).unwrap_or(value);
+                cards.push(Header::format_card(&keyword, &value));
+            }
+        }
+        let header = Header::from_cards(&cards);
+
+        let mut bytes: Vec<u8> = Vec::with_capacity(image.len() * 4);
+        for value in image.iter() {
+            if bitpix == 32 {
+                bytes.extend_from_slice(&(*value as i32).to_be_bytes());
+            } else {
+                bytes.extend_from_slice(&(*value as f32).to_be_bytes());
+            }
+        }
+        let fitsblocks: Vec<[u8; 2880]> = bytes
+            .chunks(2880)
+            .map(|chunk| {
+                let mut block = [0u8; 2880];
+                block[..chunk.len()].copy_from_slice(chunk);
+                block
+            })
+            .collect();
+        let data = Data::from_header(fitsblocks, &header);
+        Ok(HDU { header, data })
+    }
+
+    /// Column index and scalar values of a numerical column.
+    fn numeric_column(&self, name: &str) -> Result<(usize, Vec<f64>), String> {
+        let index = self.column_index(name).ok_or_else(|| format!("No column named {:?}", name))?;
+        let values = self
+            .column_f64(index)
+            .ok_or_else(|| format!("Column {:?} is not numerical", name))?;
+        Ok((index, values.iter().map(|row| row.first().cloned().unwrap_or(f64::NAN)).collect()))
+    }
+}
+
+/// Letters of the alternate pixel-list descriptions defined for a column.
+fn table_alternates(header: &Header, column: usize) -> Vec<String> {
+    ('A'..='Z')
+        .map(|letter| letter.to_string())
+        .filter(|letter| {
+            ["TCTY", "TCRP", "TCRV", "TCDE"]
+                .iter()
+                .any(|keyword| header.contains_keyword(&format!("{}{}{}", keyword, column, letter)))
+        })
+        .collect()
+}
+
+/// Rescale the reference pixel and pixel scale of an image WCS keyword to the binned pixels.
+fn binned_value(keyword: &str, value: &str, alt: &str, bin_size: f64, range: &[(f64, f64); 2]) -> Option<String> {
+    let keyword = keyword.strip_suffix(alt)?;
+    let number = value.replace(['D', 'd'], "E").parse::<f64>().ok()?;
+    let axis = |prefix: &str| -> Option<usize> { keyword.strip_prefix(prefix)?.parse::<usize>().ok() };
+    let scaled = if let Some(i) = axis("CRPIX") {
+        (number - range.get(i - 1)?.0) / bin_size + 0.5
+    } else if axis("CDELT").is_some() || (keyword.starts_with("CD") && keyword.contains('_')) {
+        number * bin_size
+    } else {
+        return None;
+    };
+    Some(Header::format_real(scaled))
+}
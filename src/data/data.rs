@@ -1,7 +1,6 @@
-use std::u8;
-
-use crate::data::{array, tables};
+use crate::data::{array, raw, tables};
 use crate::header;
+use byteorder::{BigEndian, ByteOrder};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Precision {
@@ -14,6 +13,19 @@ pub enum Precision {
 }
 
 impl Precision {
+    /// Decode one big-endian value of the given BITPIX.
+    pub fn from_bytes(bytes: &[u8], bitpix: i8) -> Precision {
+        match bitpix {
+            8 => Precision::U8(bytes[0]),
+            16 => Precision::I16(BigEndian::read_i16(bytes)),
+            32 => Precision::I32(BigEndian::read_i32(bytes)),
+            64 => Precision::I64(BigEndian::read_i64(bytes)),
+            -32 => Precision::F32(BigEndian::read_f32(bytes)),
+            -64 => Precision::F64(BigEndian::read_f64(bytes)),
+            _ => panic!("Unsupported bitpix value: {}", bitpix),
+        }
+    }
+
     pub fn to_u8(&self) -> u8 {
         match self {
             Precision::U8(value) => *value,
@@ -92,6 +104,13 @@ pub enum Data {
     Array(array::ArrayData),
     ASCIITable(tables::ASCIITable),
     BinaryTable(tables::BinaryTable),
+    Raw(raw::RawData),
+}
+
+impl Default for Data {
+    fn default() -> Self {
+        Data::new()
+    }
 }
 
 impl Data {
@@ -109,10 +128,13 @@ impl Data {
             Data::Array(array) => array.fitsblocks.push(chunk),
             Data::ASCIITable(ascii_table) => ascii_table.fitsblocks.push(chunk),
             Data::BinaryTable(binary_table) => binary_table.fitsblocks.push(chunk),
+            Data::Raw(raw) => raw.fitsblocks.push(chunk),
         }
     }
 
-    pub fn from_header(fitsblocks: &Vec<[u8; 2880]>, header: &header::Header) -> Data {
+    /// Interpret the fitsblocks of a data section according to its header.
+    /// The blocks are moved into the returned data without copying.
+    pub fn from_header(fitsblocks: Vec<[u8; 2880]>, header: &header::Header) -> Data {
         let header_type = header.get_header_type();
         match header_type {
             header::HeaderType::Primary => Data::Array(array::ArrayData::new(fitsblocks, header, Some(0), Some(1))),
@@ -123,6 +145,7 @@ impl Data {
             header::HeaderType::BinaryTable => {
                 Data::BinaryTable(tables::BinaryTable::new(fitsblocks, header))
             }
+            header::HeaderType::Other(_) => Data::Raw(raw::RawData::new(fitsblocks, header)),
         }
     }
 
@@ -132,6 +155,17 @@ impl Data {
             Data::Array(array) => &array.fitsblocks,
             Data::ASCIITable(ascii_table) => &ascii_table.fitsblocks,
             Data::BinaryTable(binary_table) => &binary_table.fitsblocks,
+            Data::Raw(raw) => &raw.fitsblocks,
+        }
+    }
+
+    pub fn into_fitsblocks(self) -> Vec<[u8; 2880]> {
+        match self {
+            Data::Empty(empty) => empty.fitsblocks,
+            Data::Array(array) => array.fitsblocks,
+            Data::ASCIITable(ascii_table) => ascii_table.fitsblocks,
+            Data::BinaryTable(binary_table) => binary_table.fitsblocks,
+            Data::Raw(raw) => raw.fitsblocks,
         }
     }
 }
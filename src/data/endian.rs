@@ -0,0 +1,61 @@
+use byteorder::{BigEndian, ByteOrder};
+
+mod sealed {
+    /// Only the `repr(transparent)` byte-array wrappers of this module may implement
+    /// [`super::BigEndianScalar`], since `cast_slice` relies on their layout.
+    pub trait Sealed {}
+}
+
+/// Scalar stored in big-endian byte order, as found in a FITS data section.
+///
+/// The wrapper types have an alignment of one, so a slice of raw bytes can be viewed as a
+/// slice of them without copying. The native value is decoded on access. The trait is
+/// sealed: it is implemented for the types of this module only.
+pub trait BigEndianScalar: sealed::Sealed + Copy + std::fmt::Debug {
+    type Native: Copy;
+    /// BITPIX value of data stored with this type
+    const BITPIX: i8;
+
+    fn get(&self) -> Self::Native;
+    fn to_f64(&self) -> f64;
+
+    /// View raw bytes as a slice of big-endian values. Trailing bytes that do not form a
+    /// whole value are ignored.
+    fn cast_slice(bytes: &[u8]) -> &[Self] {
+        let size = std::mem::size_of::<Self>();
+        // Safety: the trait is sealed and its only implementors are `repr(transparent)`
+        // wrappers around `[u8; N]`, so they have the size of N bytes, an alignment of
+        // one, no padding, and every bit pattern is valid.
+        unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const Self, bytes.len() / size) }
+    }
+}
+
+macro_rules! big_endian_scalar {
+    ($name:ident, $native:ty, $size:expr, $bitpix:expr, $read:expr) => {
+        #[repr(transparent)]
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        pub struct $name(pub [u8; $size]);
+
+        impl sealed::Sealed for $name {}
+
+        impl BigEndianScalar for $name {
+            type Native = $native;
+            const BITPIX: i8 = $bitpix;
+
+            fn get(&self) -> $native {
+                $read(&self.0)
+            }
+
+            fn to_f64(&self) -> f64 {
+                self.get() as f64
+            }
+        }
+    };
+}
+
+big_endian_scalar!(BeU8, u8, 1, 8, |bytes: &[u8]| bytes[0]);
+big_endian_scalar!(BeI16, i16, 2, 16, BigEndian::read_i16);
+big_endian_scalar!(BeI32, i32, 4, 32, BigEndian::read_i32);
+big_endian_scalar!(BeI64, i64, 8, 64, BigEndian::read_i64);
+big_endian_scalar!(BeF32, f32, 4, -32, BigEndian::read_f32);
+big_endian_scalar!(BeF64, f64, 8, -64, BigEndian::read_f64);
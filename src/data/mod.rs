@@ -1,3 +1,9 @@
+#[allow(clippy::module_inception)]
 pub mod data;
 pub mod tables;
-pub mod array;
\ No newline at end of file
+pub mod array;
+pub mod raw;
+pub mod endian;
+pub mod view;
+pub mod section;
+pub mod binning;
@@ -0,0 +1,55 @@
+use crate::header;
+
+/// Data section of an HDU whose structure is not understood by rustfits,
+/// e.g. conforming extensions such as `IUEIMAGE`, `A3DTABLE`, `FOREIGN` or `DUMP`.
+///
+/// The bytes are kept untouched so the HDU can be inspected or re-written as is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawData {
+    pub fitsblocks: Vec<[u8; 2880]>,
+    bitpix: i8,
+    naxisn: Vec<usize>,
+    n_bytes: usize,
+}
+
+impl RawData {
+    pub fn new(fitsblocks: Vec<[u8; 2880]>, header: &header::Header) -> RawData {
+        let bitpix = header.get_keyword("BITPIX").unwrap().parse::<i8>().unwrap_or(8);
+        let naxis = header.get_keyword("NAXIS").unwrap().parse::<u8>().unwrap_or(0);
+        let mut naxisn: Vec<usize> = Vec::new();
+        for i in 1..=naxis {
+            let naxisn_i = header
+                .get_keyword(&format!("NAXIS{}", i))
+                .unwrap()
+                .parse::<usize>()
+                .unwrap_or(0);
+            naxisn.push(naxisn_i);
+        }
+        RawData {
+            fitsblocks,
+            bitpix,
+            naxisn,
+            n_bytes: header.data_size().unwrap_or(0),
+        }
+    }
+
+    pub fn get_bitpix(&self) -> i8 {
+        self.bitpix
+    }
+
+    pub fn get_naxisn(&self) -> &Vec<usize> {
+        &self.naxisn
+    }
+
+    /// Number of meaningful bytes in the data section, i.e. without the trailing padding.
+    /// See [`Header::data_size`](header::Header::data_size).
+    pub fn n_bytes(&self) -> usize {
+        self.n_bytes
+    }
+
+    /// The data bytes without the padding.
+    pub fn bytes(&self) -> Vec<u8> {
+        let bytes = self.fitsblocks.as_flattened();
+        bytes[..self.n_bytes().min(bytes.len())].to_vec()
+    }
+}
@@ -0,0 +1,115 @@
+use crate::data::data::Precision;
+use ndarray::{Array, IxDyn};
+use std::ops::Range;
+
+/// Contiguous stretch of the last axis selected by a section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    /// Flat (row-major) index of the first selected element
+    pub first: usize,
+    /// Number of selected elements
+    pub count: usize,
+    /// Distance between selected elements
+    pub step: usize,
+}
+
+impl Run {
+    /// Number of elements spanned from the first to the last selected one.
+    pub fn span(&self) -> usize {
+        (self.count - 1) * self.step + 1
+    }
+}
+
+/// Plan for reading a rectangular, optionally strided, section of an image.
+///
+/// Axes follow the same order as [`ArrayData::format_data`](crate::data::array::ArrayData::format_data),
+/// so the last axis is the one stored contiguously. Each selected stretch of the last axis
+/// becomes one [`Run`], which lets backends read only the bytes they need.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub shape: Vec<usize>,
+    pub runs: Vec<Run>,
+}
+
+impl Section {
+    pub fn new(naxisn: &[usize], ranges: &[Range<usize>], steps: &[usize]) -> Result<Section, String> {
+        if naxisn.is_empty() {
+            return Err("The image has no axes (NAXIS = 0)".to_string());
+        }
+        if ranges.len() != naxisn.len() || steps.len() != naxisn.len() {
+            return Err(format!(
+                "Section has {} ranges and {} steps but the image has {} axes",
+                ranges.len(),
+                steps.len(),
+                naxisn.len()
+            ));
+        }
+        for (axis, (range, step)) in ranges.iter().zip(steps.iter()).enumerate() {
+            if range.start >= range.end || range.end > naxisn[axis] {
+                return Err(format!(
+                    "Range {:?} is empty or outside axis {} of length {}",
+                    range, axis, naxisn[axis]
+                ));
+            }
+            if *step == 0 {
+                return Err(format!("Step of axis {} must be at least 1", axis));
+            }
+        }
+        let shape: Vec<usize> = ranges
+            .iter()
+            .zip(steps.iter())
+            .map(|(range, step)| (range.end - range.start).div_ceil(*step))
+            .collect();
+        let mut strides = vec![1usize; naxisn.len()];
+        for axis in (0..naxisn.len().saturating_sub(1)).rev() {
+            strides[axis] = strides[axis + 1] * naxisn[axis + 1];
+        }
+
+        let last = naxisn.len() - 1;
+        let n_runs: usize = shape[..last].iter().product();
+        let mut runs: Vec<Run> = Vec::with_capacity(n_runs);
+        let mut index = vec![0usize; last];
+        for _ in 0..n_runs {
+            let mut first = ranges[last].start;
+            for axis in 0..last {
+                first += (ranges[axis].start + index[axis] * steps[axis]) * strides[axis];
+            }
+            runs.push(Run {
+                first,
+                count: shape[last],
+                step: steps[last],
+            });
+            for axis in (0..last).rev() {
+                index[axis] += 1;
+                if index[axis] < shape[axis] {
+                    break;
+                }
+                index[axis] = 0;
+            }
+        }
+        Ok(Section { shape, runs })
+    }
+
+    /// Decode the selected pixels from the full data bytes of an image. Fails if the bytes
+    /// end before the last selected pixel, as in a truncated file.
+    pub fn extract(&self, bytes: &[u8], bitpix: i8) -> Result<Array<Precision, IxDyn>, String> {
+        let size = (bitpix.unsigned_abs() / 8) as usize;
+        let mut values: Vec<Precision> = Vec::with_capacity(self.shape.iter().product());
+        for run in self.runs.iter() {
+            let run_bytes = bytes
+                .get(run.first * size..(run.first + run.span()) * size)
+                .ok_or_else(|| format!("The data ends before pixel {} of the section", run.first + run.span()))?;
+            self.decode_run(run_bytes, bitpix, run, &mut values);
+        }
+        Ok(Array::from_shape_vec(self.shape.clone(), values).unwrap())
+    }
+
+    /// Decode the selected pixels of one run, given the bytes from its first to its last element.
+    pub fn decode_run(&self, run_bytes: &[u8], bitpix: i8, run: &Run, values: &mut Vec<Precision>) {
+        let size = (bitpix.unsigned_abs() / 8) as usize;
+        for i in 0..run.count {
+            let start = i * run.step * size;
+            values.push(Precision::from_bytes(&run_bytes[start..start + size], bitpix));
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use crate::header;
 
+use crate::time::{Time, TimeReference, TimeScale};
 use header::Header;
 use std::fmt;
+use std::ops::Range;
 
 use byteorder::{BigEndian, ByteOrder};
 
@@ -33,7 +35,6 @@ impl<T: std::clone::Clone> Matrix2D<T> {
         let mut column_data: Vec<T> = Vec::new();
         for i in 0..self.n_row {
             let row_start = i * row_length;
-            let row_end = (i + 1) * row_length;
             column_data.push(self.data[row_start as usize + column as usize].clone());
         }
         column_data
@@ -97,10 +98,12 @@ pub struct ASCIITable {
     tfields: u32,
     tformn: Vec<String>,
     tbcoln: Vec<u32>,
+    ttypen: Vec<String>,
+    tunitn: Vec<String>,
 }
 
 impl ASCIITable {
-    pub fn new(fitsblocks: &Vec<[u8; 2880]>, header: &Header) -> ASCIITable {
+    pub fn new(fitsblocks: Vec<[u8; 2880]>, header: &Header) -> ASCIITable {
         let bitpix = header.get_keyword("BITPIX").unwrap().parse::<i8>().unwrap();
         let naxis = header.get_keyword("NAXIS").unwrap().parse::<u8>().unwrap();
         let mut naxisn: Vec<u32> = Vec::new();
@@ -131,8 +134,14 @@ impl ASCIITable {
                 .unwrap();
             tbcoln.push(tbcoln_i);
         }
+        let ttypen: Vec<String> = (1..=tfields)
+            .map(|i| header.get_value(&format!("TTYPE{}", i)).unwrap_or("").to_string())
+            .collect();
+        let tunitn: Vec<String> = (1..=tfields)
+            .map(|i| header.get_value(&format!("TUNIT{}", i)).unwrap_or("").to_string())
+            .collect();
         ASCIITable {
-            fitsblocks: fitsblocks.to_vec(),
+            fitsblocks,
             bitpix,
             naxis,
             naxisn,
@@ -141,11 +150,70 @@ impl ASCIITable {
             tfields,
             tformn,
             tbcoln,
+            ttypen,
+            tunitn,
         }
     }
 
+    pub fn n_rows(&self) -> usize {
+        self.naxisn.get(1).cloned().unwrap_or(0) as usize
+    }
+
+    pub fn get_tformn(&self) -> &Vec<String> {
+        &self.tformn
+    }
+
+    /// Column units from TUNITn, empty strings when missing.
+    pub fn get_tunitn(&self) -> &Vec<String> {
+        &self.tunitn
+    }
+
+    /// Column names from TTYPEn. Unnamed columns are empty strings.
+    pub fn column_names(&self) -> &Vec<String> {
+        &self.ttypen
+    }
+
+    /// Index of a column by name, ignoring case as the standard recommends.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.ttypen.iter().position(|ttype| ttype.eq_ignore_ascii_case(name))
+    }
+
+    /// Trimmed text of one field in a range of rows, clamped to the table.
+    pub fn column_strings_rows(&self, column: usize, rows: Range<usize>) -> Vec<String> {
+        let fitsblocks_flat: &[u8] = self.fitsblocks.as_flattened();
+        let row_length = self.naxisn[0] as usize;
+        let start = self.tbcoln[column] as usize - 1;
+        let width: usize = self.tformn[column]
+            .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+            .split('.')
+            .next()
+            .and_then(|width| width.parse().ok())
+            .unwrap_or(row_length - start);
+        let end = (start + width).min(row_length);
+        (rows.start.min(self.n_rows())..rows.end.min(self.n_rows()))
+            .map(|row| {
+                let bytes = &fitsblocks_flat[row * row_length + start..row * row_length + end];
+                String::from_utf8_lossy(bytes).trim().to_string()
+            })
+            .collect()
+    }
+
+    /// Numerical column (TFORM I, F, E or D) in a range of rows. Blank or unreadable fields are
+    /// NaN. Returns `None` for character columns.
+    pub fn column_f64_rows(&self, column: usize, rows: Range<usize>) -> Option<Vec<f64>> {
+        if !self.tformn[column].starts_with(['I', 'F', 'E', 'D']) {
+            return None;
+        }
+        let values = self
+            .column_strings_rows(column, rows)
+            .iter()
+            .map(|text| text.replace(['D', 'd'], "E").parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        Some(values)
+    }
+
     pub fn n_bits(&self) -> u32 {
-        (self.bitpix.abs() as u32)
+        (self.bitpix.unsigned_abs() as u32)
             * self.gcount
             * (self.pcount + self.naxisn.iter().product::<u32>())
     }
@@ -168,7 +236,7 @@ impl ASCIITable {
     }
 
     pub fn format_data(&self) -> Matrix2D<ASCIIField> {
-        let fitsblocks_flat: Vec<u8> = self.fitsblocks.iter().flatten().cloned().collect();
+        let fitsblocks_flat: &[u8] = self.fitsblocks.as_flattened();
         let row_length: u32 = self.naxisn[0];
         let n_row: u32 = self.naxisn[1];
         let n_field: u32 = self.tfields;
@@ -198,6 +266,8 @@ pub enum BinaryField {
     Complex64(f64, f64),
     Array32(f32, f32),
     Array64(f64, f64),
+    /// Field with a repeat count other than 1, one entry per element.
+    Vector(Vec<BinaryField>),
 }
 
 impl BinaryField{
@@ -238,6 +308,42 @@ impl BinaryField{
             _ => 1,
         }
     }
+
+    /// Split a TFORM value into its repeat count and type code, e.g. `20A` into (20, 'A').
+    /// Variable length descriptors such as `1PE(100)` give (1, 'P'). `None` if the repeat
+    /// count does not fit in a `usize`.
+    pub fn parse_tform(tform: &str) -> Option<(usize, char)> {
+        let tform = tform.trim();
+        let digits = tform.chars().take_while(|c| c.is_ascii_digit()).count();
+        let repeat = if digits == 0 { 1 } else { tform[..digits].parse::<usize>().ok()? };
+        let code = tform[digits..].chars().next().unwrap_or('A');
+        Some((repeat, code))
+    }
+
+    /// Number of bytes taken by a field of the given TFORM in each row, or `None` if it does
+    /// not fit in a `usize`.
+    pub fn field_width(tform: &str) -> Option<usize> {
+        let (repeat, code) = BinaryField::parse_tform(tform)?;
+        match code {
+            'X' => Some(repeat.div_ceil(8)),
+            'P' | 'Q' => Some(repeat.min(1) * BinaryField::n_bits(code)),
+            _ => repeat.checked_mul(BinaryField::n_bits(code)),
+        }
+    }
+
+    /// Numerical value of a field, or `None` for characters, bits, complex numbers and descriptors.
+    pub fn to_f64(&self) -> Option<f64> {
+        match self {
+            BinaryField::Logical(value) => Some(if *value { 1.0 } else { 0.0 }),
+            BinaryField::Byte(value) => Some(*value as f64),
+            BinaryField::I16(value) => Some(*value as f64),
+            BinaryField::I32(value) => Some(*value as f64),
+            BinaryField::I64(value) => Some(*value as f64),
+            BinaryField::F32(value) => Some(*value as f64),
+            BinaryField::F64(value) => Some(*value),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for BinaryField{
@@ -256,6 +362,10 @@ impl fmt::Display for BinaryField{
             BinaryField::Complex64(value1, value2) => write!(f, "{} {}", value1, value2),
             BinaryField::Array32(value1, value2) => write!(f, "{} {}", value1, value2),
             BinaryField::Array64(value1, value2) => write!(f, "{} {}", value1, value2),
+            BinaryField::Vector(values) => {
+                let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                write!(f, "{}", values.join(" "))
+            }
         }
     }
 }
@@ -270,10 +380,14 @@ pub struct BinaryTable {
     gcount: u32,
     tfields: u32,
     tformn: Vec<String>,
+    ttypen: Vec<String>,
+    tunitn: Vec<String>,
+    tscaln: Vec<f64>,
+    tzeron: Vec<f64>,
 }
 
 impl BinaryTable {
-    pub fn new(fitsblocks: &Vec<[u8; 2880]>, header: &Header) -> BinaryTable {
+    pub fn new(fitsblocks: Vec<[u8; 2880]>, header: &Header) -> BinaryTable {
         let bitpix = header.get_keyword("BITPIX").unwrap().parse::<i8>().unwrap();
         let naxis = header.get_keyword("NAXIS").unwrap().parse::<u8>().unwrap();
         let mut naxisn: Vec<u32> = Vec::new();
@@ -295,8 +409,20 @@ impl BinaryTable {
             let tformn_i = header.get_keyword(&format!("TFORM{}", i)).unwrap();
             tformn.push(tformn_i);
         }
+        let ttypen: Vec<String> = (1..=tfields)
+            .map(|i| header.get_value(&format!("TTYPE{}", i)).unwrap_or("").to_string())
+            .collect();
+        let tunitn: Vec<String> = (1..=tfields)
+            .map(|i| header.get_value(&format!("TUNIT{}", i)).unwrap_or("").to_string())
+            .collect();
+        let tscaln: Vec<f64> = (1..=tfields)
+            .map(|i| header.get_f64(&format!("TSCAL{}", i)).unwrap_or(1.0))
+            .collect();
+        let tzeron: Vec<f64> = (1..=tfields)
+            .map(|i| header.get_f64(&format!("TZERO{}", i)).unwrap_or(0.0))
+            .collect();
         BinaryTable {
-            fitsblocks: fitsblocks.to_vec(),
+            fitsblocks,
             bitpix,
             naxis,
             naxisn,
@@ -304,11 +430,125 @@ impl BinaryTable {
             gcount: 1,
             tfields,
             tformn,
+            ttypen,
+            tunitn,
+            tscaln,
+            tzeron,
+        }
+    }
+
+    /// Absolute times of a time column, relative to MJDREF in TIMESYS with TIMEZERO and
+    /// TIMEUNIT applied, converted to `scale`. `header` is the header of the table HDU.
+    pub fn column_times(&self, header: &Header, column: usize, scale: TimeScale) -> Result<Vec<Time>, String> {
+        let reference = TimeReference::from_header(header)?;
+        let values = self.column_f64(column).ok_or("Time columns must be numerical")?;
+        let values: Vec<f64> = values.iter().map(|row| row.first().cloned().unwrap_or(f64::NAN)).collect();
+        Ok(reference.times(&values, scale))
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.naxisn.get(1).cloned().unwrap_or(0) as usize
+    }
+
+    pub fn get_tformn(&self) -> &Vec<String> {
+        &self.tformn
+    }
+
+    /// Column units from TUNITn, empty strings when missing.
+    pub fn get_tunitn(&self) -> &Vec<String> {
+        &self.tunitn
+    }
+
+    /// Column names from TTYPEn. Unnamed columns are empty strings.
+    pub fn column_names(&self) -> &Vec<String> {
+        &self.ttypen
+    }
+
+    /// Index of a column by name, ignoring case as the standard recommends.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.ttypen.iter().position(|ttype| ttype.eq_ignore_ascii_case(name))
+    }
+
+    /// Byte offset of each field within a row.
+    fn field_offsets(&self) -> Vec<usize> {
+        let mut offsets: Vec<usize> = Vec::with_capacity(self.tformn.len());
+        let mut cursor: usize = 0;
+        for tform in self.tformn.iter() {
+            offsets.push(cursor);
+            cursor = cursor.saturating_add(BinaryField::field_width(tform).unwrap_or(0));
+        }
+        offsets
+    }
+
+    /// Raw bytes of one field in every row.
+    pub fn column_bytes(&self, column: usize) -> Vec<&[u8]> {
+        self.column_bytes_rows(column, 0..self.n_rows())
+    }
+
+    /// Raw bytes of one field in a range of rows, clamped to the table.
+    pub fn column_bytes_rows(&self, column: usize, rows: Range<usize>) -> Vec<&[u8]> {
+        let fitsblocks_flat: &[u8] = self.fitsblocks.as_flattened();
+        let row_length = self.naxisn[0] as usize;
+        let offset = self.field_offsets()[column];
+        let width = BinaryField::field_width(&self.tformn[column]).unwrap_or(0);
+        (rows.start.min(self.n_rows())..rows.end.min(self.n_rows()))
+            .map(|row| &fitsblocks_flat[row * row_length + offset..row * row_length + offset + width])
+            .collect()
+    }
+
+    /// Every element of a numerical column as `f64`, one vector per row, with TSCALn and
+    /// TZEROn applied. Returns `None` for character, bit, complex and descriptor columns.
+    pub fn column_f64(&self, column: usize) -> Option<Vec<Vec<f64>>> {
+        self.column_f64_rows(column, 0..self.n_rows())
+    }
+
+    /// Like [`BinaryTable::column_f64`] for a range of rows.
+    pub fn column_f64_rows(&self, column: usize, rows: Range<usize>) -> Option<Vec<Vec<f64>>> {
+        let (repeat, code) = BinaryField::parse_tform(&self.tformn[column])?;
+        if !"LBIJKED".contains(code) {
+            return None;
         }
+        let size = BinaryField::n_bits(code);
+        let (scale, zero) = (self.tscaln[column], self.tzeron[column]);
+        let values = self
+            .column_bytes_rows(column, rows)
+            .iter()
+            .map(|bytes| {
+                (0..repeat)
+                    .map(|i| {
+                        let field = BinaryField::new(&bytes[i * size..(i + 1) * size], code.to_string());
+                        zero + scale * field.to_f64().unwrap()
+                    })
+                    .collect()
+            })
+            .collect();
+        Some(values)
+    }
+
+    /// Character column as trimmed strings, one per row.
+    pub fn column_strings(&self, column: usize) -> Option<Vec<String>> {
+        self.column_strings_rows(column, 0..self.n_rows())
+    }
+
+    /// Like [`BinaryTable::column_strings`] for a range of rows.
+    pub fn column_strings_rows(&self, column: usize, rows: Range<usize>) -> Option<Vec<String>> {
+        let (_, code) = BinaryField::parse_tform(&self.tformn[column])?;
+        if code != 'A' {
+            return None;
+        }
+        let values = self
+            .column_bytes_rows(column, rows)
+            .iter()
+            .map(|bytes| {
+                let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
+                String::from_utf8_lossy(&bytes[..end]).trim_end().to_string()
+            })
+            .collect();
+        Some(values)
     }
 
     pub fn n_bits(&self) -> u32 {
-        (self.bitpix.abs() as u32)
+        (self.bitpix.unsigned_abs() as u32)
             * self.gcount
             * (self.pcount + self.naxisn.iter().product::<u32>())
     }
@@ -317,20 +557,29 @@ impl BinaryTable {
         let mut result: Vec<BinaryField> = Vec::new();
         let local_data = data.to_vec();
         let mut cursor: usize = 0;
-        for i in 0..self.tfields - 1 {
-            let length = self.tformn[i as usize].chars().nth(0).unwrap().to_digit(10).unwrap() as usize;
-            let format = self.tformn[i as usize].chars().nth(1).unwrap();
-            result.push(BinaryField::new(
-                &local_data[cursor..cursor + length*BinaryField::n_bits(format)],
-                format.to_string(),
-            ));
-            cursor += length;
+        for tform in self.tformn.iter() {
+            let (_, format) = BinaryField::parse_tform(tform).unwrap_or((0, 'A'));
+            let width = BinaryField::field_width(tform).unwrap_or(0);
+            let field = &local_data[cursor..cursor + width];
+            // Bit fields are decoded one byte at a time
+            let size = if format == 'X' { 1 } else { BinaryField::n_bits(format) };
+            if width == size {
+                result.push(BinaryField::new(field, format.to_string()));
+            } else {
+                result.push(BinaryField::Vector(
+                    field
+                        .chunks_exact(size)
+                        .map(|element| BinaryField::new(element, format.to_string()))
+                        .collect(),
+                ));
+            }
+            cursor += width;
         }
         result
     }
 
     pub fn format_data(&self) -> Matrix2D<BinaryField> {
-        let fitsblocks_flat: Vec<u8> = self.fitsblocks.iter().flatten().cloned().collect();
+        let fitsblocks_flat: &[u8] = self.fitsblocks.as_flattened();
         let row_length: u32 = self.naxisn[0];
         let n_row: u32 = self.naxisn[1];
         let n_field: u32 = self.tfields;
@@ -0,0 +1,102 @@
+use crate::data::endian::{BeF32, BeF64, BeI16, BeI32, BeI64, BeU8, BigEndianScalar};
+use crate::data::data::Precision;
+use crate::data::section::Section;
+use ndarray::{Array, ArrayView, IxDyn};
+use std::ops::Range;
+
+/// Borrowed view of the bytes of an image data section.
+///
+/// Nothing is decoded until values are requested, so a view over a memory-mapped file
+/// can be sliced without copying the image. The shape follows the same axis order as
+/// [`ArrayData::format_data`](crate::data::array::ArrayData::format_data).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageView<'a> {
+    bytes: &'a [u8],
+    bitpix: i8,
+    naxisn: &'a [usize],
+}
+
+impl<'a> ImageView<'a> {
+    pub fn new(bytes: &'a [u8], bitpix: i8, naxisn: &'a [usize]) -> ImageView<'a> {
+        ImageView {
+            bytes,
+            bitpix,
+            naxisn,
+        }
+    }
+
+    pub fn get_bitpix(&self) -> i8 {
+        self.bitpix
+    }
+
+    pub fn get_naxisn(&self) -> &'a [usize] {
+        self.naxisn
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn n_entries(&self) -> usize {
+        if self.naxisn.is_empty() {
+            return 0;
+        }
+        self.naxisn.iter().product::<usize>()
+    }
+
+    /// View the data as an array of big-endian values without copying.
+    ///
+    /// Returns `None` if `T` does not match BITPIX or the bytes are too short for the shape.
+    pub fn view<T: BigEndianScalar>(&self) -> Option<ArrayView<'a, T, IxDyn>> {
+        if T::BITPIX != self.bitpix {
+            return None;
+        }
+        let values = T::cast_slice(self.bytes);
+        if values.len() < self.n_entries() {
+            return None;
+        }
+        ArrayView::from_shape(IxDyn(self.naxisn), &values[..self.n_entries()]).ok()
+    }
+
+    /// Decode the value at a flat (row-major) index.
+    pub fn get_f64(&self, index: usize) -> f64 {
+        let size = (self.bitpix.unsigned_abs() / 8) as usize;
+        let bytes = &self.bytes[index * size..(index + 1) * size];
+        match self.bitpix {
+            8 => BeU8::cast_slice(bytes)[0].to_f64(),
+            16 => BeI16::cast_slice(bytes)[0].to_f64(),
+            32 => BeI32::cast_slice(bytes)[0].to_f64(),
+            64 => BeI64::cast_slice(bytes)[0].to_f64(),
+            -32 => BeF32::cast_slice(bytes)[0].to_f64(),
+            -64 => BeF64::cast_slice(bytes)[0].to_f64(),
+            _ => panic!("Unsupported bitpix value: {}", self.bitpix),
+        }
+    }
+
+    /// Decode the whole image into native `f64` values.
+    ///
+    /// Returns `None` if BITPIX is not a valid value or the bytes are too short for the shape.
+    pub fn to_f64(&self) -> Option<Array<f64, IxDyn>> {
+        fn convert<T: BigEndianScalar>(view: &ImageView) -> Option<Array<f64, IxDyn>> {
+            Some(view.view::<T>()?.map(|value| value.to_f64()))
+        }
+        match self.bitpix {
+            8 => convert::<BeU8>(self),
+            16 => convert::<BeI16>(self),
+            32 => convert::<BeI32>(self),
+            64 => convert::<BeI64>(self),
+            -32 => convert::<BeF32>(self),
+            -64 => convert::<BeF64>(self),
+            _ => None,
+        }
+    }
+
+    /// Decode only the pixels inside `ranges`. See [`ArrayData::read_section`](crate::data::array::ArrayData::read_section).
+    pub fn read_section(&self, ranges: &[Range<usize>]) -> Result<Array<Precision, IxDyn>, String> {
+        self.read_section_strided(ranges, &vec![1; ranges.len()])
+    }
+
+    pub fn read_section_strided(&self, ranges: &[Range<usize>], steps: &[usize]) -> Result<Array<Precision, IxDyn>, String> {
+        Section::new(self.naxisn, ranges, steps)?.extract(self.bytes, self.bitpix)
+    }
+}
@@ -0,0 +1,514 @@
+//! Comparison of two FITS files, HDU by HDU, in the spirit of astropy's `fitsdiff`.
+
+use crate::data::data::Data;
+use crate::data::view::ImageView;
+use crate::data::tables::{ASCIITable, BinaryTable};
+use crate::fits::{FITS, HDU};
+use crate::header::Card;
+use crate::info::glob_match;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffOptions {
+    /// Keyword globs left out of the header comparison, e.g. `DATE`, `CHECKSUM`.
+    pub ignore_keywords: Vec<String>,
+    pub ignore_comments: bool,
+    /// Two values are equal when `|a - b| <= atol + rtol * |b|`.
+    pub rtol: f64,
+    pub atol: f64,
+    /// Number of differing pixels or table cells listed per HDU or column.
+    pub max_reported: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            ignore_keywords: Vec::new(),
+            ignore_comments: false,
+            rtol: 0.0,
+            atol: 0.0,
+            max_reported: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CardDifference {
+    OnlyInA { keyword: String, value: String },
+    OnlyInB { keyword: String, value: String },
+    Value { keyword: String, a: String, b: String },
+    Comment { keyword: String, a: String, b: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataDifference {
+    /// The HDUs hold different kinds of data (image, tables, ...).
+    Type { a: String, b: String },
+    Shape { a: Vec<usize>, b: Vec<usize> },
+    /// Pixels outside the tolerance, with examples as (1-based pixel, a, b).
+    Pixels {
+        count: usize,
+        total: usize,
+        max_absolute: f64,
+        max_relative: f64,
+        examples: Vec<(Vec<usize>, f64, f64)>,
+    },
+    Rows { a: usize, b: usize },
+    ColumnOnlyInA(String),
+    ColumnOnlyInB(String),
+    ColumnFormat { column: String, a: String, b: String },
+    /// Rows of a column outside the tolerance, with examples as (0-based row, a, b).
+    Column {
+        column: String,
+        count: usize,
+        examples: Vec<(usize, String, String)>,
+    },
+    /// Data of other extension types differs in this many bytes.
+    Bytes { count: usize },
+    /// The image data is shorter than its header declares in at least one file, as (bytes
+    /// present, bytes expected) for each. The pixels are not compared.
+    Truncated { a: (usize, usize), b: (usize, usize) },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HduReport {
+    pub index: usize,
+    pub header: Vec<CardDifference>,
+    pub data: Vec<DataDifference>,
+}
+
+impl HduReport {
+    pub fn is_identical(&self) -> bool {
+        self.header.is_empty() && self.data.is_empty()
+    }
+}
+
+/// Differences between two files. Only HDUs with differences are listed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub hdu_counts: (usize, usize),
+    pub hdus: Vec<HduReport>,
+}
+
+impl Report {
+    pub fn is_identical(&self) -> bool {
+        self.hdu_counts.0 == self.hdu_counts.1 && self.hdus.is_empty()
+    }
+}
+
+/// Compare the HDUs the two files have in common.
+pub fn compare(a: &FITS, b: &FITS, options: &DiffOptions) -> Report {
+    let hdus = a
+        .hdus
+        .iter()
+        .zip(b.hdus.iter())
+        .enumerate()
+        .map(|(index, (a, b))| compare_hdu(index, a, b, options))
+        .filter(|report| !report.is_identical())
+        .collect();
+    Report {
+        hdu_counts: (a.hdus.len(), b.hdus.len()),
+        hdus,
+    }
+}
+
+/// Compare the header and data of two HDUs.
+pub fn compare_hdu(index: usize, a: &HDU, b: &HDU, options: &DiffOptions) -> HduReport {
+    HduReport {
+        index,
+        header: compare_headers(&a.header.cards(), &b.header.cards(), options),
+        data: compare_data(a, b, options),
+    }
+}
+
+/// Cards keyed by keyword, with repeated keywords (COMMENT, HISTORY, ...) numbered by
+/// occurrence.
+fn keyed_cards(cards: &[String], options: &DiffOptions) -> Vec<(String, Card)> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    cards
+        .iter()
+        .map(|card| Card::parse(card))
+        .filter(|card| !options.ignore_keywords.iter().any(|pattern| glob_match(pattern, &card.keyword)))
+        .filter(|card| !(card.keyword.is_empty() && card.comment.is_empty()))
+        .map(|card| {
+            let count = seen.entry(card.keyword.clone()).or_insert(0);
+            *count += 1;
+            let key = if *count == 1 {
+                card.keyword.clone()
+            } else {
+                format!("{} ({})", card.keyword, count)
+            };
+            (key, card)
+        })
+        .collect()
+}
+
+/// Value of a card for comparison. Commentary cards are compared on their text.
+fn card_value(card: &Card) -> String {
+    card.value_text().unwrap_or_else(|| card.comment.trim().to_string())
+}
+
+fn same_value(a: &str, b: &str) -> bool {
+    let parse = |text: &str| text.replace(['D', 'd'], "E").parse::<f64>().ok();
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Card differences between two headers, given as 80-character cards.
+pub fn compare_headers(a: &[String], b: &[String], options: &DiffOptions) -> Vec<CardDifference> {
+    let a = keyed_cards(a, options);
+    let b = keyed_cards(b, options);
+    let b_map: HashMap<&str, &Card> = b.iter().map(|(key, card)| (key.as_str(), card)).collect();
+    let a_map: HashMap<&str, &Card> = a.iter().map(|(key, card)| (key.as_str(), card)).collect();
+    let mut differences = Vec::new();
+    for (key, card_a) in a.iter() {
+        match b_map.get(key.as_str()) {
+            None => differences.push(CardDifference::OnlyInA {
+                keyword: key.clone(),
+                value: card_value(card_a),
+            }),
+            Some(card_b) => {
+                let (value_a, value_b) = (card_value(card_a), card_value(card_b));
+                if !same_value(&value_a, &value_b) {
+                    differences.push(CardDifference::Value {
+                        keyword: key.clone(),
+                        a: value_a,
+                        b: value_b,
+                    });
+                } else if !options.ignore_comments && card_a.value.is_some() && card_a.comment != card_b.comment {
+                    differences.push(CardDifference::Comment {
+                        keyword: key.clone(),
+                        a: card_a.comment.clone(),
+                        b: card_b.comment.clone(),
+                    });
+                }
+            }
+        }
+    }
+    for (key, card_b) in b.iter().filter(|(key, _)| !a_map.contains_key(key.as_str())) {
+        differences.push(CardDifference::OnlyInB {
+            keyword: key.clone(),
+            value: card_value(card_b),
+        });
+    }
+    differences
+}
+
+fn close(a: f64, b: f64, options: &DiffOptions) -> bool {
+    a == b || (a.is_nan() && b.is_nan()) || (a - b).abs() <= options.atol + options.rtol * b.abs()
+}
+
+fn data_kind(data: &Data) -> &'static str {
+    match data {
+        Data::Empty(_) => "empty",
+        Data::Array(_) => "image",
+        Data::ASCIITable(_) => "ASCII table",
+        Data::BinaryTable(_) => "binary table",
+        Data::Raw(_) => "raw",
+    }
+}
+
+fn compare_data(a: &HDU, b: &HDU, options: &DiffOptions) -> Vec<DataDifference> {
+    match (&a.data, &b.data) {
+        (Data::Array(_), Data::Array(_)) => compare_images(a, b, options),
+        (Data::BinaryTable(table_a), Data::BinaryTable(table_b)) => {
+            compare_tables(&TableColumns::Binary(table_a), &TableColumns::Binary(table_b), options)
+        }
+        (Data::ASCIITable(table_a), Data::ASCIITable(table_b)) => {
+            compare_tables(&TableColumns::Ascii(table_a), &TableColumns::Ascii(table_b), options)
+        }
+        (Data::Empty(_), Data::Empty(_)) => Vec::new(),
+        (Data::Raw(raw_a), Data::Raw(raw_b)) => {
+            let (bytes_a, bytes_b) = (raw_a.bytes(), raw_b.bytes());
+            let count = bytes_a.iter().zip(bytes_b.iter()).filter(|(a, b)| a != b).count()
+                + bytes_a.len().abs_diff(bytes_b.len());
+            if count > 0 {
+                vec![DataDifference::Bytes { count }]
+            } else {
+                Vec::new()
+            }
+        }
+        (data_a, data_b) => vec![DataDifference::Type {
+            a: data_kind(data_a).to_string(),
+            b: data_kind(data_b).to_string(),
+        }],
+    }
+}
+
+fn compare_images(a: &HDU, b: &HDU, options: &DiffOptions) -> Vec<DataDifference> {
+    let (array_a, array_b) = match (&a.data, &b.data) {
+        (Data::Array(array_a), Data::Array(array_b)) => (array_a, array_b),
+        _ => return Vec::new(),
+    };
+    if array_a.get_naxisn() != array_b.get_naxisn() {
+        return vec![DataDifference::Shape {
+            a: array_a.get_naxisn().clone(),
+            b: array_b.get_naxisn().clone(),
+        }];
+    }
+    let naxisn = array_a.get_naxisn();
+    let (view_a, view_b) = (array_a.view(), array_b.view());
+    let sizes = |view: &ImageView| {
+        let expected = view.n_entries() * (view.get_bitpix().unsigned_abs() / 8) as usize;
+        (view.bytes().len(), expected)
+    };
+    let (sizes_a, sizes_b) = (sizes(&view_a), sizes(&view_b));
+    if sizes_a.0 < sizes_a.1 || sizes_b.0 < sizes_b.1 {
+        return vec![DataDifference::Truncated { a: sizes_a, b: sizes_b }];
+    }
+    let scaling = |hdu: &HDU| {
+        (
+            hdu.header.get_f64("BSCALE").unwrap_or(1.0),
+            hdu.header.get_f64("BZERO").unwrap_or(0.0),
+        )
+    };
+    let ((scale_a, zero_a), (scale_b, zero_b)) = (scaling(a), scaling(b));
+    let total = view_a.n_entries().min(view_b.n_entries());
+    let (mut count, mut max_absolute, mut max_relative) = (0, 0.0f64, 0.0f64);
+    let mut examples = Vec::new();
+    for index in 0..total {
+        let value_a = zero_a + scale_a * view_a.get_f64(index);
+        let value_b = zero_b + scale_b * view_b.get_f64(index);
+        if close(value_a, value_b, options) {
+            continue;
+        }
+        count += 1;
+        let absolute = (value_a - value_b).abs();
+        max_absolute = max_absolute.max(absolute);
+        if value_b != 0.0 {
+            max_relative = max_relative.max(absolute / value_b.abs());
+        }
+        if examples.len() < options.max_reported {
+            examples.push((pixel_coordinates(index, naxisn), value_a, value_b));
+        }
+    }
+    if count == 0 {
+        return Vec::new();
+    }
+    vec![DataDifference::Pixels {
+        count,
+        total,
+        max_absolute,
+        max_relative,
+        examples,
+    }]
+}
+
+/// 1-based pixel coordinates of a flat index, NAXIS1 first.
+fn pixel_coordinates(mut index: usize, naxisn: &[usize]) -> Vec<usize> {
+    naxisn
+        .iter()
+        .map(|n| {
+            let coordinate = index % n.max(&1) + 1;
+            index /= n.max(&1);
+            coordinate
+        })
+        .collect()
+}
+
+enum TableColumns<'a> {
+    Binary(&'a BinaryTable),
+    Ascii(&'a ASCIITable),
+}
+
+enum Cells {
+    Numbers(Vec<Vec<f64>>),
+    Text(Vec<String>),
+    Bytes(Vec<Vec<u8>>),
+}
+
+impl TableColumns<'_> {
+    fn names(&self) -> &Vec<String> {
+        match self {
+            TableColumns::Binary(table) => table.column_names(),
+            TableColumns::Ascii(table) => table.column_names(),
+        }
+    }
+
+    fn formats(&self) -> &Vec<String> {
+        match self {
+            TableColumns::Binary(table) => table.get_tformn(),
+            TableColumns::Ascii(table) => table.get_tformn(),
+        }
+    }
+
+    fn n_rows(&self) -> usize {
+        match self {
+            TableColumns::Binary(table) => table.n_rows(),
+            TableColumns::Ascii(table) => table.n_rows(),
+        }
+    }
+
+    fn cells(&self, column: usize, rows: usize) -> Cells {
+        match self {
+            TableColumns::Binary(table) => {
+                if let Some(numbers) = table.column_f64_rows(column, 0..rows) {
+                    Cells::Numbers(numbers)
+                } else if let Some(text) = table.column_strings_rows(column, 0..rows) {
+                    Cells::Text(text)
+                } else {
+                    Cells::Bytes(table.column_bytes_rows(column, 0..rows).iter().map(|bytes| bytes.to_vec()).collect())
+                }
+            }
+            TableColumns::Ascii(table) => match table.column_f64_rows(column, 0..rows) {
+                Some(numbers) => Cells::Numbers(numbers.into_iter().map(|value| vec![value]).collect()),
+                None => Cells::Text(table.column_strings_rows(column, 0..rows)),
+            },
+        }
+    }
+}
+
+fn format_numbers(values: &[f64]) -> String {
+    match values {
+        [value] => value.to_string(),
+        _ => format!("{:?}", values),
+    }
+}
+
+fn compare_tables(a: &TableColumns, b: &TableColumns, options: &DiffOptions) -> Vec<DataDifference> {
+    let mut differences = Vec::new();
+    let (rows_a, rows_b) = (a.n_rows(), b.n_rows());
+    if rows_a != rows_b {
+        differences.push(DataDifference::Rows { a: rows_a, b: rows_b });
+    }
+    let rows = rows_a.min(rows_b);
+    for name in a.names().iter().filter(|name| !b.names().contains(name)) {
+        differences.push(DataDifference::ColumnOnlyInA(name.clone()));
+    }
+    for name in b.names().iter().filter(|name| !a.names().contains(name)) {
+        differences.push(DataDifference::ColumnOnlyInB(name.clone()));
+    }
+    for (column_a, name) in a.names().iter().enumerate() {
+        let column_b = match b.names().iter().position(|other| other == name) {
+            Some(column_b) => column_b,
+            None => continue,
+        };
+        let (format_a, format_b) = (&a.formats()[column_a], &b.formats()[column_b]);
+        let rows_differing: Vec<(usize, String, String)> = match (a.cells(column_a, rows), b.cells(column_b, rows)) {
+            (Cells::Numbers(values_a), Cells::Numbers(values_b)) => values_a
+                .iter()
+                .zip(values_b.iter())
+                .enumerate()
+                .filter(|(_, (row_a, row_b))| {
+                    row_a.len() != row_b.len()
+                        || row_a.iter().zip(row_b.iter()).any(|(a, b)| !close(*a, *b, options))
+                })
+                .map(|(row, (row_a, row_b))| (row, format_numbers(row_a), format_numbers(row_b)))
+                .collect(),
+            (Cells::Text(values_a), Cells::Text(values_b)) => values_a
+                .into_iter()
+                .zip(values_b)
+                .enumerate()
+                .filter(|(_, (a, b))| a != b)
+                .map(|(row, (a, b))| (row, a, b))
+                .collect(),
+            (Cells::Bytes(values_a), Cells::Bytes(values_b)) => values_a
+                .into_iter()
+                .zip(values_b)
+                .enumerate()
+                .filter(|(_, (a, b))| a != b)
+                .map(|(row, (a, b))| (row, format!("{:?}", a), format!("{:?}", b)))
+                .collect(),
+            _ => {
+                differences.push(DataDifference::ColumnFormat {
+                    column: name.clone(),
+                    a: format_a.clone(),
+                    b: format_b.clone(),
+                });
+                continue;
+            }
+        };
+        if !rows_differing.is_empty() {
+            differences.push(DataDifference::Column {
+                column: name.clone(),
+                count: rows_differing.len(),
+                examples: rows_differing.into_iter().take(options.max_reported).collect(),
+            });
+        }
+    }
+    differences
+}
+
+impl fmt::Display for CardDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CardDifference::OnlyInA { keyword, value } => write!(f, "Keyword {} only in a: {}", keyword, value),
+            CardDifference::OnlyInB { keyword, value } => write!(f, "Keyword {} only in b: {}", keyword, value),
+            CardDifference::Value { keyword, a, b } => {
+                write!(f, "Keyword {} has different values:\n    a> {}\n    b> {}", keyword, a, b)
+            }
+            CardDifference::Comment { keyword, a, b } => {
+                write!(f, "Keyword {} has different comments:\n    a> {}\n    b> {}", keyword, a, b)
+            }
+        }
+    }
+}
+
+impl fmt::Display for DataDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataDifference::Type { a, b } => write!(f, "Data types differ: {} vs {}", a, b),
+            DataDifference::Shape { a, b } => write!(f, "Image dimensions differ: {:?} vs {:?}", a, b),
+            DataDifference::Pixels {
+                count,
+                total,
+                max_absolute,
+                max_relative,
+                examples,
+            } => {
+                write!(
+                    f,
+                    "{} of {} pixels differ (maximum absolute difference {}, maximum relative difference {})",
+                    count, total, max_absolute, max_relative
+                )?;
+                for (pixel, a, b) in examples {
+                    write!(f, "\n    at {:?}: {} vs {}", pixel, a, b)?;
+                }
+                Ok(())
+            }
+            DataDifference::Rows { a, b } => write!(f, "Row counts differ: {} vs {}", a, b),
+            DataDifference::ColumnOnlyInA(name) => write!(f, "Column {} only in a", name),
+            DataDifference::ColumnOnlyInB(name) => write!(f, "Column {} only in b", name),
+            DataDifference::ColumnFormat { column, a, b } => {
+                write!(f, "Column {} has different formats: {} vs {}", column, a, b)
+            }
+            DataDifference::Column { column, count, examples } => {
+                write!(f, "Column {}: {} rows differ", column, count)?;
+                for (row, a, b) in examples {
+                    write!(f, "\n    row {}: {} vs {}", row, a, b)?;
+                }
+                Ok(())
+            }
+            DataDifference::Bytes { count } => write!(f, "{} data bytes differ", count),
+            DataDifference::Truncated { a, b } => write!(
+                f,
+                "Image data truncated: {} of {} bytes in a, {} of {} bytes in b",
+                a.0, a.1, b.0, b.1
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_identical() {
+            return writeln!(f, "No differences found.");
+        }
+        if self.hdu_counts.0 != self.hdu_counts.1 {
+            writeln!(f, "Files have different numbers of HDUs: {} vs {}", self.hdu_counts.0, self.hdu_counts.1)?;
+        }
+        for hdu in &self.hdus {
+            writeln!(f, "HDU {}:", hdu.index)?;
+            for difference in &hdu.header {
+                writeln!(f, "  {}", difference)?;
+            }
+            for difference in &hdu.data {
+                writeln!(f, "  {}", difference)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! PNG and JPEG previews of image HDUs, built on [`crate::render`].
+
+use crate::fits::HDU;
+use crate::render::{image_plane, intensities, lupton_rgb, render, LuptonOptions, RenderOptions, RgbaImage};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct PngOptions {
+    pub render: RenderOptions,
+    /// Write a 16-bit grayscale PNG of the stretched intensities instead of 8-bit RGBA.
+    /// The colormap is ignored.
+    pub sixteen_bit: bool,
+}
+
+/// Write a PNG preview of the first plane of an image HDU.
+pub fn write_png<P: AsRef<Path>>(path: P, hdu: &HDU, options: &PngOptions) -> io::Result<()> {
+    let bytes = encode_png(hdu, options).map_err(invalid_data)?;
+    write_file(path, &bytes)
+}
+
+/// PNG preview of the first plane of an image HDU, in memory.
+pub fn encode_png(hdu: &HDU, options: &PngOptions) -> Result<Vec<u8>, String> {
+    if !options.sixteen_bit {
+        return encode_rgba_png(&render(hdu, &options.render)?);
+    }
+    let (width, height, values) = image_plane(hdu)?;
+    let samples: Vec<u8> = intensities(&values, width, height, &options.render)
+        .iter()
+        .flat_map(|x| {
+            let x = if x.is_nan() { 0.0 } else { *x };
+            ((x * 65535.0).round() as u16).to_be_bytes()
+        })
+        .collect();
+    encode(width, height, png::ColorType::Grayscale, png::BitDepth::Sixteen, &samples)
+}
+
+/// Write a JPEG preview of the first plane of an image HDU. `quality` ranges from 1 to 100.
+pub fn write_jpeg<P: AsRef<Path>>(path: P, hdu: &HDU, options: &RenderOptions, quality: u8) -> io::Result<()> {
+    let bytes = encode_jpeg(&render(hdu, options).map_err(invalid_data)?, quality).map_err(invalid_data)?;
+    write_file(path, &bytes)
+}
+
+/// Write a Lupton asinh colour composite of three images as an 8-bit PNG.
+pub fn write_rgb_png<P: AsRef<Path>>(path: P, red: &HDU, green: &HDU, blue: &HDU, options: &LuptonOptions) -> io::Result<()> {
+    let image = lupton_rgb(red, green, blue, options).map_err(invalid_data)?;
+    write_file(path, &encode_rgba_png(&image).map_err(invalid_data)?)
+}
+
+pub fn encode_rgba_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    encode(image.width, image.height, png::ColorType::Rgba, png::BitDepth::Eight, &image.pixels)
+}
+
+/// JPEG of a rendered image. Transparent pixels become black.
+pub fn encode_jpeg(image: &RgbaImage, quality: u8) -> Result<Vec<u8>, String> {
+    let (width, height) = (dimension(image.width)?, dimension(image.height)?);
+    let mut bytes: Vec<u8> = Vec::new();
+    jpeg_encoder::Encoder::new(&mut bytes, quality.clamp(1, 100))
+        .encode(&image.pixels, width, height, jpeg_encoder::ColorType::Rgba)
+        .map_err(|error| error.to_string())?;
+    Ok(bytes)
+}
+
+fn encode(width: usize, height: usize, color: png::ColorType, depth: png::BitDepth, data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut bytes: Vec<u8> = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut bytes, width as u32, height as u32);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        let mut writer = encoder.write_header().map_err(|error| error.to_string())?;
+        writer.write_image_data(data).map_err(|error| error.to_string())?;
+    }
+    Ok(bytes)
+}
+
+fn dimension(length: usize) -> Result<u16, String> {
+    if length == 0 || length > u16::MAX as usize {
+        return Err(format!("JPEG images must be 1 to {} pixels wide and high", u16::MAX));
+    }
+    Ok(length as u16)
+}
+
+fn write_file<P: AsRef<Path>>(path: P, bytes: &[u8]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(bytes)?;
+    writer.flush()
+}
+
+fn invalid_data(error: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
@@ -0,0 +1,741 @@
+//! cfitsio-style extended filenames, e.g. `events.fits[EVENTS][PI > 100][col X;Y]` or
+//! `image.fits[1][100:200,300:400:2]`.
+//!
+//! The brackets after the file name select an HDU (by number, or by EXTNAME with an
+//! optional EXTVER), an image section (1-based, inclusive, with optional steps), a list of
+//! table columns (`col A;B`) and row filters. Several row filters must all hold.
+
+use crate::data::data::Data;
+use crate::data::section::Section;
+use crate::data::tables::{BinaryField, BinaryTable};
+use crate::fits::{find_extension, FITS, HDU};
+use crate::header::{Card, Header, HeaderType};
+use crate::reader::FitsReader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HduSelector {
+    /// 0-based HDU number, the primary HDU being 0.
+    Index(usize),
+    /// EXTNAME, compared without case, and EXTVER (1 when missing from the header).
+    Name { name: String, version: Option<i64> },
+}
+
+/// Pixel range of one image axis: 1-based, inclusive, with a step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisRange {
+    pub start: usize,
+    /// Last pixel, `None` for the end of the axis.
+    pub end: Option<usize>,
+    pub step: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendedFilename {
+    pub path: String,
+    pub hdu: Option<HduSelector>,
+    /// Ranges in axis order, NAXIS1 first.
+    pub section: Option<Vec<AxisRange>>,
+    pub columns: Option<Vec<String>>,
+    pub row_filter: Option<RowFilter>,
+}
+
+impl ExtendedFilename {
+    pub fn parse(text: &str) -> Result<ExtendedFilename, String> {
+        let text = text.trim();
+        let (path, groups) = match text.find('[') {
+            Some(index) => (&text[..index], bracket_groups(&text[index..])?),
+            None => (text, Vec::new()),
+        };
+        let mut filename = ExtendedFilename {
+            path: path.to_string(),
+            hdu: None,
+            section: None,
+            columns: None,
+            row_filter: None,
+        };
+        // `file.fits+2` is a shorthand for `file.fits[2]`
+        if let Some((base, number)) = path.rsplit_once('+') {
+            if let Ok(index) = number.parse::<usize>() {
+                filename.path = base.to_string();
+                filename.hdu = Some(HduSelector::Index(index));
+            }
+        }
+        if filename.path.is_empty() {
+            return Err(format!("No file name in {}", text));
+        }
+        let mut filters: Vec<String> = Vec::new();
+        for (i, group) in groups.iter().enumerate() {
+            let group = group.trim();
+            if i == 0 && filename.hdu.is_none() {
+                if let Some(hdu) = parse_hdu(group) {
+                    filename.hdu = Some(hdu);
+                    continue;
+                }
+            }
+            if group.len() > 4 && group.get(..4).is_some_and(|prefix| prefix.eq_ignore_ascii_case("col ")) {
+                let columns: Vec<String> = group[4..]
+                    .split([';', ','])
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect();
+                if columns.is_empty() {
+                    return Err(format!("No columns in [{}]", group));
+                }
+                filename.columns = Some(columns);
+            } else if is_section(group) {
+                filename.section = Some(parse_section(group)?);
+            } else if !group.is_empty() {
+                filters.push(format!("({})", group));
+            }
+        }
+        if !filters.is_empty() {
+            filename.row_filter = Some(RowFilter::parse(&filters.join(" && "))?);
+        }
+        if filename.section.is_some() && (filename.columns.is_some() || filename.row_filter.is_some()) {
+            return Err("An image section cannot be combined with column or row selection".to_string());
+        }
+        Ok(filename)
+    }
+
+    /// Open the file and apply the selection.
+    pub fn open(&self) -> io::Result<FITS> {
+        let mut reader = FITS::open(File::open(&self.path)?)?;
+        self.apply(&mut reader)
+            .map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))
+    }
+
+    /// Apply the selection to an open file.
+    ///
+    /// An image section gives a file with a single primary HDU holding the section. Column
+    /// and row selection give the whole file with the selected table filtered. Without
+    /// either, the whole file is returned.
+    pub fn apply<R: Read + Seek>(&self, reader: &mut FitsReader<R>) -> Result<FITS, String> {
+        let headers: Vec<&Header> = reader.entries().iter().map(|entry| &entry.header).collect();
+        let table = self.columns.is_some() || self.row_filter.is_some();
+        let index = match &self.hdu {
+            Some(selector) => Some(find_hdu(&headers, selector)?),
+            None if self.section.is_some() => Some(
+                headers
+                    .iter()
+                    .position(|header| {
+                        matches!(header.get_header_type(), HeaderType::Primary | HeaderType::Image)
+                            && !header.get_naxisn().is_empty()
+                    })
+                    .ok_or("The file has no image HDU")?,
+            ),
+            None if table => Some(
+                headers
+                    .iter()
+                    .position(|header| header.get_header_type() == HeaderType::BinaryTable)
+                    .ok_or("The file has no binary table HDU")?,
+            ),
+            None => None,
+        };
+        let error = |err: io::Error| err.to_string();
+        if let (Some(index), Some(ranges)) = (index, &self.section) {
+            let hdu = image_section(reader, index, ranges)?;
+            let mut fits = FITS::new();
+            fits.hdus.push(hdu);
+            return Ok(fits);
+        }
+        let mut fits = reader.read_all().map_err(error)?;
+        if let (Some(index), true) = (index, table) {
+            let filtered = filter_table(&fits.hdus[index], self.columns.as_deref(), self.row_filter.as_ref())?;
+            fits.hdus[index] = filtered;
+        }
+        Ok(fits)
+    }
+}
+
+/// Split `[a][b]...` into the contents of each bracket pair.
+fn bracket_groups(text: &str) -> Result<Vec<String>, String> {
+    let mut groups = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in text.chars() {
+        match c {
+            '[' => {
+                if depth > 0 {
+                    current.push(c);
+                }
+                depth += 1;
+            }
+            ']' if depth == 0 => return Err(format!("Unbalanced ']' in {}", text)),
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    groups.push(std::mem::take(&mut current));
+                } else {
+                    current.push(c);
+                }
+            }
+            c if depth == 0 && !c.is_whitespace() => {
+                return Err(format!("Unexpected '{}' between brackets in {}", c, text));
+            }
+            c if depth > 0 => current.push(c),
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(format!("Missing ']' in {}", text));
+    }
+    Ok(groups)
+}
+
+/// `2`, `EVENTS` or `SCI, 2`.
+fn parse_hdu(group: &str) -> Option<HduSelector> {
+    if let Ok(index) = group.parse::<usize>() {
+        return Some(HduSelector::Index(index));
+    }
+    let (name, version) = match group.split_once(',') {
+        Some((name, version)) => (name.trim(), Some(version.trim().parse::<i64>().ok()?)),
+        None => (group, None),
+    };
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Some(HduSelector::Name {
+            name: name.to_string(),
+            version,
+        })
+    } else {
+        None
+    }
+}
+
+fn is_section(group: &str) -> bool {
+    (group.contains(':') || group.contains('*'))
+        && group.chars().all(|c| c.is_ascii_digit() || " :,*-".contains(c))
+}
+
+/// `1:100, *, 5:50:2`, one comma-separated range per axis.
+fn parse_section(group: &str) -> Result<Vec<AxisRange>, String> {
+    group
+        .split(',')
+        .map(|axis| {
+            let parts: Vec<&str> = axis.split(':').map(|part| part.trim()).collect();
+            let number = |text: &str| {
+                text.parse::<usize>()
+                    .ok()
+                    .filter(|value| *value > 0)
+                    .ok_or_else(|| format!("Invalid pixel '{}' in section [{}]", text, group))
+            };
+            let (start, end, step) = match parts.as_slice() {
+                ["*"] => (1, None, "1"),
+                ["*", step] => (1, None, *step),
+                [start, end] => (number(start)?, Some(number(end)?), "1"),
+                [start, end, step] => (number(start)?, Some(number(end)?), *step),
+                _ => return Err(format!("Invalid axis '{}' in section [{}]", axis.trim(), group)),
+            };
+            if parts[0].starts_with('-') || end.is_some_and(|end| end < start) {
+                return Err(format!("Flipped axis '{}' is not supported", axis.trim()));
+            }
+            Ok(AxisRange {
+                start,
+                end,
+                step: number(step)?,
+            })
+        })
+        .collect()
+}
+
+fn find_hdu(headers: &[&Header], selector: &HduSelector) -> Result<usize, String> {
+    match selector {
+        HduSelector::Index(index) if *index < headers.len() => Ok(*index),
+        HduSelector::Index(index) => Err(format!("HDU {} does not exist, the file has {} HDUs", index, headers.len())),
+        HduSelector::Name { name, version } => find_extension(headers.iter().copied(), name, *version),
+    }
+}
+
+const STRUCTURAL: [&str; 7] = ["SIMPLE", "XTENSION", "EXTEND", "BITPIX", "PCOUNT", "GCOUNT", "TFIELDS"];
+
+/// Cards that describe the old layout or contents and must not be copied over.
+fn structural(keyword: &str) -> bool {
+    STRUCTURAL.contains(&keyword)
+        || keyword == "CHECKSUM"
+        || keyword == "DATASUM"
+        || (keyword.starts_with("NAXIS") && keyword[5..].chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Read an image section into a new primary HDU, rescaling CRPIX, CDELT and CD so the
+/// world coordinates of each pixel are unchanged.
+fn image_section<R: Read + Seek>(reader: &mut FitsReader<R>, index: usize, ranges: &[AxisRange]) -> Result<HDU, String> {
+    let header = reader.header(index).clone();
+    if !matches!(header.get_header_type(), HeaderType::Primary | HeaderType::Image) {
+        return Err(format!("HDU {} is of type {}, not an image", index, header.get_header_type()));
+    }
+    let naxisn = header.get_naxisn();
+    if ranges.len() != naxisn.len() {
+        return Err(format!("Section has {} axes but the image has {}", ranges.len(), naxisn.len()));
+    }
+    let bitpix = header.get_f64("BITPIX").unwrap_or(0.0) as i8;
+    let size = (bitpix.unsigned_abs() / 8) as usize;
+    // Sections run over the stored order, where NAXIS1 varies fastest
+    let bounds: Vec<std::ops::Range<usize>> = ranges
+        .iter()
+        .zip(naxisn.iter())
+        .map(|(range, n)| range.start - 1..range.end.unwrap_or(*n))
+        .collect();
+    let steps: Vec<usize> = ranges.iter().map(|range| range.step).collect();
+    let reversed = |values: &[usize]| values.iter().rev().cloned().collect::<Vec<usize>>();
+    let section = Section::new(
+        &reversed(&naxisn),
+        &bounds.iter().rev().cloned().collect::<Vec<_>>(),
+        &reversed(&steps),
+    )?;
+    let mut bytes: Vec<u8> = Vec::with_capacity(section.shape.iter().product::<usize>() * size);
+    for run in section.runs.iter() {
+        let run_bytes = reader
+            .read_data_bytes(index, (run.first * size) as u64, run.span() * size)
+            .map_err(|err| err.to_string())?;
+        for k in 0..run.count {
+            bytes.extend_from_slice(&run_bytes[k * run.step * size..(k * run.step + 1) * size]);
+        }
+    }
+
+    let shape = reversed(&section.shape);
+    let mut cards = vec![
+        Header::format_card("SIMPLE", "T"),
+        Header::format_card("BITPIX", &bitpix.to_string()),
+        Header::format_card("NAXIS", &shape.len().to_string()),
+    ];
+    for (i, n) in shape.iter().enumerate() {
+        cards.push(Header::format_card(&format!("NAXIS{}", i + 1), &n.to_string()));
+    }
+    for card in header.cards() {
+        let parsed = Card::parse(&card);
+        if structural(&parsed.keyword) {
+            continue;
+        }
+        let value = parsed.value_text().and_then(|value| value.replace(['D', 'd'], "E").parse::<f64>().ok());
+        match (value, section_scaling(&parsed.keyword, ranges)) {
+            (Some(value), Some(Scaling::Reference(start, step))) => cards.push(Header::format_card(
+                &parsed.keyword,
+                &Header::format_real((value - start as f64) / step as f64 + 1.0),
+            )),
+            (Some(value), Some(Scaling::Increment(step))) => cards.push(Header::format_card(
+                &parsed.keyword,
+                &Header::format_real(value * step as f64),
+            )),
+            _ => cards.push(card),
+        }
+    }
+    let header = Header::from_cards(&cards);
+    Ok(HDU {
+        data: Data::from_header(blocks(bytes, 0), &header),
+        header,
+    })
+}
+
+enum Scaling {
+    /// CRPIXi: first selected pixel and step of axis i.
+    Reference(usize, usize),
+    /// CDELTi and CDi_j: step of the pixel axis.
+    Increment(usize),
+}
+
+/// How a WCS keyword (primary or alternate) changes when taking a section.
+fn section_scaling(keyword: &str, ranges: &[AxisRange]) -> Option<Scaling> {
+    let axis = |text: &str| -> Option<usize> {
+        let digits: String = text.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let rest = &text[digits.len()..];
+        let valid_suffix = rest.is_empty() || (rest.len() == 1 && rest.chars().all(|c| c.is_ascii_uppercase()));
+        let axis = digits.parse::<usize>().ok()?;
+        (valid_suffix && axis >= 1 && axis <= ranges.len()).then_some(axis - 1)
+    };
+    if let Some(rest) = keyword.strip_prefix("CRPIX") {
+        let axis = axis(rest)?;
+        return Some(Scaling::Reference(ranges[axis].start, ranges[axis].step));
+    }
+    if let Some(rest) = keyword.strip_prefix("CDELT") {
+        return Some(Scaling::Increment(ranges[axis(rest)?].step));
+    }
+    if let Some((_, column)) = keyword.strip_prefix("CD").and_then(|rest| rest.split_once('_')) {
+        return Some(Scaling::Increment(ranges[axis(column)?].step));
+    }
+    None
+}
+
+/// Pad bytes into whole fitsblocks.
+fn blocks(mut bytes: Vec<u8>, fill: u8) -> Vec<[u8; 2880]> {
+    bytes.resize(bytes.len().div_ceil(2880) * 2880, fill);
+    bytes
+        .chunks_exact(2880)
+        .map(|chunk| {
+            let mut block = [0u8; 2880];
+            block.copy_from_slice(chunk);
+            block
+        })
+        .collect()
+}
+
+/// Column keywords with a single column number, optionally followed by an alternate
+/// description letter. Longer prefixes come first.
+const COLUMN_KEYWORDS: [&str; 28] = [
+    "TTYPE", "TFORM", "TUNIT", "TSCAL", "TZERO", "TNULL", "TDISP", "TDMIN", "TDMAX", "TLMIN", "TLMAX", "TBCOL",
+    "TCTYP", "TCUNI", "TCRPX", "TCRVL", "TCDLT", "TCROT", "TDIM", "TCTY", "TCUN", "TCRP", "TCRV", "TCDE", "WCSN",
+    "LONP", "LATP", "RADE",
+];
+
+/// New keyword for a column keyword after the columns are renumbered. `None` if the
+/// keyword is not a column keyword, `Some(None)` if its column is dropped.
+fn renumber(keyword: &str, map: &HashMap<usize, usize>) -> Option<Option<String>> {
+    // Two-index keywords: TCn_k and TPn_k relate two columns, TVn_m and TSn_m hold
+    // parameter m of column n
+    for prefix in ["TC", "TP", "TV", "TS"] {
+        let rest = match keyword.strip_prefix(prefix) {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let (first, second) = match rest.split_once('_') {
+            Some(split) => split,
+            None => continue,
+        };
+        let n = match first.parse::<usize>() {
+            Ok(n) => n,
+            Err(_) => continue,
+        };
+        let digits: String = second.chars().take_while(|c| c.is_ascii_digit()).collect();
+        if digits.is_empty() {
+            continue;
+        }
+        let new_n = match map.get(&n) {
+            Some(new_n) => *new_n,
+            None => return Some(None),
+        };
+        if prefix == "TC" || prefix == "TP" {
+            let k = digits.parse::<usize>().ok()?;
+            return Some(map.get(&k).map(|new_k| format!("{}{}_{}{}", prefix, new_n, new_k, &second[digits.len()..])));
+        }
+        return Some(Some(format!("{}{}_{}", prefix, new_n, second)));
+    }
+    for prefix in COLUMN_KEYWORDS {
+        let rest = match keyword.strip_prefix(prefix) {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let suffix = &rest[digits.len()..];
+        if digits.is_empty() || suffix.len() > 1 || !suffix.chars().all(|c| c.is_ascii_uppercase()) {
+            continue;
+        }
+        let n = digits.parse::<usize>().ok()?;
+        return Some(map.get(&n).map(|new_n| format!("{}{}{}", prefix, new_n, suffix)));
+    }
+    None
+}
+
+/// Keep the selected columns, in the given order, and the rows passing the filter.
+fn filter_table(hdu: &HDU, columns: Option<&[String]>, row_filter: Option<&RowFilter>) -> Result<HDU, String> {
+    let table = match &hdu.data {
+        Data::BinaryTable(table) => table,
+        _ => {
+            return Err(format!(
+                "Column and row selection need a binary table, the HDU is of type {}",
+                hdu.header.get_header_type()
+            ))
+        }
+    };
+    if hdu.header.get_f64("PCOUNT").unwrap_or(0.0) > 0.0 {
+        return Err("Tables with variable length arrays cannot be filtered".to_string());
+    }
+    let selected: Vec<usize> = match columns {
+        None => (0..table.column_names().len()).collect(),
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                table.column_index(name).ok_or_else(|| {
+                    format!("No column {}, the table has {}", name, table.column_names().join(", "))
+                })
+            })
+            .collect::<Result<Vec<usize>, String>>()?,
+    };
+    let rows: Vec<bool> = match row_filter {
+        Some(filter) => filter.evaluate(table)?,
+        None => vec![true; table.n_rows()],
+    };
+
+    let fields: Vec<Vec<&[u8]>> = selected.iter().map(|column| table.column_bytes(*column)).collect();
+    let row_length: usize = selected
+        .iter()
+        .map(|column| BinaryField::field_width(&table.get_tformn()[*column]).unwrap_or(0))
+        .sum();
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut n_rows = 0;
+    for (row, keep) in rows.iter().enumerate() {
+        if *keep {
+            n_rows += 1;
+            for field in fields.iter() {
+                bytes.extend_from_slice(field[row]);
+            }
+        }
+    }
+
+    // Column numbers in the header are 1-based
+    let map: HashMap<usize, usize> = selected.iter().enumerate().map(|(new, old)| (old + 1, new + 1)).collect();
+    let mut cards = vec![
+        "XTENSION= 'BINTABLE'           / binary table extension".to_string(),
+        Header::format_card("BITPIX", "8"),
+        Header::format_card("NAXIS", "2"),
+        Header::format_card("NAXIS1", &row_length.to_string()),
+        Header::format_card("NAXIS2", &n_rows.to_string()),
+        Header::format_card("PCOUNT", "0"),
+        Header::format_card("GCOUNT", "1"),
+        Header::format_card("TFIELDS", &selected.len().to_string()),
+    ];
+    let mut column_cards: Vec<(usize, String)> = Vec::new();
+    for card in hdu.header.cards() {
+        let keyword = Card::parse(&card).keyword;
+        if structural(&keyword) {
+            continue;
+        }
+        match renumber(&keyword, &map) {
+            None => cards.push(card),
+            Some(None) => {}
+            Some(Some(new_keyword)) => {
+                let column = new_keyword.trim_start_matches(|c: char| !c.is_ascii_digit());
+                let column: usize = column.chars().take_while(|c| c.is_ascii_digit()).collect::<String>().parse().unwrap_or(0);
+                column_cards.push((column, format!("{:<8}{}", new_keyword, &card[8..])));
+            }
+        }
+    }
+    // Keep the column keywords grouped by their new column number
+    column_cards.sort_by_key(|(column, _)| *column);
+    let position = cards.len().min(8);
+    cards.splice(position..position, column_cards.into_iter().map(|(_, card)| card));
+    let header = Header::from_cards(&cards);
+    Ok(HDU {
+        data: Data::from_header(blocks(bytes, 0), &header),
+        header,
+    })
+}
+
+/// Boolean expression over the numerical columns of a table, e.g. `PI > 100 && X < 5`.
+///
+/// Supports `+ - * /`, comparisons (`== != < <= > >=`, `=` meaning `==`), `&& || !`,
+/// parentheses, numbers, column names and `#ROW` (1-based row number).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowFilter {
+    text: String,
+    expression: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Number(f64),
+    Column(String),
+    Row,
+    Not(Box<Expression>),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, String, Box<Expression>),
+}
+
+impl RowFilter {
+    pub fn parse(text: &str) -> Result<RowFilter, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let expression = parser.or()?;
+        if parser.position != parser.tokens.len() {
+            return Err(format!("Unexpected '{}' in row filter {}", parser.tokens[parser.position], text));
+        }
+        Ok(RowFilter {
+            text: text.to_string(),
+            expression,
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether each row of the table passes the filter.
+    pub fn evaluate(&self, table: &BinaryTable) -> Result<Vec<bool>, String> {
+        let mut names: Vec<String> = Vec::new();
+        self.expression.columns(&mut names);
+        let mut values: HashMap<String, Vec<f64>> = HashMap::new();
+        for name in names {
+            let column = table.column_index(&name)
+                .ok_or_else(|| format!("No column {}, the table has {}", name, table.column_names().join(", ")))?;
+            let column_values = table
+                .column_f64(column)
+                .filter(|rows| rows.iter().all(|row| row.len() == 1))
+                .ok_or_else(|| format!("Column {} is not a scalar numerical column", name))?;
+            values.insert(name, column_values.into_iter().map(|row| row[0]).collect());
+        }
+        Ok((0..table.n_rows())
+            .map(|row| {
+                let value = self.expression.evaluate(row, &values);
+                value != 0.0 && !value.is_nan()
+            })
+            .collect())
+    }
+}
+
+impl Expression {
+    fn columns(&self, names: &mut Vec<String>) {
+        match self {
+            Expression::Column(name) if !names.contains(name) => names.push(name.clone()),
+            Expression::Not(inner) | Expression::Negate(inner) => inner.columns(names),
+            Expression::Binary(left, _, right) => {
+                left.columns(names);
+                right.columns(names);
+            }
+            _ => {}
+        }
+    }
+
+    fn evaluate(&self, row: usize, values: &HashMap<String, Vec<f64>>) -> f64 {
+        let truth = |value: bool| if value { 1.0 } else { 0.0 };
+        match self {
+            Expression::Number(value) => *value,
+            Expression::Column(name) => values[name][row],
+            Expression::Row => (row + 1) as f64,
+            Expression::Not(inner) => truth(inner.evaluate(row, values) == 0.0),
+            Expression::Negate(inner) => -inner.evaluate(row, values),
+            Expression::Binary(left, operator, right) => {
+                let (a, b) = (left.evaluate(row, values), right.evaluate(row, values));
+                match operator.as_str() {
+                    "+" => a + b,
+                    "-" => a - b,
+                    "*" => a * b,
+                    "/" => a / b,
+                    "==" => truth(a == b),
+                    "!=" => truth(a != b),
+                    "<" => truth(a < b),
+                    "<=" => truth(a <= b),
+                    ">" => truth(a > b),
+                    ">=" => truth(a >= b),
+                    "&&" => truth(a != 0.0 && b != 0.0),
+                    _ => truth(a != 0.0 || b != 0.0),
+                }
+            }
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<String>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_digit()
+                    || chars[i] == '.'
+                    || "eEdD".contains(chars[i])
+                    || ("+-".contains(chars[i]) && "eEdD".contains(chars[i - 1])))
+            {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else if c.is_ascii_alphabetic() || c == '_' || c == '#' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            let pair: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            if ["==", "!=", "<=", ">=", "&&", "||"].contains(&pair.as_str()) {
+                tokens.push(pair);
+                i += 2;
+            } else if "+-*/<>!()=".contains(c) {
+                tokens.push(if c == '=' { "==".to_string() } else { c.to_string() });
+                i += 1;
+            } else {
+                return Err(format!("Unexpected '{}' in row filter {}", c, text));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|token| token.as_str())
+    }
+
+    fn binary(&mut self, operators: &[&str], next: fn(&mut Parser) -> Result<Expression, String>) -> Result<Expression, String> {
+        let mut left = next(self)?;
+        while let Some(operator) = self.peek().filter(|token| operators.contains(token)).map(|token| token.to_string()) {
+            self.position += 1;
+            let right = next(self)?;
+            left = Expression::Binary(Box::new(left), operator, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expression, String> {
+        self.binary(&["||"], Parser::and)
+    }
+
+    fn and(&mut self) -> Result<Expression, String> {
+        self.binary(&["&&"], Parser::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expression, String> {
+        self.binary(&["==", "!=", "<", "<=", ">", ">="], Parser::sum)
+    }
+
+    fn sum(&mut self) -> Result<Expression, String> {
+        self.binary(&["+", "-"], Parser::product)
+    }
+
+    fn product(&mut self) -> Result<Expression, String> {
+        self.binary(&["*", "/"], Parser::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some("!") => {
+                self.position += 1;
+                Ok(Expression::Not(Box::new(self.unary()?)))
+            }
+            Some("-") => {
+                self.position += 1;
+                Ok(Expression::Negate(Box::new(self.unary()?)))
+            }
+            _ => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expression, String> {
+        let token = self.peek().ok_or("Row filter ends unexpectedly")?.to_string();
+        self.position += 1;
+        if token == "(" {
+            let inner = self.or()?;
+            if self.peek() != Some(")") {
+                return Err("Missing ')' in row filter".to_string());
+            }
+            self.position += 1;
+            return Ok(inner);
+        }
+        if token.eq_ignore_ascii_case("#ROW") {
+            return Ok(Expression::Row);
+        }
+        if token.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            return token
+                .replace(['D', 'd'], "E")
+                .parse::<f64>()
+                .map(Expression::Number)
+                .map_err(|_| format!("Invalid number {} in row filter", token));
+        }
+        if token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            return Ok(Expression::Column(token));
+        }
+        Err(format!("Unexpected '{}' in row filter", token))
+    }
+}
@@ -2,9 +2,12 @@
 
 use crate::header;
 use crate::data;
+use crate::checksum;
 
+use crate::reader::{scan_headers, FitsReader};
 use header::Header;
-use std::convert::TryInto;
+use std::fmt;
+use std::io::{self, Read, Seek, Write};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct HDU {
@@ -16,31 +19,215 @@ pub struct HDU {
 pub struct FITS {
     /// FITS file contains a vector of HDUs
     pub hdus: Vec<HDU>,
+    /// Problems found while splitting the file into HDUs
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Non-fatal problem found while reading a FITS file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Diagnostic {
+    /// The data section of an HDU is shorter than its header declares.
+    /// The missing bytes are zero-filled.
+    Truncated {
+        hdu: usize,
+        expected: usize,
+        available: usize,
+    },
+    /// The file ends before the END card of an HDU header.
+    MissingEnd { hdu: usize },
+    /// Bytes after the last HDU that do not form a valid header.
+    TrailingBytes { offset: usize, length: usize },
+    /// The DATASUM or CHECKSUM keyword of an HDU does not match its contents.
+    ChecksumMismatch { hdu: usize, keyword: String },
+    /// The data size given by BITPIX, NAXISn, PCOUNT and GCOUNT overflows. The data is
+    /// left empty and the rest of the file is not read.
+    InvalidDataSize { hdu: usize },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::Truncated {
+                hdu,
+                expected,
+                available,
+            } => write!(
+                f,
+                "HDU {}: data section truncated, expected {} bytes but only {} are available",
+                hdu, expected, available
+            ),
+            Diagnostic::MissingEnd { hdu } => {
+                write!(f, "HDU {}: file ends before the END card of the header", hdu)
+            }
+            Diagnostic::TrailingBytes { offset, length } => write!(
+                f,
+                "{} trailing bytes at offset {} do not form a valid HDU",
+                length, offset
+            ),
+            Diagnostic::ChecksumMismatch { hdu, keyword } => {
+                write!(f, "HDU {}: {} does not match the contents of the HDU", hdu, keyword)
+            }
+            Diagnostic::InvalidDataSize { hdu } => write!(
+                f,
+                "HDU {}: the data size given by BITPIX, NAXISn, PCOUNT and GCOUNT is too large",
+                hdu
+            ),
+        }
+    }
+}
+
+impl HDU {
+    /// Serialize the HDU back into fitsblocks, header first.
+    ///
+    /// The stored blocks are written as they were read, so HDUs of unknown extension
+    /// types round-trip byte-for-byte.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header_blocks = self.header.get_fitsblocks();
+        let data_blocks = self.data.get_fitsblocks();
+        let mut bytes: Vec<u8> = Vec::with_capacity((header_blocks.len() + data_blocks.len()) * 2880);
+        for block in header_blocks.iter().chain(data_blocks.iter()) {
+            bytes.extend_from_slice(block);
+        }
+        bytes
+    }
+
+    /// Check the DATASUM and CHECKSUM keywords against the contents of the HDU.
+    pub fn verify_checksum(&self) -> checksum::Verification {
+        checksum::verify(self)
+    }
+
+    /// Compute and write DATASUM and CHECKSUM into the header.
+    pub fn update_checksum(&mut self) {
+        checksum::update(self)
+    }
+}
+
+/// Position of the first header whose EXTNAME matches `name` without case and, if given,
+/// whose EXTVER is `version`. The error lists the named HDUs of the file.
+pub(crate) fn find_extension<'a>(
+    headers: impl IntoIterator<Item = &'a Header>,
+    name: &str,
+    version: Option<i64>,
+) -> Result<usize, String> {
+    let mut available: Vec<String> = Vec::new();
+    for (index, header) in headers.into_iter().enumerate() {
+        let extname = match header.get_extname() {
+            Some(extname) => extname,
+            None => continue,
+        };
+        if extname.eq_ignore_ascii_case(name) && version.is_none_or(|version| header.get_extver() == version) {
+            return Ok(index);
+        }
+        available.push(format!("{} (EXTVER {})", extname, header.get_extver()));
+    }
+    let wanted = match version {
+        Some(version) => format!("{} with EXTVER {}", name, version),
+        None => name.to_string(),
+    };
+    if available.is_empty() {
+        Err(format!("No HDU named {}, the file has no named HDUs", wanted))
+    } else {
+        Err(format!("No HDU named {}, the file has {}", wanted, available.join(", ")))
+    }
+}
+
+impl Default for FITS {
+    fn default() -> Self {
+        FITS::new()
+    }
 }
 
 impl FITS{
 
     pub fn new() -> FITS {
-        FITS { hdus: Vec::new() }
-    }
-
-    pub fn new_from_buffer(buffer: &Vec<u8>) -> FITS {
-        /// Create a FITS file from a buffer of bytes
-        /// 
-        /// # Arguments
-        /// 
-        /// * `buffer` - A vector of bytes
-        /// 
-        /// # Example
-        let mut hdus = FITS::bytes_to_hdu(&buffer);
-        for i in 0..hdus.len() {
-            hdus[i].header.initialize_header();
-            hdus[i].data = data::data::Data::from_header(hdus[i].data.get_fitsblocks(), &hdus[i].header)
+        FITS {
+            hdus: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Create a FITS file from a buffer of bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A vector of bytes
+    pub fn new_from_buffer(buffer: &[u8]) -> FITS {
+        let (mut hdus, diagnostics) = FITS::segment(buffer);
+        for hdu in hdus.iter_mut() {
+            let fitsblocks = std::mem::take(&mut hdu.data).into_fitsblocks();
+            hdu.data = data::data::Data::from_header(fitsblocks, &hdu.header)
+        }
+        let mut fits = FITS { hdus, diagnostics };
+        fits.diagnostics.extend(fits.checksum_diagnostics());
+        fits
+    }
+
+    /// Open a seekable source lazily. Only the headers are read; the data of each HDU is
+    /// read on demand through the returned [`FitsReader`].
+    pub fn open<R: Read + Seek>(reader: R) -> io::Result<FitsReader<R>> {
+        FitsReader::new(reader)
+    }
+
+    /// Open a file given a cfitsio-style extended filename such as `events.fits[EVENTS][PI > 100]`
+    /// or `image.fits[1][100:200,300:400]`, and apply the HDU, section, column and row
+    /// selection. See [`crate::filename`].
+    pub fn open_extended(filename: &str) -> io::Result<FITS> {
+        crate::filename::ExtendedFilename::parse(filename)
+            .map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?
+            .open()
+    }
+
+    /// Open an asynchronous seekable source. Only the headers are read before returning;
+    /// the data of each HDU is fetched on demand.
+    #[cfg(feature = "async")]
+    pub async fn open_async<R>(reader: R) -> io::Result<crate::async_reader::AsyncFitsReader<R>>
+    where
+        R: futures_util::io::AsyncRead + futures_util::io::AsyncSeek + Unpin,
+    {
+        crate::async_reader::AsyncFitsReader::new(reader).await
+    }
+
+    /// Memory-map a file. Image data can then be viewed without copying.
+    #[cfg(feature = "mmap")]
+    pub fn open_mmap<P: AsRef<std::path::Path>>(path: P) -> io::Result<crate::mmap::MmapFits> {
+        crate::mmap::MmapFits::open(path)
+    }
+
+    /// Serialize every HDU back into a FITS byte stream.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.hdus.iter().flat_map(|hdu| hdu.to_bytes()).collect()
+    }
+
+    /// A diagnostic for every DATASUM or CHECKSUM keyword that does not match its HDU.
+    pub fn checksum_diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for (hdu, verification) in self.hdus.iter().map(HDU::verify_checksum).enumerate() {
+            if verification.datasum == checksum::Status::Invalid {
+                diagnostics.push(Diagnostic::ChecksumMismatch { hdu, keyword: "DATASUM".to_string() });
+            }
+            if verification.checksum == checksum::Status::Invalid {
+                diagnostics.push(Diagnostic::ChecksumMismatch { hdu, keyword: "CHECKSUM".to_string() });
+            }
+        }
+        diagnostics
+    }
+
+    /// Compute DATASUM and CHECKSUM for every HDU, so the file is written with checksums.
+    pub fn update_checksums(&mut self) {
+        for hdu in self.hdus.iter_mut() {
+            hdu.update_checksum();
+        }
+    }
+
+    /// Write every HDU to a writer, one HDU at a time.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for hdu in self.hdus.iter() {
+            writer.write_all(&hdu.to_bytes())?;
         }
-        FITS { hdus: hdus }
+        Ok(())
     }
 
-    fn check_header_beginning(chunk: [u8; 2880]) -> bool {
+    pub(crate) fn check_header_beginning(chunk: [u8; 2880]) -> bool {
         let mut result = false;
         // The padding for 32 after SIMPLE is to reduce the number of false positives
         if chunk[0..8] == [83, 73, 77, 80, 76, 69, 32, 32]
@@ -51,7 +238,7 @@ impl FITS{
         result
     }
     
-    fn check_end(chunks: [u8; 2880]) -> bool {
+    pub(crate) fn check_end(chunks: [u8; 2880]) -> bool {
         let mut end = false;
         for i in 0..36 {
             if chunks[i * 80..(i + 1) * 80][0..8] == [69, 78, 68, 32, 32, 32, 32, 32] {
@@ -62,44 +249,63 @@ impl FITS{
         end
     }
     
-    pub fn bytes_to_hdu(buffer: &Vec<u8>) -> Vec<HDU> {
-        let n_chunks = buffer.len() / 2880;
-        let mut read_header = false;
-        let mut hdus: Vec<HDU> = Vec::new();
-        let mut current_hdu: HDU = HDU {
-            header: Header::new(),
-            data: data::data::Data::new(),
-        };
-        for i in 0..n_chunks {
-            let start = i * 2880;
-            let end = start + 2880;
-            let chunk: [u8; 2880] = buffer[start..end]
-                .try_into()
-                .expect("slice with incorrect length");
-            if FITS::check_header_beginning(chunk) == true {
-                read_header = true;
-                if !current_hdu.header.is_empty() {
-                    hdus.push(current_hdu);
-                    current_hdu = HDU {
-                        header: Header::new(),
-                        data: data::data::Data::new(),
-                    };
-                }
-            }
-            if read_header == true {
-                current_hdu.header.append(chunk);
-                if FITS::check_end(chunk) {
-                    read_header = false;
-                }
-            } else {
-                current_hdu.data.append(chunk);
-            }
-            if i == n_chunks - 1 {
-                hdus.push(current_hdu);
-                break;
+    /// Split a buffer into HDUs, dropping any diagnostics. See [`FITS::segment`].
+    pub fn bytes_to_hdu(buffer: &[u8]) -> Vec<HDU> {
+        FITS::segment(buffer).0
+    }
+
+    /// Split a buffer into HDUs with initialized headers and unformatted data.
+    ///
+    /// HDU boundaries are computed from the data size declared in each header rather than
+    /// by looking for `SIMPLE`/`XTENSION` at the start of every block, so data that happens
+    /// to begin with those bytes is not mistaken for a new header. Truncated data sections
+    /// are kept with the missing bytes zero-filled, and reported in the diagnostics.
+    pub fn segment(buffer: &[u8]) -> (Vec<HDU>, Vec<Diagnostic>) {
+        let length = buffer.len() as u64;
+        let (entries, diagnostics) = scan_headers(&mut io::Cursor::new(buffer), length)
+            .expect("reading from memory cannot fail");
+        let mut hdus: Vec<HDU> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let start = entry.data_offset as usize;
+            let available = entry.available(length);
+            let mut data = data::data::Data::new();
+            for block in 0..entry.data_size.div_ceil(2880) {
+                let mut chunk = [0u8; 2880];
+                let first = (block * 2880).min(available);
+                let last = ((block + 1) * 2880).min(available);
+                chunk[..last - first].copy_from_slice(&buffer[start + first..start + last]);
+                data.append(chunk);
             }
+            hdus.push(HDU {
+                header: entry.header,
+                data,
+            });
         }
-        hdus
+        (hdus, diagnostics)
+    }
+
+    /// Position of the first HDU with the given EXTNAME, compared without case, and EXTVER
+    /// if given.
+    pub fn hdu_index(&self, name: &str, version: Option<i64>) -> Result<usize, String> {
+        find_extension(self.hdus.iter().map(|hdu| &hdu.header), name, version)
+    }
+
+    /// First HDU with the given EXTNAME, e.g. `fits.hdu("SCI")`.
+    pub fn hdu(&self, name: &str) -> Result<&HDU, String> {
+        Ok(&self.hdus[self.hdu_index(name, None)?])
+    }
+
+    /// HDU with the given EXTNAME and EXTVER, e.g. `fits.hdu_ver("SCI", 2)`.
+    pub fn hdu_ver(&self, name: &str, version: i64) -> Result<&HDU, String> {
+        Ok(&self.hdus[self.hdu_index(name, Some(version))?])
+    }
+
+    /// HDUs, with their positions, that have `class` as HDUCLASS or one of their HDUCLASn
+    /// values, compared without case.
+    pub fn hdus_with_class<'a>(&'a self, class: &'a str) -> impl Iterator<Item = (usize, &'a HDU)> + 'a {
+        self.hdus.iter().enumerate().filter(move |(_, hdu)| {
+            hdu.header.get_hduclas().iter().any(|value| value.eq_ignore_ascii_case(class))
+        })
     }
 
     pub fn list_headers(&self) -> Vec<String> {
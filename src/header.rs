@@ -10,6 +10,12 @@ pub struct Header {
     keywords: HashMap<String, (u16, [String; 2])>,
 }
 
+impl Default for Header {
+    fn default() -> Self {
+        Header::new()
+    }
+}
+
 impl Header {
     pub fn new() -> Header {
         Header {
@@ -20,6 +26,48 @@ impl Header {
         }
     }
 
+    /// Build an initialized header from 80-character cards. END is appended and the last
+    /// fitsblock is padded with spaces.
+    pub fn from_cards(cards: &[String]) -> Header {
+        let mut bytes: Vec<u8> = Vec::with_capacity((cards.len() + 1) * 80);
+        for card in cards.iter().map(|card| card.as_str()).chain(std::iter::once("END")) {
+            let mut line = format!("{:<80}", card).into_bytes();
+            line.truncate(80);
+            bytes.extend(line);
+        }
+        bytes.resize(bytes.len().div_ceil(2880) * 2880, b' ');
+        let mut header = Header::new();
+        for block in bytes.chunks_exact(2880) {
+            let mut chunk = [0u8; 2880];
+            chunk.copy_from_slice(block);
+            header.append(chunk);
+        }
+        header.initialize_header();
+        header
+    }
+
+    /// Format a fixed-format `KEYWORD = value` card. Numbers and the logical values T and F
+    /// are right-justified in columns 11-30, anything else is written as a quoted string.
+    pub fn format_card(keyword: &str, value: &str) -> String {
+        let literal = value == "T" || value == "F" || value.replace(['D', 'd'], "E").parse::<f64>().is_ok();
+        let card = if literal {
+            format!("{:<8}= {:>20}", keyword, value)
+        } else {
+            format!("{:<8}= '{:<8}'", keyword, value.replace('\'', "''"))
+        };
+        card.chars().take(80).collect()
+    }
+
+    /// Format a floating point value with a FITS exponent (`E`), keeping full precision.
+    pub fn format_real(value: f64) -> String {
+        let text = format!("{:?}", value);
+        if text.contains('e') {
+            format!("{:E}", value)
+        } else {
+            text
+        }
+    }
+
     pub fn initialize_header(&mut self) {
         self.initiailzed = true;
         let mut counter = 0;
@@ -43,7 +91,84 @@ impl Header {
     }
 
     pub fn get_header_type(&self) -> HeaderType {
-        self.header_type
+        self.header_type.clone()
+    }
+
+    pub fn get_fitsblocks(&self) -> &Vec<[u8; 2880]> {
+        &self.fitsblocks
+    }
+
+    /// Length of each data axis, from NAXIS1 to NAXISn. Missing or invalid values are 0.
+    pub fn get_naxisn(&self) -> Vec<usize> {
+        let parse = |keyword: &str| -> usize {
+            match self.keywords.get(keyword) {
+                Some(value) => value.1[0].parse::<usize>().unwrap_or(0),
+                None => 0,
+            }
+        };
+        (1..=parse("NAXIS")).map(|i| parse(&format!("NAXIS{}", i))).collect()
+    }
+
+    /// Size in bytes of the data section described by this header, without the padding
+    /// to a whole number of fitsblocks, or `None` if it does not fit in a `usize`.
+    ///
+    /// Computed as `|BITPIX| * GCOUNT * (PCOUNT + NAXIS1 * ... * NAXISn) / 8`. For random
+    /// groups (`GROUPS = T` with `NAXIS1 = 0`) the first axis is left out of the product.
+    pub fn data_size(&self) -> Option<usize> {
+        let parse = |keyword: &str, default: usize| -> usize {
+            match self.keywords.get(keyword) {
+                Some(value) => value.1[0].parse::<usize>().unwrap_or(default),
+                None => default,
+            }
+        };
+        let bitpix = match self.keywords.get("BITPIX") {
+            Some(value) => value.1[0].parse::<i64>().unwrap_or(0).unsigned_abs() as usize,
+            None => 0,
+        };
+        let naxis = parse("NAXIS", 0);
+        if naxis == 0 {
+            return Some(0);
+        }
+        let groups = self.keywords.get("GROUPS").map(|value| value.1[0] == "T") == Some(true);
+        let mut product: usize = 1;
+        for i in 1..=naxis {
+            let naxisn = parse(&format!("NAXIS{}", i), 0);
+            if i == 1 && groups && naxisn == 0 {
+                continue;
+            }
+            product = product.checked_mul(naxisn)?;
+        }
+        let bits = bitpix
+            .checked_mul(parse("GCOUNT", 1))?
+            .checked_mul(parse("PCOUNT", 0).checked_add(product)?)?;
+        Some(bits / 8)
+    }
+
+    /// Number of fitsblocks occupied by the data section, including the padding, or `None`
+    /// if the data size overflows.
+    pub fn n_data_blocks(&self) -> Option<usize> {
+        Some(self.data_size()?.div_ceil(2880))
+    }
+
+    /// Cards of the header as written, in order, up to but not including END.
+    pub fn cards(&self) -> Vec<String> {
+        self.fitsblocks
+            .iter()
+            .flat_map(|block| block.chunks(80))
+            .map(|card| String::from_utf8_lossy(card).into_owned())
+            .take_while(|card| card.trim_end() != "END")
+            .collect()
+    }
+
+    /// Replace the first card with the same keyword, or add the card before END.
+    pub fn set_card(&mut self, card: &str) {
+        let keyword = Card::parse(card).keyword;
+        let mut cards = self.cards();
+        match cards.iter().position(|existing| Card::parse(existing).keyword == keyword) {
+            Some(index) => cards[index] = card.to_string(),
+            None => cards.push(card.to_string()),
+        }
+        *self = Header::from_cards(&cards);
     }
 
     pub fn print(&self) {
@@ -56,11 +181,10 @@ impl Header {
         let input = self
             .keywords
             .iter()
-            .map(|(key, value)| (key, value))
             .collect::<Vec<(&String, &(u16, [String; 2]))>>();
         if sorted{
             let mut input = input;
-            input.sort_by(|a, b| a.1.0.cmp(&b.1.0));
+            input.sort_by_key(|a| a.1.0);
             input.iter().map(|(key, value)| (key.to_string(), value.1[0].to_string())).collect::<Vec<(String, String)>>()
         }else{
             input.iter().map(|(key, value)| (key.to_string(), value.1[0].to_string())).collect::<Vec<(String, String)>>()
@@ -74,43 +198,62 @@ impl Header {
         }
     }
 
+    /// Value of a keyword, or `None` if the keyword is not in the header.
+    pub fn get_value(&self, keyword: &str) -> Option<&str> {
+        self.keywords.get(keyword).map(|value| value.1[0].as_str())
+    }
+
+    /// Numerical value of a keyword, accepting Fortran-style `D` exponents.
+    pub fn get_f64(&self, keyword: &str) -> Option<f64> {
+        self.get_value(keyword)?.replace(['D', 'd'], "E").parse::<f64>().ok()
+    }
+
+    pub fn contains_keyword(&self, keyword: &str) -> bool {
+        self.keywords.contains_key(keyword)
+    }
+
+    /// EXTNAME, or `None` for an unnamed HDU.
+    pub fn get_extname(&self) -> Option<&str> {
+        self.get_value("EXTNAME")
+    }
+
+    /// EXTVER, 1 when missing.
+    pub fn get_extver(&self) -> i64 {
+        self.get_f64("EXTVER").unwrap_or(1.0) as i64
+    }
+
+    /// HDUCLASS followed by HDUCLAS1, HDUCLAS2, ... up to the first missing level.
+    pub fn get_hduclas(&self) -> Vec<&str> {
+        let mut classes: Vec<&str> = self.get_value("HDUCLASS").into_iter().collect();
+        classes.extend((1..).map_while(|level| self.get_value(&format!("HDUCLAS{}", level))));
+        classes
+    }
+
     fn parse_line(buffer: &[u8]) -> (String, [String; 2]) {
-        let mut keyword = String::from(str::from_utf8(&buffer[0..8]).unwrap());
-        keyword.retain(|c| !c.is_whitespace());
-        if buffer[8..10] == [61, 32] {
-            let value = String::from(str::from_utf8(&buffer[11..80]).unwrap());
-            let parts = value.split("/").collect::<Vec<&str>>();
-            if parts.len() == 2 {
-                let comment = String::from(parts[1]);
-                let mut value = String::from(parts[0]);
-                value.retain(|c| !c.is_whitespace() && c != '\'');
-                (keyword, [value, comment])
-            } else {
-                let mut value = String::from(parts[0]);
+        let card = Card::parse(&String::from_utf8_lossy(buffer));
+        match card.value {
+            Some(mut value) => {
                 value.retain(|c| !c.is_whitespace() && c != '\'');
-                (keyword, [value, String::from("")])
+                (card.keyword, [value, card.comment])
             }
-        } else {
-            let value = String::from(str::from_utf8(&buffer[9..80]).unwrap());
-            (keyword, [String::from(""), value])
+            None => (card.keyword, [String::from(""), card.comment]),
         }
     }
 
     fn check_type(&self) -> HeaderType {
-        if self.initiailzed == false {
+        if !self.initiailzed {
             panic!("Header not initialized");
         }
         if self.keywords.contains_key("SIMPLE") {
-            return HeaderType::Primary;
+            HeaderType::Primary
         } else if self.keywords.contains_key("XTENSION") {
             match self.keywords.get("XTENSION").unwrap().1[0].as_str() {
-                "IMAGE" => return HeaderType::Image,
-                "BINTABLE" => return HeaderType::BinaryTable,
-                "TABLE" => return HeaderType::ASCIITable,
-                _ => panic!(
-                    "Invalid XTENSION value {:?}. Only standard extension is supported for now",
-                    self.keywords.get("XTENSION").unwrap().1[0]
-                ),
+                "IMAGE" => HeaderType::Image,
+                "BINTABLE" => HeaderType::BinaryTable,
+                "TABLE" => HeaderType::ASCIITable,
+                // Conforming (IUEIMAGE, A3DTABLE, FOREIGN, DUMP, ...) or unknown extension.
+                // The data is kept as raw bytes so the HDU can still be skipped or re-written.
+                other => HeaderType::Other(other.to_string()),
             }
         } else {
             panic!("Invalid header type");
@@ -118,12 +261,68 @@ impl Header {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+/// One header card split into keyword, value and comment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Card {
+    pub keyword: String,
+    /// Value as written, without surrounding spaces. String values keep their quotes.
+    /// `None` for commentary cards (COMMENT, HISTORY, blank keyword, ...).
+    pub value: Option<String>,
+    pub comment: String,
+}
+
+impl Card {
+    pub fn parse(card: &str) -> Card {
+        let card: String = card.chars().take(80).collect();
+        let keyword = card.chars().take(8).collect::<String>().trim().to_string();
+        let rest: String = card.chars().skip(8).collect();
+        if !rest.starts_with("= ") {
+            return Card {
+                keyword,
+                value: None,
+                comment: rest.trim_end().to_string(),
+            };
+        }
+        let field: String = rest.chars().skip(2).collect();
+        // The comment starts at the first slash outside a quoted string
+        let mut quoted = false;
+        let split = field.char_indices().find(|(_, c)| {
+            if *c == '\'' {
+                quoted = !quoted;
+            }
+            *c == '/' && !quoted
+        });
+        let (value, comment) = match split {
+            Some((index, _)) => (&field[..index], field[index + 1..].trim().to_string()),
+            None => (field.as_str(), String::new()),
+        };
+        Card {
+            keyword,
+            value: Some(value.trim().to_string()),
+            comment,
+        }
+    }
+
+    /// The value with string quotes removed, doubled quotes collapsed and trailing spaces
+    /// trimmed.
+    pub fn value_text(&self) -> Option<String> {
+        let value = self.value.as_ref()?;
+        if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+            Some(value[1..value.len() - 1].replace("''", "'").trim_end().to_string())
+        } else {
+            Some(value.clone())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum HeaderType {
     Primary,
     Image,
     ASCIITable,
     BinaryTable,
+    /// Any other XTENSION value, holding the extension name as written in the header.
+    Other(String),
 }
 
 impl fmt::Display for HeaderType {
@@ -137,6 +336,7 @@ impl fmt::Display for HeaderType {
                 HeaderType::Image => "Image",
                 HeaderType::ASCIITable => "ASCII Table",
                 HeaderType::BinaryTable => "Binary Table",
+                HeaderType::Other(name) => name.as_str(),
             }
         )
     }
@@ -0,0 +1,253 @@
+//! HDU summaries and header listings, as printed by the `rustfits info` and
+//! `rustfits header` commands.
+
+use crate::header::{Card, Header, HeaderType};
+use std::fmt::Write;
+
+/// One line of an HDU listing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HduSummary {
+    pub index: usize,
+    /// EXTNAME, or PRIMARY for the first HDU.
+    pub name: String,
+    /// EXTVER, 1 if missing.
+    pub version: i64,
+    /// HDU type as returned by `FITS::list_headers`.
+    pub kind: String,
+    /// Number of cards before END.
+    pub cards: usize,
+    pub bitpix: i64,
+    /// Axis lengths from NAXIS1 to NAXISn.
+    pub dimensions: Vec<usize>,
+    /// Number of table columns, 0 for images.
+    pub columns: usize,
+    /// Pixel type for images, column formats for tables.
+    pub format: String,
+}
+
+impl HduSummary {
+    pub fn new(index: usize, kind: String, header: &Header) -> HduSummary {
+        let name = match header.get_extname() {
+            Some(name) => name.to_string(),
+            None if index == 0 => String::from("PRIMARY"),
+            None => String::new(),
+        };
+        let columns = header.get_f64("TFIELDS").unwrap_or(0.0) as usize;
+        let bitpix = header.get_f64("BITPIX").unwrap_or(0.0) as i64;
+        let format = match header.get_header_type() {
+            HeaderType::BinaryTable | HeaderType::ASCIITable => {
+                let forms: Vec<&str> = (1..=columns)
+                    .map(|i| header.get_value(&format!("TFORM{}", i)).unwrap_or("?"))
+                    .collect();
+                format!("[{}]", forms.join(", "))
+            }
+            _ => pixel_type(bitpix, header.get_f64("BZERO").unwrap_or(0.0)).to_string(),
+        };
+        HduSummary {
+            index,
+            name,
+            version: header.get_extver(),
+            kind,
+            cards: header.cards().len(),
+            bitpix,
+            dimensions: header.get_naxisn(),
+            columns,
+            format,
+        }
+    }
+
+    /// Dimensions as `NAXIS1 x NAXIS2 x ...`, or `rows R x columns C` for tables.
+    pub fn shape(&self) -> String {
+        if self.columns > 0 || self.kind.contains("Table") {
+            format!("{}R x {}C", self.dimensions.get(1).copied().unwrap_or(0), self.columns)
+        } else if self.dimensions.is_empty() {
+            String::from("()")
+        } else {
+            let axes: Vec<String> = self.dimensions.iter().map(|n| n.to_string()).collect();
+            axes.join(" x ")
+        }
+    }
+}
+
+fn pixel_type(bitpix: i64, bzero: f64) -> &'static str {
+    match (bitpix, bzero) {
+        (8, _) => "uint8",
+        (16, 32768.0) => "uint16",
+        (16, _) => "int16",
+        (32, 2147483648.0) => "uint32",
+        (32, _) => "int32",
+        (64, _) => "int64",
+        (-32, _) => "float32",
+        (-64, _) => "float64",
+        _ => "unknown",
+    }
+}
+
+/// Summaries of all HDUs, given the type names from `list_headers` and the headers.
+pub fn summaries<'a, I: IntoIterator<Item = &'a Header>>(kinds: Vec<String>, headers: I) -> Vec<HduSummary> {
+    kinds
+        .into_iter()
+        .zip(headers)
+        .enumerate()
+        .map(|(index, (kind, header))| HduSummary::new(index, kind, header))
+        .collect()
+}
+
+/// Fixed-width table of HDU summaries, in the layout of astropy's `fitsinfo`.
+pub fn format_summaries(summaries: &[HduSummary]) -> String {
+    let mut text = format!(
+        "{:<4} {:<12} {:>4} {:<13} {:>6}  {:<7} {:<20} {}\n",
+        "No.", "Name", "Ver", "Type", "Cards", "BITPIX", "Dimensions", "Format"
+    );
+    for summary in summaries {
+        writeln!(
+            text,
+            "{:<4} {:<12} {:>4} {:<13} {:>6}  {:<7} {:<20} {}",
+            summary.index,
+            summary.name,
+            summary.version,
+            summary.kind,
+            summary.cards,
+            summary.bitpix,
+            summary.shape(),
+            summary.format
+        )
+        .unwrap();
+    }
+    text
+}
+
+/// Match a keyword against a glob pattern with `*` and `?`, ignoring case.
+pub fn glob_match(pattern: &str, keyword: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_uppercase().chars().collect();
+    let keyword: Vec<char> = keyword.to_uppercase().chars().collect();
+    let (mut p, mut k) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while k < keyword.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == keyword[k]) {
+            p += 1;
+            k += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, k));
+            p += 1;
+        } else if let Some((star_p, star_k)) = star {
+            p = star_p + 1;
+            k = star_k + 1;
+            star = Some((star_p, star_k + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Cards of a header whose keyword matches any of the patterns. All cards are kept when
+/// there are no patterns.
+pub fn select_cards(header: &Header, patterns: &[String]) -> Vec<String> {
+    header
+        .cards()
+        .into_iter()
+        .filter(|card| {
+            let keyword = Card::parse(card).keyword;
+            patterns.is_empty() || patterns.iter().any(|pattern| glob_match(pattern, &keyword))
+        })
+        .collect()
+}
+
+/// Cards as text, one per line with trailing spaces removed, each HDU preceded by a
+/// `# HDU n` line.
+pub fn cards_text(hdus: &[(usize, Vec<String>)]) -> String {
+    let mut text = String::new();
+    for (index, cards) in hdus {
+        writeln!(text, "# HDU {}", index).unwrap();
+        for card in cards {
+            writeln!(text, "{}", card.trim_end()).unwrap();
+        }
+    }
+    text
+}
+
+/// Cards as a JSON array of `{"hdu", "cards": [{"keyword", "value", "comment"}]}`
+/// objects. Logical values become booleans and numbers stay numbers.
+pub fn cards_json(hdus: &[(usize, Vec<String>)]) -> String {
+    let entries: Vec<String> = hdus
+        .iter()
+        .map(|(index, cards)| {
+            let cards: Vec<String> = cards
+                .iter()
+                .map(|card| {
+                    let card = Card::parse(card);
+                    format!(
+                        "{{\"keyword\": {}, \"value\": {}, \"comment\": {}}}",
+                        json_string(&card.keyword),
+                        json_value(&card),
+                        json_string(&card.comment)
+                    )
+                })
+                .collect();
+            format!("{{\"hdu\": {}, \"cards\": [{}]}}", index, cards.join(", "))
+        })
+        .collect();
+    format!("[{}]\n", entries.join(", "))
+}
+
+/// Cards as CSV with a `hdu,keyword,value,comment` header line.
+pub fn cards_csv(hdus: &[(usize, Vec<String>)]) -> String {
+    let mut text = String::from("hdu,keyword,value,comment\n");
+    for (index, cards) in hdus {
+        for card in cards {
+            let card = Card::parse(card);
+            writeln!(
+                text,
+                "{},{},{},{}",
+                index,
+                csv_field(&card.keyword),
+                csv_field(&card.value_text().unwrap_or_default()),
+                csv_field(&card.comment)
+            )
+            .unwrap();
+        }
+    }
+    text
+}
+
+fn json_value(card: &Card) -> String {
+    let text = match card.value_text() {
+        Some(text) => text,
+        None => return String::from("null"),
+    };
+    let raw = card.value.as_deref().unwrap_or("");
+    if raw.starts_with('\'') {
+        json_string(&text)
+    } else if raw == "T" || raw == "F" {
+        String::from(if raw == "T" { "true" } else { "false" })
+    } else {
+        match (raw.parse::<i64>(), raw.replace(['D', 'd'], "E").parse::<f64>()) {
+            (Ok(integer), _) => integer.to_string(),
+            (_, Ok(number)) if number.is_finite() => format!("{:?}", number),
+            _ => json_string(&text),
+        }
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
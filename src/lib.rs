@@ -3,3 +3,20 @@
 pub mod fits;
 pub mod header;
 pub mod data;
+pub mod reader;
+pub mod stream;
+pub mod wcs;
+pub mod time;
+pub mod render;
+pub mod info;
+pub mod diff;
+pub mod verify;
+pub mod checksum;
+pub mod filename;
+pub mod wasm;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "async")]
+pub mod async_reader;
+#[cfg(feature = "export")]
+pub mod export;
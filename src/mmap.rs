@@ -0,0 +1,82 @@
+use crate::data::view::ImageView;
+use crate::fits::{Diagnostic, HDU};
+use crate::header::{Header, HeaderType};
+use crate::reader::{scan_headers, HDUEntry, Layout};
+
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Memory-mapped FITS file. Headers are parsed when the file is opened and data
+/// sections are borrowed straight from the mapping.
+pub struct MmapFits {
+    mmap: Mmap,
+    layout: Layout,
+    naxisn: Vec<Vec<usize>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl MmapFits {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<MmapFits> {
+        let file = File::open(path)?;
+        // Safety: the mapping is read-only. As with any mmap, the file must not be
+        // truncated or modified by another process while it is mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let length = mmap.len() as u64;
+        let (entries, diagnostics) = scan_headers(&mut io::Cursor::new(&mmap[..]), length)?;
+        let naxisn = entries.iter().map(|entry| entry.header.get_naxisn()).collect();
+        Ok(MmapFits {
+            mmap,
+            layout: Layout { length, entries },
+            naxisn,
+            diagnostics,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.layout.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layout.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &Vec<HDUEntry> {
+        &self.layout.entries
+    }
+
+    pub fn header(&self, index: usize) -> &Header {
+        &self.layout.entries[index].header
+    }
+
+    /// Borrow the data bytes of one HDU, without padding. If the file is truncated only the
+    /// bytes present are returned.
+    pub fn data_bytes(&self, index: usize) -> io::Result<&[u8]> {
+        let entry = self.layout.entry(index)?;
+        let start = entry.data_offset as usize;
+        Ok(&self.mmap[start..start + entry.available(self.layout.length)])
+    }
+
+    /// Borrow the data of an image HDU. Returns `None` for tables and other extensions.
+    pub fn image(&self, index: usize) -> io::Result<Option<ImageView<'_>>> {
+        let bytes = self.data_bytes(index)?;
+        let header = &self.layout.entries[index].header;
+        Ok(match header.get_header_type() {
+            HeaderType::Primary | HeaderType::Image => header
+                .get_keyword("BITPIX")
+                .and_then(|bitpix| bitpix.parse::<i8>().ok())
+                .map(|bitpix| ImageView::new(bytes, bitpix, &self.naxisn[index])),
+            _ => None,
+        })
+    }
+
+    /// Copy one HDU out of the mapping. Data missing from a truncated file is zero-filled,
+    /// as with [`FitsReader::read_hdu`](crate::reader::FitsReader::read_hdu).
+    pub fn read_hdu(&self, index: usize) -> io::Result<HDU> {
+        let (_, mut fitsblocks, _) = self.layout.fitsblocks(index)?;
+        let bytes = self.data_bytes(index)?;
+        fitsblocks.as_flattened_mut()[..bytes.len()].copy_from_slice(bytes);
+        Ok(self.layout.hdu(index, fitsblocks))
+    }
+}
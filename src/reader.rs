@@ -0,0 +1,369 @@
+use crate::data::data::{Data, Precision};
+use crate::data::section::Section;
+use crate::fits::{find_extension, Diagnostic, FITS, HDU};
+use crate::header::{Header, HeaderType};
+use ndarray::{Array, IxDyn};
+
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+/// Location of one HDU inside a seekable source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HDUEntry {
+    pub header: Header,
+    /// Byte offset of the first header block
+    pub header_offset: u64,
+    /// Byte offset of the first data block
+    pub data_offset: u64,
+    /// Size of the data section in bytes, without padding
+    pub data_size: usize,
+}
+
+impl HDUEntry {
+    /// Number of data bytes actually present in the source, which is less than `data_size`
+    /// if the file is truncated.
+    pub fn available(&self, source_length: u64) -> usize {
+        let remaining = source_length.saturating_sub(self.data_offset) as usize;
+        self.data_size.min(remaining)
+    }
+}
+
+/// Lazily read FITS file. Only the headers are read when the file is opened; the data of
+/// each HDU is read on demand.
+pub struct FitsReader<R: Read + Seek> {
+    reader: R,
+    layout: Layout,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl<R: Read + Seek> FitsReader<R> {
+    /// Scan the headers of a seekable source, skipping over the data sections.
+    pub fn new(mut reader: R) -> io::Result<FitsReader<R>> {
+        let length = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+        let (entries, diagnostics) = scan_headers(&mut reader, length)?;
+        Ok(FitsReader {
+            reader,
+            layout: Layout { length, entries },
+            diagnostics,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.layout.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layout.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &Vec<HDUEntry> {
+        &self.layout.entries
+    }
+
+    pub fn header(&self, index: usize) -> &Header {
+        &self.layout.entries[index].header
+    }
+
+    /// Position of the first HDU with the given EXTNAME and, if given, EXTVER, without
+    /// reading any data. See [`FITS::hdu_index`].
+    pub fn hdu_index(&self, name: &str, version: Option<i64>) -> Result<usize, String> {
+        self.layout.hdu_index(name, version)
+    }
+
+    pub fn list_headers(&self) -> Vec<String> {
+        self.layout.list_headers()
+    }
+
+    /// Read the data section of one HDU as fitsblocks, zero-filling anything missing.
+    pub fn read_fitsblocks(&mut self, index: usize) -> io::Result<Vec<[u8; 2880]>> {
+        let (offset, mut fitsblocks, available) = self.layout.fitsblocks(index)?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.reader
+            .read_exact(&mut fitsblocks.as_flattened_mut()[..available])?;
+        Ok(fitsblocks)
+    }
+
+    /// Read `length` bytes starting `offset` bytes into the data section of one HDU.
+    pub fn read_data_bytes(&mut self, index: usize, offset: u64, length: usize) -> io::Result<Vec<u8>> {
+        let start = self.layout.data_bytes(index, offset, length)?;
+        let mut bytes = vec![0u8; length];
+        self.reader.seek(SeekFrom::Start(start))?;
+        self.reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn read_data(&mut self, index: usize) -> io::Result<Data> {
+        let fitsblocks = self.read_fitsblocks(index)?;
+        Ok(self.layout.data(index, fitsblocks))
+    }
+
+    pub fn read_hdu(&mut self, index: usize) -> io::Result<HDU> {
+        let fitsblocks = self.read_fitsblocks(index)?;
+        Ok(self.layout.hdu(index, fitsblocks))
+    }
+
+    /// Read every HDU into memory.
+    pub fn read_all(&mut self) -> io::Result<FITS> {
+        let mut hdus: Vec<HDU> = Vec::with_capacity(self.len());
+        for i in 0..self.len() {
+            hdus.push(self.read_hdu(i)?);
+        }
+        Ok(Layout::fits(hdus, &self.diagnostics))
+    }
+
+    /// Read only the pixels of an image HDU inside `ranges`, one read per selected row.
+    /// See [`ArrayData::read_section`](crate::data::array::ArrayData::read_section).
+    pub fn read_section(&mut self, index: usize, ranges: &[Range<usize>]) -> io::Result<Array<Precision, IxDyn>> {
+        self.read_section_strided(index, ranges, &vec![1; ranges.len()])
+    }
+
+    pub fn read_section_strided(
+        &mut self,
+        index: usize,
+        ranges: &[Range<usize>],
+        steps: &[usize],
+    ) -> io::Result<Array<Precision, IxDyn>> {
+        let entry = self.layout.entry(index)?;
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+        match entry.header.get_header_type() {
+            HeaderType::Primary | HeaderType::Image => {}
+            other => return Err(invalid(format!("HDU {} is a {}, not an image", index, other))),
+        }
+        let bitpix = entry
+            .header
+            .get_keyword("BITPIX")
+            .and_then(|value| value.parse::<i8>().ok())
+            .ok_or_else(|| invalid(format!("HDU {} has no valid BITPIX", index)))?;
+        let section = Section::new(&entry.header.get_naxisn(), ranges, steps).map_err(invalid)?;
+        let size = (bitpix.unsigned_abs() / 8) as usize;
+        let mut values: Vec<Precision> = Vec::with_capacity(section.shape.iter().product());
+        for run in section.runs.iter() {
+            let run_bytes = self.read_data_bytes(index, (run.first * size) as u64, run.span() * size)?;
+            section.decode_run(&run_bytes, bitpix, run, &mut values);
+        }
+        Ok(Array::from_shape_vec(section.shape.clone(), values).unwrap())
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Where the HDUs of a scanned source are, shared by [`FitsReader`] and the asynchronous
+/// reader so that they only differ in how bytes are read.
+#[derive(Clone, Debug)]
+pub(crate) struct Layout {
+    pub(crate) length: u64,
+    pub(crate) entries: Vec<HDUEntry>,
+}
+
+impl Layout {
+    pub(crate) fn entry(&self, index: usize) -> io::Result<&HDUEntry> {
+        self.entries.get(index).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("HDU {} out of range, the file has {} HDUs", index, self.entries.len()),
+            )
+        })
+    }
+
+    pub(crate) fn hdu_index(&self, name: &str, version: Option<i64>) -> Result<usize, String> {
+        find_extension(self.entries.iter().map(|entry| &entry.header), name, version)
+    }
+
+    pub(crate) fn list_headers(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|entry| entry.header.get_header_type().to_string())
+            .collect()
+    }
+
+    /// Source offset of the data of one HDU, zeroed fitsblocks covering the declared data
+    /// size to read it into and the number of bytes actually present in the source.
+    pub(crate) fn fitsblocks(&self, index: usize) -> io::Result<(u64, Vec<[u8; 2880]>, usize)> {
+        let entry = self.entry(index)?;
+        let available = entry.available(self.length);
+        Ok((entry.data_offset, vec![[0u8; 2880]; entry.data_size.div_ceil(2880)], available))
+    }
+
+    /// Source offset of `length` bytes starting `offset` bytes into the data section of one
+    /// HDU, or an error if they are not all present.
+    pub(crate) fn data_bytes(&self, index: usize, offset: u64, length: usize) -> io::Result<u64> {
+        let entry = self.entry(index)?;
+        if offset as usize + length > entry.available(self.length) {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "HDU {}: bytes {}..{} are outside the data section",
+                    index,
+                    offset,
+                    offset as usize + length
+                ),
+            ));
+        }
+        Ok(entry.data_offset + offset)
+    }
+
+    pub(crate) fn data(&self, index: usize, fitsblocks: Vec<[u8; 2880]>) -> Data {
+        Data::from_header(fitsblocks, &self.entries[index].header)
+    }
+
+    pub(crate) fn hdu(&self, index: usize, fitsblocks: Vec<[u8; 2880]>) -> HDU {
+        HDU {
+            header: self.entries[index].header.clone(),
+            data: self.data(index, fitsblocks),
+        }
+    }
+
+    /// In-memory file from every HDU, with the scan and checksum diagnostics.
+    pub(crate) fn fits(hdus: Vec<HDU>, diagnostics: &[Diagnostic]) -> FITS {
+        let mut fits = FITS::new();
+        fits.hdus = hdus;
+        fits.diagnostics = diagnostics.to_vec();
+        fits.diagnostics.extend(fits.checksum_diagnostics());
+        fits
+    }
+}
+
+/// Walk the HDUs of a source of known length, reading headers and seeking over data.
+pub(crate) fn scan_headers<R: Read + Seek>(
+    reader: &mut R,
+    length: u64,
+) -> io::Result<(Vec<HDUEntry>, Vec<Diagnostic>)> {
+    let mut scan = HeaderScan::new(reader.stream_position()?, length);
+    let mut chunk = [0u8; 2880];
+    while let Some(offset) = scan.next_block() {
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut chunk)?;
+        scan.push_block(chunk);
+    }
+    Ok(scan.finish())
+}
+
+/// Header scanning state machine, independent of how the source is read. Callers read the
+/// fitsblock at each offset returned by [`HeaderScan::next_block`] and hand it to
+/// [`HeaderScan::push_block`]; data sections are skipped without being read.
+pub(crate) struct HeaderScan {
+    length: u64,
+    cursor: u64,
+    header_offset: u64,
+    header: Header,
+    entries: Vec<HDUEntry>,
+    diagnostics: Vec<Diagnostic>,
+    done: bool,
+}
+
+impl HeaderScan {
+    pub(crate) fn new(start: u64, length: u64) -> HeaderScan {
+        HeaderScan {
+            length,
+            cursor: start,
+            header_offset: start,
+            header: Header::new(),
+            entries: Vec::new(),
+            diagnostics: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Offset of the next fitsblock to read, or `None` once the scan is over.
+    pub(crate) fn next_block(&mut self) -> Option<u64> {
+        if self.done {
+            return None;
+        }
+        if self.cursor + 2880 <= self.length {
+            return Some(self.cursor);
+        }
+        if !self.header.is_empty() {
+            self.diagnostics.push(Diagnostic::MissingEnd { hdu: self.entries.len() });
+        } else if self.cursor < self.length {
+            self.trailing_bytes();
+        }
+        self.done = true;
+        None
+    }
+
+    /// Add the fitsblock read at the offset last returned by [`HeaderScan::next_block`].
+    pub(crate) fn push_block(&mut self, chunk: [u8; 2880]) {
+        if self.header.is_empty() {
+            if !FITS::check_header_beginning(chunk) {
+                self.trailing_bytes();
+                self.done = true;
+                return;
+            }
+            self.header_offset = self.cursor;
+        }
+        self.header.append(chunk);
+        self.cursor += 2880;
+        if FITS::check_end(chunk) {
+            let header = std::mem::take(&mut self.header);
+            let hdu_index = self.entries.len();
+            let (entry, next) = locate_data(
+                header,
+                self.header_offset,
+                self.cursor,
+                self.length,
+                hdu_index,
+                &mut self.diagnostics,
+            );
+            self.entries.push(entry);
+            self.cursor = next;
+        }
+    }
+
+    pub(crate) fn finish(self) -> (Vec<HDUEntry>, Vec<Diagnostic>) {
+        (self.entries, self.diagnostics)
+    }
+
+    fn trailing_bytes(&mut self) {
+        self.diagnostics.push(Diagnostic::TrailingBytes {
+            offset: self.cursor as usize,
+            length: (self.length - self.cursor) as usize,
+        });
+    }
+}
+
+/// Initialize a complete header read at `header_offset` and work out where its data ends.
+/// Returns the entry and the offset of the next HDU.
+pub(crate) fn locate_data(
+    mut header: Header,
+    header_offset: u64,
+    data_offset: u64,
+    length: u64,
+    hdu_index: usize,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> (HDUEntry, u64) {
+    header.initialize_header();
+    let data_size = match header.data_size() {
+        Some(data_size) => data_size,
+        None => {
+            diagnostics.push(Diagnostic::InvalidDataSize { hdu: hdu_index });
+            let entry = HDUEntry {
+                data_offset,
+                data_size: 0,
+                header_offset,
+                header,
+            };
+            return (entry, length);
+        }
+    };
+    let entry = HDUEntry {
+        data_offset,
+        data_size,
+        header_offset,
+        header,
+    };
+    let available = entry.available(length);
+    if available < data_size {
+        diagnostics.push(Diagnostic::Truncated {
+            hdu: hdu_index,
+            expected: data_size,
+            available,
+        });
+        (entry, length)
+    } else {
+        let padded = (data_size as u64).div_ceil(2880) * 2880;
+        (entry, (data_offset + padded).min(length))
+    }
+}
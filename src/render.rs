@@ -0,0 +1,372 @@
+//! Render image HDUs to 8-bit RGBA for display, e.g. in a browser canvas.
+
+use crate::data::data::Data;
+use crate::fits::HDU;
+
+/// Mapping from normalized values in [0, 1] to display intensity.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Stretch {
+    Linear,
+    Sqrt,
+    /// `log(a x + 1) / log(a + 1)` with `a = 1000`, as in DS9
+    Log,
+    /// `asinh(x / 0.1) / asinh(10)`
+    Asinh,
+    /// Histogram equalization: each value is mapped to its rank among the pixels
+    Histogram,
+}
+
+/// How the values mapped to black and white are chosen.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Limits {
+    MinMax,
+    /// Lower and upper percentiles, e.g. `Percentile(0.5, 99.5)`
+    Percentile(f64, f64),
+    /// IRAF zscale: limits from a line fitted to sorted pixel samples
+    ZScale,
+    Manual(f64, f64),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Colormap {
+    Gray,
+    /// Black, red, yellow, white
+    Heat,
+    Viridis,
+    Inferno,
+    Magma,
+    Plasma,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderOptions {
+    pub stretch: Stretch,
+    pub limits: Limits,
+    pub colormap: Colormap,
+    pub invert: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            stretch: Stretch::Linear,
+            limits: Limits::ZScale,
+            colormap: Colormap::Gray,
+            invert: false,
+        }
+    }
+}
+
+/// Row-major 8-bit RGBA pixels, top row first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RgbaImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Stretch {
+    pub fn from_name(name: &str) -> Option<Stretch> {
+        match name.to_ascii_lowercase().as_str() {
+            "linear" => Some(Stretch::Linear),
+            "sqrt" => Some(Stretch::Sqrt),
+            "log" => Some(Stretch::Log),
+            "asinh" => Some(Stretch::Asinh),
+            "histogram" | "histeq" => Some(Stretch::Histogram),
+            _ => None,
+        }
+    }
+
+    /// Stretch a value already normalized to [0, 1]. Histogram equalization is applied
+    /// beforehand and is the identity here.
+    pub fn apply(&self, x: f64) -> f64 {
+        match self {
+            Stretch::Linear | Stretch::Histogram => x,
+            Stretch::Sqrt => x.sqrt(),
+            Stretch::Log => (1000.0 * x + 1.0).ln() / 1001f64.ln(),
+            Stretch::Asinh => (x / 0.1).asinh() / 10f64.asinh(),
+        }
+    }
+}
+
+impl Colormap {
+    pub fn from_name(name: &str) -> Option<Colormap> {
+        match name.to_ascii_lowercase().as_str() {
+            "gray" | "grey" => Some(Colormap::Gray),
+            "heat" => Some(Colormap::Heat),
+            "viridis" => Some(Colormap::Viridis),
+            "inferno" => Some(Colormap::Inferno),
+            "magma" => Some(Colormap::Magma),
+            "plasma" => Some(Colormap::Plasma),
+            _ => None,
+        }
+    }
+
+    /// Evenly spaced colours, linearly interpolated in between.
+    fn stops(&self) -> &'static [u32] {
+        match self {
+            Colormap::Gray => &[0x000000, 0xffffff],
+            Colormap::Heat => &[0x000000, 0xff0000, 0xffff00, 0xffffff],
+            Colormap::Viridis => &[
+                0x440154, 0x482878, 0x3e4989, 0x31688e, 0x26828e, 0x1f9e89, 0x35b779, 0x6ece58, 0xfde725,
+            ],
+            Colormap::Inferno => &[
+                0x000004, 0x1f0c48, 0x550f6d, 0x88226a, 0xba3655, 0xe35933, 0xf98e09, 0xf9cb35, 0xfcffa4,
+            ],
+            Colormap::Magma => &[
+                0x000004, 0x1c1044, 0x4f127b, 0x812581, 0xb5367a, 0xe55064, 0xfb8761, 0xfec287, 0xfcfdbf,
+            ],
+            Colormap::Plasma => &[
+                0x0d0887, 0x4c02a1, 0x7e03a8, 0xa92395, 0xcc4778, 0xe56b5d, 0xf89441, 0xfdc328, 0xf0f921,
+            ],
+        }
+    }
+
+    /// Colour of an intensity in [0, 1].
+    pub fn color(&self, x: f64) -> [u8; 3] {
+        let stops = self.stops();
+        let position = x.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+        let index = (position.floor() as usize).min(stops.len() - 2);
+        let fraction = position - index as f64;
+        let channel = |stop: u32, shift: u32| ((stop >> shift) & 0xff) as f64;
+        let mut color = [0u8; 3];
+        for (i, shift) in [16, 8, 0].iter().enumerate() {
+            let (a, b) = (channel(stops[index], *shift), channel(stops[index + 1], *shift));
+            color[i] = (a + (b - a) * fraction).round() as u8;
+        }
+        color
+    }
+}
+
+/// Render the first plane of an image HDU. NAXIS1 is the width and NAXIS2 the height; rows
+/// are flipped so that the first FITS row ends up at the bottom, as astronomical images are
+/// usually displayed. BSCALE and BZERO are applied.
+pub fn render(hdu: &HDU, options: &RenderOptions) -> Result<RgbaImage, String> {
+    let (width, height, values) = image_plane(hdu)?;
+    Ok(render_values(&values, width, height, options))
+}
+
+/// Render row-major values, first row at the bottom. NaN values are transparent.
+pub fn render_values(values: &[f64], width: usize, height: usize, options: &RenderOptions) -> RgbaImage {
+    let mut pixels = vec![0u8; width * height * 4];
+    for (pixel, x) in pixels.chunks_exact_mut(4).zip(intensities(values, width, height, options)) {
+        if x.is_nan() {
+            continue;
+        }
+        pixel[..3].copy_from_slice(&options.colormap.color(x));
+        pixel[3] = 255;
+    }
+    RgbaImage { width, height, pixels }
+}
+
+/// Display intensities in [0, 1] after limits, stretch and inversion, in the row order of
+/// [`render_values`]. Non-finite values stay NaN. The colormap is not applied.
+pub fn intensities(values: &[f64], width: usize, height: usize, options: &RenderOptions) -> Vec<f64> {
+    let (low, high) = limits(values, options.limits);
+    let mut sorted: Vec<f64> = Vec::new();
+    if options.stretch == Stretch::Histogram {
+        sorted = values.iter().cloned().filter(|v| v.is_finite()).map(|v| v.clamp(low, high)).collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    }
+    let mut output = vec![f64::NAN; width * height];
+    for row in 0..height {
+        let target = height - 1 - row;
+        for column in 0..width {
+            let value = values[row * width + column];
+            if !value.is_finite() {
+                continue;
+            }
+            let x = if options.stretch == Stretch::Histogram {
+                let rank = sorted.partition_point(|v| *v < value.clamp(low, high));
+                rank as f64 / (sorted.len().max(2) - 1) as f64
+            } else if high > low {
+                (value - low) / (high - low)
+            } else {
+                0.0
+            };
+            let x = options.stretch.apply(x.clamp(0.0, 1.0));
+            output[target * width + column] = if options.invert { 1.0 - x } else { x };
+        }
+    }
+    output
+}
+
+/// First 2D plane of an image HDU with BSCALE and BZERO applied: (width, height, values).
+pub fn image_plane(hdu: &HDU) -> Result<(usize, usize, Vec<f64>), String> {
+    let naxisn = hdu.header.get_naxisn();
+    let width = naxisn.first().cloned().unwrap_or(0);
+    let height = naxisn.get(1).cloned().unwrap_or(1);
+    let values = physical_values(hdu, width * height)?;
+    Ok((width, height, values))
+}
+
+/// The first `count` pixels of an image HDU with BSCALE and BZERO applied. Fails if the
+/// data of a truncated file ends before them.
+pub(crate) fn physical_values(hdu: &HDU, count: usize) -> Result<Vec<f64>, String> {
+    let array = match &hdu.data {
+        Data::Array(array) if !array.get_naxisn().is_empty() => array,
+        _ => return Err("HDU has no image data".to_string()),
+    };
+    let view = array.view();
+    let size = (view.get_bitpix().unsigned_abs() / 8) as usize;
+    if view.bytes().len() < count * size {
+        return Err("Image data is shorter than its header declares".to_string());
+    }
+    let scale = hdu.header.get_f64("BSCALE").unwrap_or(1.0);
+    let zero = hdu.header.get_f64("BZERO").unwrap_or(0.0);
+    Ok((0..count).map(|index| zero + scale * view.get_f64(index)).collect())
+}
+
+/// Values mapped to black and white, ignoring NaN and infinite pixels.
+pub fn limits(values: &[f64], limits: Limits) -> (f64, f64) {
+    if let Limits::Manual(low, high) = limits {
+        return (low, high);
+    }
+    let mut finite: Vec<f64> = values.iter().cloned().filter(|v| v.is_finite()).collect();
+    if finite.is_empty() {
+        return (0.0, 1.0);
+    }
+    finite.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    match limits {
+        Limits::MinMax => (finite[0], finite[finite.len() - 1]),
+        Limits::Percentile(low, high) => (percentile(&finite, low), percentile(&finite, high)),
+        Limits::ZScale => {
+            let step = (finite.len() / 1000).max(1);
+            let samples: Vec<f64> = finite.iter().step_by(step).cloned().collect();
+            zscale(&samples)
+        }
+        Limits::Manual(low, high) => (low, high),
+    }
+}
+
+/// Percentile of sorted values, interpolating between neighbours.
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let position = (percent / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let index = position.floor() as usize;
+    let next = (index + 1).min(sorted.len() - 1);
+    sorted[index] + (sorted[next] - sorted[index]) * (position - index as f64)
+}
+
+/// IRAF zscale of sorted samples, with contrast 0.25, 2.5-sigma rejection and at most five
+/// iterations, as implemented by astropy's `ZScaleInterval`.
+fn zscale(samples: &[f64]) -> (f64, f64) {
+    let (contrast, max_reject, min_npixels, krej, max_iterations) = (0.25, 0.5, 5, 2.5, 5);
+    let npix = samples.len();
+    let (mut low, mut high) = (samples[0], samples[npix - 1]);
+    let min_pixels = min_npixels.max((npix as f64 * max_reject) as usize);
+    let grow = ((npix as f64 * 0.01) as usize).max(1);
+    let mut bad = vec![false; npix];
+    let (mut good, mut last_good) = (npix, npix + 1);
+    let mut fit = (0.0, 0.0);
+    for _ in 0..max_iterations {
+        if good >= last_good || good < min_pixels {
+            break;
+        }
+        fit = fit_line(samples, &bad);
+        let flat: Vec<f64> = (0..npix).map(|i| samples[i] - (fit.0 + fit.1 * i as f64)).collect();
+        let kept: Vec<f64> = (0..npix).filter(|i| !bad[*i]).map(|i| flat[i]).collect();
+        let mean = kept.iter().sum::<f64>() / kept.len() as f64;
+        let sigma = (kept.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / kept.len() as f64).sqrt();
+        let rejected: Vec<bool> = flat.iter().map(|v| v.abs() > krej * sigma).collect();
+        // Grow rejected pixels to their neighbours, like a convolution with a box of `grow` ones
+        let half = grow / 2;
+        for (i, bad) in bad.iter_mut().enumerate() {
+            let start = i.saturating_sub(grow - 1 - half);
+            let end = (i + half).min(npix - 1);
+            *bad = *bad || rejected[start..=end].iter().any(|rejected| *rejected);
+        }
+        last_good = good;
+        good = bad.iter().filter(|b| !**b).count();
+    }
+    if good >= min_pixels {
+        let slope = fit.1 / contrast;
+        let center = (npix - 1) / 2;
+        let median = if npix % 2 == 1 {
+            samples[npix / 2]
+        } else {
+            (samples[npix / 2 - 1] + samples[npix / 2]) / 2.0
+        };
+        low = low.max(median - (center as f64 - 1.0) * slope);
+        high = high.min(median + (npix - center) as f64 * slope);
+    }
+    (low, high)
+}
+
+/// Least-squares line `intercept + slope * i` through the samples that are not rejected.
+fn fit_line(samples: &[f64], bad: &[bool]) -> (f64, f64) {
+    let points: Vec<(f64, f64)> = (0..samples.len()).filter(|i| !bad[*i]).map(|i| (i as f64, samples[i])).collect();
+    let n = points.len() as f64;
+    let (sx, sy) = points.iter().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+    let (mx, my) = (sx / n, sy / n);
+    let sxx: f64 = points.iter().map(|(x, _)| (x - mx).powi(2)).sum();
+    let sxy: f64 = points.iter().map(|(x, y)| (x - mx) * (y - my)).sum();
+    let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    (my - slope * mx, slope)
+}
+
+/// Parameters of [`lupton_rgb`], with the defaults of Lupton et al. (2004) as used by
+/// astropy's `make_lupton_rgb`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LuptonOptions {
+    /// Value subtracted from each band, usually the sky level
+    pub minimum: [f64; 3],
+    /// Linear range of the stretch
+    pub stretch: f64,
+    /// Asinh softening parameter
+    pub q: f64,
+}
+
+impl Default for LuptonOptions {
+    fn default() -> Self {
+        LuptonOptions {
+            minimum: [0.0; 3],
+            stretch: 5.0,
+            q: 8.0,
+        }
+    }
+}
+
+/// Colour composite of three images (red, green, blue) with the asinh stretch of
+/// Lupton et al. (2004). The stretch is applied to the mean intensity and the bands are
+/// scaled by the same factor, so colours are preserved even in saturated pixels.
+pub fn lupton_rgb(red: &HDU, green: &HDU, blue: &HDU, options: &LuptonOptions) -> Result<RgbaImage, String> {
+    let bands = [image_plane(red)?, image_plane(green)?, image_plane(blue)?];
+    let (width, height) = (bands[0].0, bands[0].1);
+    if bands.iter().any(|band| band.0 != width || band.1 != height) {
+        return Err("The three images must have the same shape".to_string());
+    }
+    let q = if options.q.abs() < 1.0 / 2f64.powi(23) { 0.1 } else { options.q.min(1e10) };
+    let slope = 0.1 * 255.0 / (0.1 * q).asinh();
+    let soften = q / options.stretch;
+    let mut pixels = vec![0u8; width * height * 4];
+    for row in 0..height {
+        let target = height - 1 - row;
+        for column in 0..width {
+            let index = row * width + column;
+            let mut rgb = [0.0f64; 3];
+            for (band, value) in rgb.iter_mut().enumerate() {
+                *value = bands[band].2[index] - options.minimum[band];
+            }
+            if rgb.iter().any(|value| !value.is_finite()) {
+                continue;
+            }
+            let intensity = (rgb[0] + rgb[1] + rgb[2]) / 3.0;
+            let factor = if intensity <= 0.0 { 0.0 } else { (intensity * soften).asinh() * slope / intensity };
+            for value in rgb.iter_mut() {
+                *value = (*value * factor).max(0.0);
+            }
+            let max = rgb.iter().cloned().fold(0.0, f64::max);
+            if max > 255.0 {
+                for value in rgb.iter_mut() {
+                    *value *= 255.0 / max;
+                }
+            }
+            let offset = (target * width + column) * 4;
+            for (channel, value) in rgb.iter().enumerate() {
+                pixels[offset + channel] = value.round() as u8;
+            }
+            pixels[offset + 3] = 255;
+        }
+    }
+    Ok(RgbaImage { width, height, pixels })
+}
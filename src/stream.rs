@@ -0,0 +1,176 @@
+use crate::fits::{Diagnostic, FITS};
+use crate::header::Header;
+
+use std::convert::TryInto;
+
+/// Event emitted by [`FitsStreamParser::feed`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamEvent<'a> {
+    /// The header of an HDU has been read up to its END card.
+    HeaderComplete(usize, Header),
+    /// Bytes of the data section of an HDU, borrowed from the fed buffer. Padding is not included.
+    DataChunk(usize, &'a [u8]),
+    /// The data section of an HDU, including its padding, has been consumed.
+    HduComplete(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum State {
+    Header,
+    Data { remaining: usize, padding: usize },
+    Trailing,
+    /// The data size of the last header overflows, so the rest of the input is ignored.
+    Invalid,
+}
+
+/// Push parser for FITS files received in arbitrary-sized chunks, e.g. browser uploads or pipes.
+///
+/// Only the header block currently being read is buffered; data bytes are handed back as
+/// slices of the fed buffer.
+pub struct FitsStreamParser {
+    state: State,
+    block: Vec<u8>,
+    header: Header,
+    hdu_index: usize,
+    offset: usize,
+    trailing_offset: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Default for FitsStreamParser {
+    fn default() -> Self {
+        FitsStreamParser::new()
+    }
+}
+
+impl FitsStreamParser {
+    pub fn new() -> FitsStreamParser {
+        FitsStreamParser {
+            state: State::Header,
+            block: Vec::with_capacity(2880),
+            header: Header::new(),
+            hdu_index: 0,
+            offset: 0,
+            trailing_offset: 0,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Consume the next chunk of the file and return the events it completes.
+    pub fn feed<'a>(&mut self, mut bytes: &'a [u8]) -> Vec<StreamEvent<'a>> {
+        let mut events: Vec<StreamEvent<'a>> = Vec::new();
+        while !bytes.is_empty() {
+            match self.state {
+                State::Header => {
+                    let n = (2880 - self.block.len()).min(bytes.len());
+                    self.block.extend_from_slice(&bytes[..n]);
+                    bytes = &bytes[n..];
+                    self.offset += n;
+                    if self.block.len() == 2880 {
+                        self.end_of_block(&mut events);
+                    }
+                }
+                State::Data { remaining, padding } => {
+                    if remaining > 0 {
+                        let n = remaining.min(bytes.len());
+                        events.push(StreamEvent::DataChunk(self.hdu_index, &bytes[..n]));
+                        bytes = &bytes[n..];
+                        self.offset += n;
+                        self.state = State::Data {
+                            remaining: remaining - n,
+                            padding,
+                        };
+                    } else {
+                        let n = padding.min(bytes.len());
+                        bytes = &bytes[n..];
+                        self.offset += n;
+                        self.state = State::Data {
+                            remaining,
+                            padding: padding - n,
+                        };
+                    }
+                    if self.state == (State::Data { remaining: 0, padding: 0 }) {
+                        self.end_of_hdu(&mut events);
+                    }
+                }
+                State::Trailing | State::Invalid => {
+                    self.offset += bytes.len();
+                    bytes = &bytes[bytes.len()..];
+                }
+            }
+        }
+        events
+    }
+
+    /// Signal the end of the input and return the problems found in the stream.
+    pub fn finish(mut self) -> Vec<Diagnostic> {
+        match self.state {
+            State::Header => {
+                if !self.header.is_empty() {
+                    self.diagnostics.push(Diagnostic::MissingEnd { hdu: self.hdu_index });
+                } else if !self.block.is_empty() {
+                    self.diagnostics.push(Diagnostic::TrailingBytes {
+                        offset: self.offset - self.block.len(),
+                        length: self.block.len(),
+                    });
+                }
+            }
+            State::Data { remaining, .. } => {
+                if remaining > 0 {
+                    let expected = self.header.data_size().unwrap_or(remaining);
+                    self.diagnostics.push(Diagnostic::Truncated {
+                        hdu: self.hdu_index,
+                        expected,
+                        available: expected - remaining,
+                    });
+                }
+            }
+            State::Trailing => {
+                self.diagnostics.push(Diagnostic::TrailingBytes {
+                    offset: self.trailing_offset,
+                    length: self.offset - self.trailing_offset,
+                });
+            }
+            State::Invalid => {}
+        }
+        self.diagnostics
+    }
+
+    fn end_of_block(&mut self, events: &mut Vec<StreamEvent<'_>>) {
+        let chunk: [u8; 2880] = self.block[..].try_into().expect("slice with incorrect length");
+        self.block.clear();
+        if self.header.is_empty() && !FITS::check_header_beginning(chunk) {
+            self.trailing_offset = self.offset - 2880;
+            self.state = State::Trailing;
+            return;
+        }
+        self.header.append(chunk);
+        if FITS::check_end(chunk) {
+            self.header.initialize_header();
+            events.push(StreamEvent::HeaderComplete(self.hdu_index, self.header.clone()));
+            let data_size = match self.header.data_size() {
+                Some(data_size) => data_size,
+                None => {
+                    self.diagnostics.push(Diagnostic::InvalidDataSize { hdu: self.hdu_index });
+                    self.end_of_hdu(events);
+                    self.state = State::Invalid;
+                    return;
+                }
+            };
+            self.state = State::Data {
+                remaining: data_size,
+                padding: (2880 - data_size % 2880) % 2880,
+            };
+            if data_size == 0 {
+                self.end_of_hdu(events);
+            }
+        }
+    }
+
+    fn end_of_hdu(&mut self, events: &mut Vec<StreamEvent<'_>>) {
+        events.push(StreamEvent::HduComplete(self.hdu_index));
+        self.hdu_index += 1;
+        self.header = Header::new();
+        self.state = State::Header;
+    }
+}
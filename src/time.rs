@@ -0,0 +1,393 @@
+use crate::header::Header;
+use std::fmt;
+
+/// Time scales of the FITS time paper (Rots et al. 2015) that can be converted between.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimeScale {
+    TAI,
+    TT,
+    UTC,
+    GPS,
+    TDB,
+    TCG,
+    TCB,
+}
+
+impl TimeScale {
+    /// Scale of a TIMESYS value, accepting the deprecated synonyms TDT, ET and IAT.
+    pub fn from_code(code: &str) -> Option<TimeScale> {
+        match code.to_ascii_uppercase().as_str() {
+            "TAI" | "IAT" => Some(TimeScale::TAI),
+            "TT" | "TDT" | "ET" => Some(TimeScale::TT),
+            "UTC" => Some(TimeScale::UTC),
+            "GPS" => Some(TimeScale::GPS),
+            "TDB" => Some(TimeScale::TDB),
+            "TCG" => Some(TimeScale::TCG),
+            "TCB" => Some(TimeScale::TCB),
+            _ => None,
+        }
+    }
+
+    /// Scale of a header from TIMESYS, UTC when the keyword is missing.
+    pub fn from_header(header: &Header) -> Result<TimeScale, String> {
+        match header.get_value("TIMESYS") {
+            Some(code) => TimeScale::from_code(code).ok_or_else(|| format!("Unsupported TIMESYS {:?}", code)),
+            None => Ok(TimeScale::UTC),
+        }
+    }
+}
+
+impl fmt::Display for TimeScale {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// TAI - UTC in seconds from the first day of (year, month), since the introduction of leap
+/// seconds in 1972.
+const LEAP_SECONDS: [(i64, i64, f64); 28] = [
+    (1972, 1, 10.0),
+    (1972, 7, 11.0),
+    (1973, 1, 12.0),
+    (1974, 1, 13.0),
+    (1975, 1, 14.0),
+    (1976, 1, 15.0),
+    (1977, 1, 16.0),
+    (1978, 1, 17.0),
+    (1979, 1, 18.0),
+    (1980, 1, 19.0),
+    (1981, 7, 20.0),
+    (1982, 7, 21.0),
+    (1983, 7, 22.0),
+    (1985, 7, 23.0),
+    (1988, 1, 24.0),
+    (1990, 1, 25.0),
+    (1991, 1, 26.0),
+    (1992, 7, 27.0),
+    (1993, 7, 28.0),
+    (1994, 7, 29.0),
+    (1996, 1, 30.0),
+    (1997, 7, 31.0),
+    (1999, 1, 32.0),
+    (2006, 1, 33.0),
+    (2009, 1, 34.0),
+    (2012, 7, 35.0),
+    (2015, 7, 36.0),
+    (2017, 1, 37.0),
+];
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+/// TT - TAI in seconds
+const TT_TAI: f64 = 32.184;
+/// TAI - GPS in seconds
+const TAI_GPS: f64 = 19.0;
+/// Rate of TCG relative to TT, and of TCB relative to TDB (IAU 2000 and 2006 resolutions)
+const L_G: f64 = 6.969_290_134e-10;
+const L_B: f64 = 1.550_519_768e-8;
+const TDB_0: f64 = -6.55e-5;
+/// 1977-01-01T00:00:32.184 TT, where TT, TCG and TCB coincide, as MJD
+const T_0: f64 = 43144.0 + TT_TAI / SECONDS_PER_DAY;
+
+/// TAI - UTC on a UTC day. Before 1972 the 1972 value is used, since the earlier
+/// fractional offsets of UTC are not tabulated.
+pub fn leap_seconds(mjd: i64) -> f64 {
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|(year, month, _)| mjd >= mjd_from_date(*year, *month, 1))
+        .map_or(LEAP_SECONDS[0].2, |entry| entry.2)
+}
+
+/// Modified Julian Date of a date of the proleptic Gregorian calendar.
+pub fn mjd_from_date(year: i64, month: i64, day: i64) -> i64 {
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 678_881
+}
+
+/// Gregorian (year, month, day) of a Modified Julian Date.
+pub fn date_from_mjd(mjd: i64) -> (i64, i64, i64) {
+    let z = mjd + 678_881;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let m = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * m + 2) / 5 + 1;
+    let month = if m < 10 { m + 3 } else { m - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// An instant in a given time scale, held as a whole MJD and the seconds into that day so
+/// that sub-microsecond resolution is kept. UTC days containing a leap second last 86401 s.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Time {
+    pub scale: TimeScale,
+    day: i64,
+    seconds: f64,
+}
+
+impl Time {
+    pub fn from_mjd(mjd: f64, scale: TimeScale) -> Time {
+        Time::from_mjd_parts(mjd.floor(), mjd - mjd.floor(), scale)
+    }
+
+    /// Time from an MJD split into two parts, such as MJDREFI and MJDREFF, to keep precision.
+    pub fn from_mjd_parts(integer: f64, fraction: f64, scale: TimeScale) -> Time {
+        Time::normalized(integer as i64, (integer - integer.trunc() + fraction) * SECONDS_PER_DAY, scale)
+    }
+
+    /// Parse an ISO-8601 date (`YYYY-MM-DD`, optionally followed by `Thh:mm:ss[.s]`) or the
+    /// pre-2000 `DD/MM/YY` form of DATE-OBS, which refers to 19YY.
+    pub fn parse(text: &str, scale: TimeScale) -> Result<Time, String> {
+        let text = text.trim();
+        let error = || format!("Invalid date {:?}", text);
+        let number = |part: &str| part.parse::<i64>().map_err(|_| error());
+        if text.len() == 8 && text.as_bytes()[2] == b'/' && text.as_bytes()[5] == b'/' {
+            let parts: Vec<&str> = text.split('/').collect();
+            let (day, month, year) = (number(parts[0])?, number(parts[1])?, number(parts[2])?);
+            return Ok(Time::normalized(mjd_from_date(1900 + year, month, day), 0.0, scale));
+        }
+        let (date, time) = match text.find('T') {
+            Some(index) => (&text[..index], &text[index + 1..]),
+            None => (text, ""),
+        };
+        let parts: Vec<&str> = date.split('-').collect();
+        if parts.len() != 3 || parts[0].len() != 4 {
+            return Err(error());
+        }
+        let (year, month, day) = (number(parts[0])?, number(parts[1])?, number(parts[2])?);
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(error());
+        }
+        let mut seconds = 0.0;
+        if !time.is_empty() {
+            let parts: Vec<&str> = time.split(':').collect();
+            if parts.len() != 3 {
+                return Err(error());
+            }
+            let second = parts[2].parse::<f64>().map_err(|_| error())?;
+            seconds = (number(parts[0])? * 3600 + number(parts[1])? * 60) as f64 + second;
+        }
+        // No normalization here, so a UTC leap second (23:59:60) stays on its own day
+        Ok(Time {
+            scale,
+            day: mjd_from_date(year, month, day),
+            seconds,
+        })
+    }
+
+    fn normalized(day: i64, seconds: f64, scale: TimeScale) -> Time {
+        let days = (seconds / SECONDS_PER_DAY).floor();
+        Time {
+            scale,
+            day: day + days as i64,
+            seconds: seconds - days * SECONDS_PER_DAY,
+        }
+    }
+
+    pub fn mjd(&self) -> f64 {
+        self.day as f64 + self.seconds / SECONDS_PER_DAY
+    }
+
+    pub fn jd(&self) -> f64 {
+        self.mjd() + 2_400_000.5
+    }
+
+    /// Whole MJD and the seconds into that day.
+    pub fn mjd_parts(&self) -> (i64, f64) {
+        (self.day, self.seconds)
+    }
+
+    /// Add elapsed seconds of the time's own scale. For UTC the seconds are counted in TAI,
+    /// so leap seconds are included.
+    pub fn add_seconds(&self, seconds: f64) -> Time {
+        if self.scale == TimeScale::UTC {
+            let tai = self.to_scale(TimeScale::TAI);
+            return Time::normalized(tai.day, tai.seconds + seconds, TimeScale::TAI).to_scale(TimeScale::UTC);
+        }
+        Time::normalized(self.day, self.seconds + seconds, self.scale)
+    }
+
+    /// The same instant in another time scale.
+    pub fn to_scale(&self, scale: TimeScale) -> Time {
+        if scale == self.scale {
+            return *self;
+        }
+        let (day, seconds) = self.tai();
+        let tai = Time::normalized(day, seconds, TimeScale::TAI);
+        match scale {
+            TimeScale::TAI => tai,
+            TimeScale::TT => Time::normalized(tai.day, tai.seconds + TT_TAI, scale),
+            TimeScale::GPS => Time::normalized(tai.day, tai.seconds - TAI_GPS, scale),
+            TimeScale::UTC => {
+                // UTC day `d` starts at TAI d + (TAI - UTC)(d)
+                let offset = leap_seconds(tai.day);
+                if tai.seconds >= offset {
+                    Time { scale, day: tai.day, seconds: tai.seconds - offset }
+                } else {
+                    let day = tai.day - 1;
+                    Time { scale, day, seconds: tai.seconds + SECONDS_PER_DAY - leap_seconds(day) }
+                }
+            }
+            TimeScale::TDB => {
+                let tt = tai.to_scale(TimeScale::TT);
+                Time::normalized(tt.day, tt.seconds + tdb_minus_tt(tt.mjd()), scale)
+            }
+            TimeScale::TCG => {
+                let tt = tai.to_scale(TimeScale::TT);
+                let elapsed = (tt.day as f64 - T_0) * SECONDS_PER_DAY + tt.seconds;
+                Time::normalized(tt.day, tt.seconds + L_G / (1.0 - L_G) * elapsed, scale)
+            }
+            TimeScale::TCB => {
+                let tdb = tai.to_scale(TimeScale::TDB);
+                let elapsed = (tdb.day as f64 - T_0) * SECONDS_PER_DAY + tdb.seconds;
+                Time::normalized(tdb.day, tdb.seconds + (L_B * elapsed - TDB_0) / (1.0 - L_B), scale)
+            }
+        }
+    }
+
+    /// TAI as a whole MJD and seconds, not normalized.
+    fn tai(&self) -> (i64, f64) {
+        let elapsed = |time: &Time| (time.day as f64 - T_0) * SECONDS_PER_DAY + time.seconds;
+        match self.scale {
+            TimeScale::TAI => (self.day, self.seconds),
+            TimeScale::TT => (self.day, self.seconds - TT_TAI),
+            TimeScale::GPS => (self.day, self.seconds + TAI_GPS),
+            TimeScale::UTC => (self.day, self.seconds + leap_seconds(self.day)),
+            TimeScale::TDB => (self.day, self.seconds - tdb_minus_tt(self.mjd()) - TT_TAI),
+            TimeScale::TCG => (self.day, self.seconds - L_G * elapsed(self) - TT_TAI),
+            TimeScale::TCB => {
+                let tdb = Time::normalized(self.day, self.seconds - L_B * elapsed(self) + TDB_0, TimeScale::TDB);
+                tdb.tai()
+            }
+        }
+    }
+
+    /// ISO-8601 representation, `YYYY-MM-DDThh:mm:ss` with `decimals` digits of seconds.
+    pub fn to_iso(&self, decimals: usize) -> String {
+        let unit = 10f64.powi(decimals as i32);
+        let mut day = self.day;
+        let mut ticks = (self.seconds * unit).round() as i64;
+        let day_length = if self.scale == TimeScale::UTC {
+            (SECONDS_PER_DAY + leap_seconds(day + 1) - leap_seconds(day)) as i64
+        } else {
+            SECONDS_PER_DAY as i64
+        };
+        if ticks >= day_length * unit as i64 {
+            ticks -= day_length * unit as i64;
+            day += 1;
+        }
+        let (year, month, date) = date_from_mjd(day);
+        let whole = ticks / unit as i64;
+        let (hour, minute, second) = if whole >= SECONDS_PER_DAY as i64 {
+            (23, 59, 60 + whole - SECONDS_PER_DAY as i64)
+        } else {
+            (whole / 3600, whole % 3600 / 60, whole % 60)
+        };
+        let mut text = format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", year, month, date, hour, minute, second);
+        if decimals > 0 {
+            text.push_str(&format!(".{:0width$}", ticks % unit as i64, width = decimals));
+        }
+        text
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.to_iso(3), self.scale)
+    }
+}
+
+/// TDB - TT in seconds, to about 10 microseconds (Fairhead & Bretagnon leading terms).
+fn tdb_minus_tt(mjd: f64) -> f64 {
+    let g = (357.53 + 0.985_600_28 * (mjd - 51544.5)).to_radians();
+    0.001_657 * g.sin() + 0.000_014 * (2.0 * g).sin()
+}
+
+/// Seconds in one TIMEUNIT.
+fn unit_to_seconds(unit: &str) -> Result<f64, String> {
+    match unit {
+        "s" => Ok(1.0),
+        "ms" => Ok(1e-3),
+        "us" => Ok(1e-6),
+        "ns" => Ok(1e-9),
+        "min" => Ok(60.0),
+        "h" => Ok(3600.0),
+        "d" => Ok(SECONDS_PER_DAY),
+        "a" | "yr" => Ok(365.25 * SECONDS_PER_DAY),
+        "cy" => Ok(36525.0 * SECONDS_PER_DAY),
+        _ => Err(format!("Unsupported TIMEUNIT {:?}", unit)),
+    }
+}
+
+/// Reference of relative time values: MJDREF (or MJDREFI + MJDREFF, JDREF, DATEREF) in the
+/// TIMESYS scale, with TIMEZERO and TIMEUNIT applied to every value.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimeReference {
+    pub reference: Time,
+    /// TIMEZERO, in TIMEUNIT
+    pub zero: f64,
+    /// Length of one TIMEUNIT in seconds
+    pub unit: f64,
+}
+
+impl TimeReference {
+    pub fn from_header(header: &Header) -> Result<TimeReference, String> {
+        let scale = TimeScale::from_header(header)?;
+        let split = |integer: &str, fraction: &str| match (header.get_f64(integer), header.get_f64(fraction)) {
+            (None, None) => None,
+            (integer, fraction) => Some((integer.unwrap_or(0.0), fraction.unwrap_or(0.0))),
+        };
+        let reference = if let Some((integer, fraction)) = split("MJDREFI", "MJDREFF") {
+            Time::from_mjd_parts(integer, fraction, scale)
+        } else if let Some(mjd) = header.get_f64("MJDREF") {
+            Time::from_mjd(mjd, scale)
+        } else if let Some((integer, fraction)) = split("JDREFI", "JDREFF") {
+            Time::from_mjd_parts(integer - 2_400_001.0, fraction + 0.5, scale)
+        } else if let Some(jd) = header.get_f64("JDREF") {
+            Time::from_mjd_parts(jd.floor() - 2_400_001.0, jd - jd.floor() + 0.5, scale)
+        } else if let Some(date) = header.get_value("DATEREF") {
+            Time::parse(date, scale)?
+        } else {
+            Time::from_mjd(0.0, scale)
+        };
+        Ok(TimeReference {
+            reference,
+            zero: header.get_f64("TIMEZERO").unwrap_or(0.0),
+            unit: unit_to_seconds(header.get_value("TIMEUNIT").unwrap_or("s"))?,
+        })
+    }
+
+    /// Absolute time of a relative time value, in the TIMESYS scale.
+    pub fn time(&self, value: f64) -> Time {
+        self.reference.add_seconds((self.zero + value) * self.unit)
+    }
+
+    /// Absolute times of relative time values, converted to `scale`.
+    pub fn times(&self, values: &[f64], scale: TimeScale) -> Vec<Time> {
+        values.iter().map(|value| self.time(*value).to_scale(scale)).collect()
+    }
+}
+
+/// DATE-OBS of a header in its TIMESYS scale, combined with TIME-OBS when DATE-OBS only
+/// holds a date. Returns `None` when DATE-OBS is missing.
+pub fn date_obs(header: &Header) -> Result<Option<Time>, String> {
+    let scale = TimeScale::from_header(header)?;
+    let date = match header.get_value("DATE-OBS") {
+        Some(date) => date,
+        None => return Ok(None),
+    };
+    match header.get_value("TIME-OBS") {
+        Some(time) if !date.contains('T') => {
+            let day = Time::parse(date, scale)?;
+            let (year, month, day) = date_from_mjd(day.day);
+            Time::parse(&format!("{:04}-{:02}-{:02}T{}", year, month, day, time), scale).map(Some)
+        }
+        _ => Time::parse(date, scale).map(Some),
+    }
+}
@@ -0,0 +1,597 @@
+//! Conformance checks against the FITS 4.0 standard, in the spirit of `fitsverify`.
+//!
+//! The checks run on the raw bytes, so problems the reader silently tolerates (padding,
+//! card layout, misplaced END cards, ...) are reported too.
+
+use crate::checksum::{self, Status};
+use crate::data::tables::BinaryField;
+use crate::header::Card;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The file violates a requirement of the standard.
+    Error,
+    /// The file conforms, but uses deprecated or unusual constructs.
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// HDU index, `None` for problems with the file as a whole.
+    pub hdu: Option<usize>,
+    /// 1-based card number within the HDU header.
+    pub card: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.hdu, self.card) {
+            (Some(hdu), Some(card)) => write!(f, "HDU {}, card {}: ", hdu, card)?,
+            (Some(hdu), None) => write!(f, "HDU {}: ", hdu)?,
+            _ => {}
+        }
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}", severity, self.message)
+    }
+}
+
+const BLOCK: usize = 2880;
+const COMMENTARY: [&str; 4] = ["", "COMMENT", "HISTORY", "CONTINUE"];
+
+struct Checker {
+    issues: Vec<Issue>,
+    hdu: Option<usize>,
+}
+
+impl Checker {
+    fn error(&mut self, card: Option<usize>, message: String) {
+        self.issues.push(Issue {
+            severity: Severity::Error,
+            hdu: self.hdu,
+            card,
+            message,
+        });
+    }
+
+    fn warning(&mut self, card: Option<usize>, message: String) {
+        self.issues.push(Issue {
+            severity: Severity::Warning,
+            hdu: self.hdu,
+            card,
+            message,
+        });
+    }
+}
+
+/// Cards of one header, with the position of each non-commentary keyword.
+struct Cards {
+    cards: Vec<String>,
+    parsed: Vec<Card>,
+    positions: HashMap<String, usize>,
+}
+
+impl Cards {
+    fn new(cards: Vec<String>) -> Cards {
+        let parsed: Vec<Card> = cards.iter().map(|card| Card::parse(card)).collect();
+        let mut positions = HashMap::new();
+        for (index, card) in parsed.iter().enumerate() {
+            positions.entry(card.keyword.clone()).or_insert(index);
+        }
+        Cards {
+            cards,
+            parsed,
+            positions,
+        }
+    }
+
+    fn position(&self, keyword: &str) -> Option<usize> {
+        self.positions.get(keyword).copied()
+    }
+
+    fn value(&self, keyword: &str) -> Option<&str> {
+        self.parsed[self.position(keyword)?].value.as_deref()
+    }
+
+    fn integer(&self, keyword: &str) -> Option<i64> {
+        self.value(keyword)?.parse::<i64>().ok()
+    }
+
+    fn string(&self, keyword: &str) -> Option<String> {
+        self.parsed[self.position(keyword)?].value_text()
+    }
+}
+
+/// Check a whole file and return every problem found, grouped by HDU.
+pub fn verify(bytes: &[u8]) -> Vec<Issue> {
+    let mut checker = Checker {
+        issues: Vec::new(),
+        hdu: None,
+    };
+    if bytes.is_empty() {
+        checker.error(None, "file is empty".to_string());
+        return checker.issues;
+    }
+    // usize::is_multiple_of needs Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    if bytes.len() % BLOCK != 0 {
+        checker.error(
+            None,
+            format!("file size {} is not a multiple of 2880 bytes", bytes.len()),
+        );
+    }
+    let mut offset = 0;
+    let mut hdu = 0;
+    let mut extend: Option<String> = None;
+    while offset < bytes.len() {
+        if hdu > 0 && !bytes[offset..].starts_with(b"XTENSION") {
+            checker.hdu = None;
+            checker.warning(
+                None,
+                format!("{} bytes after the last HDU are not an extension", bytes.len() - offset),
+            );
+            break;
+        }
+        checker.hdu = Some(hdu);
+        match check_hdu(&mut checker, bytes, offset, hdu) {
+            Some((next, cards)) => {
+                if hdu == 0 {
+                    extend = cards.value("EXTEND").map(|value| value.to_string());
+                }
+                offset = next;
+            }
+            None => break,
+        }
+        hdu += 1;
+    }
+    if hdu > 1 && extend.as_deref() != Some("T") {
+        checker.hdu = Some(0);
+        checker.warning(None, "file has extensions but the primary header lacks EXTEND = T".to_string());
+    }
+    checker.issues
+}
+
+/// Check one HDU starting at `offset`. Returns the offset of the next HDU and the header
+/// cards, or `None` when the file cannot be followed any further.
+fn check_hdu(checker: &mut Checker, bytes: &[u8], offset: usize, hdu: usize) -> Option<(usize, Cards)> {
+    let mut cards: Vec<String> = Vec::new();
+    let mut end: Option<usize> = None;
+    for (number, card) in bytes[offset..].chunks(80).enumerate() {
+        if card.len() < 80 {
+            break;
+        }
+        if card.starts_with(b"END") && card[3..8].iter().all(|byte| *byte == b' ') {
+            if card[8..].iter().any(|byte| *byte != b' ') {
+                checker.error(Some(number + 1), "END card must be blank in columns 9-80".to_string());
+            }
+            end = Some(number);
+            break;
+        }
+        check_card(checker, number + 1, card);
+        cards.push(String::from_utf8_lossy(card).into_owned());
+    }
+    let end = match end {
+        Some(end) => end,
+        None => {
+            checker.error(None, "header has no END card".to_string());
+            return None;
+        }
+    };
+    let header_length = ((end + 1) * 80).div_ceil(BLOCK) * BLOCK;
+    let data_start = offset + header_length;
+    if data_start > bytes.len() {
+        checker.error(None, "header is not padded to a whole 2880-byte block".to_string());
+    } else if bytes[offset + (end + 1) * 80..data_start].iter().any(|byte| *byte != b' ') {
+        checker.error(None, "header fill after the END card must be ASCII spaces".to_string());
+    }
+
+    let cards = Cards::new(cards);
+    check_duplicates(checker, &cards);
+    let data_size = check_mandatory(checker, &cards, hdu)?;
+    let xtension = cards.string("XTENSION").unwrap_or_default();
+    match xtension.as_str() {
+        "TABLE" => check_ascii_table(checker, &cards),
+        "BINTABLE" => check_binary_table(checker, &cards),
+        _ => {}
+    }
+
+    let data_end = data_start + data_size.div_ceil(BLOCK) * BLOCK;
+    if data_end > bytes.len() {
+        checker.error(
+            None,
+            format!(
+                "data section is truncated, expected {} bytes but only {} are available",
+                data_end - data_start,
+                bytes.len().saturating_sub(data_start)
+            ),
+        );
+        return None;
+    }
+    let fill = if xtension == "TABLE" { b' ' } else { 0 };
+    if bytes[data_start + data_size..data_end].iter().any(|byte| *byte != fill) {
+        let fill = if fill == 0 { "zeros" } else { "ASCII spaces" };
+        checker.error(None, format!("data fill after the last data byte must be {}", fill));
+    }
+    let verification = checksum::verify_bytes(&bytes[offset..data_start], &bytes[data_start..data_end], &cards.cards);
+    for (keyword, status) in [("DATASUM", verification.datasum), ("CHECKSUM", verification.checksum)] {
+        if status == Status::Invalid {
+            checker.error(
+                cards.position(keyword).map(|p| p + 1),
+                format!("{} does not match the contents of the HDU", keyword),
+            );
+        }
+    }
+    Some((data_end, cards))
+}
+
+/// Column-level checks on a single card: ASCII text, keyword characters and value syntax.
+fn check_card(checker: &mut Checker, number: usize, card: &[u8]) {
+    if let Some(column) = card.iter().position(|byte| !(0x20..=0x7e).contains(byte)) {
+        checker.error(
+            Some(number),
+            format!("illegal character 0x{:02X} in column {}", card[column], column + 1),
+        );
+        return;
+    }
+    let text = String::from_utf8_lossy(card);
+    let keyword = text[..8].trim_end();
+    if keyword.contains(' ') {
+        checker.error(Some(number), format!("keyword '{}' must be left-justified without spaces", &text[..8]));
+    } else if let Some(c) = keyword.chars().find(|c| !(c.is_ascii_uppercase() || c.is_ascii_digit() || *c == '-' || *c == '_')) {
+        checker.error(Some(number), format!("illegal character '{}' in keyword {}", c, keyword));
+    }
+    if &text[8..10] == "= " && !COMMENTARY.contains(&keyword) {
+        if let Err(message) = check_value(&text[10..]) {
+            checker.error(Some(number), format!("{}: {}", keyword, message));
+        }
+    }
+}
+
+/// Check the free-format value field (columns 11-80) of a keyword card.
+fn check_value(field: &str) -> Result<(), String> {
+    let trimmed = field.trim_start();
+    if let Some(string) = trimmed.strip_prefix('\'') {
+        // A quote is escaped by doubling it
+        let mut chars = string.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            if c != '\'' {
+                continue;
+            }
+            if chars.peek().map(|(_, next)| *next) == Some('\'') {
+                chars.next();
+                continue;
+            }
+            let rest = string[index + 1..].trim_start();
+            if !rest.is_empty() && !rest.starts_with('/') {
+                return Err(format!("unexpected text after string value: {}", rest.trim_end()));
+            }
+            return Ok(());
+        }
+        return Err("string value has no closing quote".to_string());
+    }
+    let value = trimmed.split('/').next().unwrap_or("").trim();
+    if value.is_empty() || value == "T" || value == "F" || is_number(value) {
+        return Ok(());
+    }
+    if let Some(inner) = value.strip_prefix('(').and_then(|value| value.strip_suffix(')')) {
+        let parts: Vec<&str> = inner.split(',').map(|part| part.trim()).collect();
+        if parts.len() == 2 && parts.iter().all(|part| is_number(part)) {
+            return Ok(());
+        }
+    }
+    Err(format!("invalid value {}", value))
+}
+
+/// Integer or real number in the FITS format: optional sign, digits with an optional
+/// decimal point, and an optional `E` or `D` exponent.
+fn is_number(text: &str) -> bool {
+    let text = text.strip_prefix(['+', '-']).unwrap_or(text);
+    let (mantissa, exponent) = match text.find(['E', 'D']) {
+        Some(index) => (&text[..index], Some(&text[index + 1..])),
+        None => (text, None),
+    };
+    let digits = mantissa.replacen('.', "", 1);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    match exponent {
+        Some(exponent) => {
+            let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            !exponent.is_empty() && exponent.chars().all(|c| c.is_ascii_digit())
+        }
+        None => true,
+    }
+}
+
+fn check_duplicates(checker: &mut Checker, cards: &Cards) {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for (index, card) in cards.parsed.iter().enumerate() {
+        if COMMENTARY.contains(&card.keyword.as_str()) || card.value.is_none() {
+            continue;
+        }
+        if let Some(first) = seen.insert(card.keyword.as_str(), index) {
+            checker.warning(
+                Some(index + 1),
+                format!("{} repeats the keyword of card {}", card.keyword, first + 1),
+            );
+        }
+    }
+}
+
+/// Check a mandatory keyword: its position, fixed-format layout and integer value range.
+/// Returns the value if it could be read.
+fn mandatory_integer(
+    checker: &mut Checker,
+    cards: &Cards,
+    keyword: &str,
+    position: usize,
+    range: std::ops::RangeInclusive<i64>,
+) -> Option<i64> {
+    let value = mandatory(checker, cards, keyword, position)?;
+    match value.parse::<i64>() {
+        Ok(value) if range.contains(&value) => Some(value),
+        Ok(value) => {
+            checker.error(
+                Some(position + 1),
+                format!("{} = {} is outside {}..={}", keyword, value, range.start(), range.end()),
+            );
+            None
+        }
+        Err(_) => {
+            checker.error(Some(position + 1), format!("{} must be an integer", keyword));
+            None
+        }
+    }
+}
+
+fn mandatory(checker: &mut Checker, cards: &Cards, keyword: &str, position: usize) -> Option<String> {
+    let found = match cards.position(keyword) {
+        Some(found) => found,
+        None => {
+            checker.error(None, format!("mandatory keyword {} is missing", keyword));
+            return None;
+        }
+    };
+    if found != position {
+        checker.error(
+            Some(found + 1),
+            format!("{} must be card {}", keyword, position + 1),
+        );
+    }
+    let card = &cards.cards[found];
+    let value = cards.parsed[found].value.clone();
+    let value = match value {
+        Some(value) => value,
+        None => {
+            checker.error(Some(found + 1), format!("{} has no value", keyword));
+            return None;
+        }
+    };
+    // Fixed format: strings start in column 11, other values end in column 30. Cards with
+    // non-ASCII bytes are already reported by `check_card`.
+    let fixed = if !card.is_ascii() {
+        true
+    } else if value.starts_with('\'') {
+        card[10..].starts_with('\'')
+    } else {
+        card[10..30].trim_start() == value && !card[..30].ends_with(' ')
+    };
+    if !fixed {
+        checker.error(Some(found + 1), format!("{} is not in fixed format", keyword));
+    }
+    Some(value)
+}
+
+/// Check the mandatory keywords of a primary or extension header and return the size of
+/// the data section, or `None` if it cannot be determined.
+fn check_mandatory(checker: &mut Checker, cards: &Cards, hdu: usize) -> Option<usize> {
+    let mut position = 0;
+    let mut xtension = String::new();
+    if hdu == 0 {
+        if let Some(value) = mandatory(checker, cards, "SIMPLE", 0) {
+            if value == "F" {
+                checker.warning(Some(1), "SIMPLE = F, the file declares itself non-conforming".to_string());
+            } else if value != "T" {
+                checker.error(Some(1), "SIMPLE must be T".to_string());
+            }
+        }
+        if let Some(found) = cards.position("XTENSION") {
+            checker.error(Some(found + 1), "XTENSION is not allowed in the primary header".to_string());
+        }
+    } else {
+        if let Some(value) = mandatory(checker, cards, "XTENSION", 0) {
+            xtension = cards.string("XTENSION").unwrap_or(value);
+            match xtension.as_str() {
+                "IMAGE" | "TABLE" | "BINTABLE" => {}
+                other => checker.warning(Some(1), format!("non-standard extension type {}", other)),
+            }
+        }
+        for keyword in ["SIMPLE", "EXTEND"] {
+            if let Some(found) = cards.position(keyword) {
+                checker.warning(Some(found + 1), format!("{} is only allowed in the primary header", keyword));
+            }
+        }
+    }
+    position += 1;
+
+    let bitpix = mandatory_integer(checker, cards, "BITPIX", position, -64..=64);
+    if let Some(value) = bitpix {
+        if ![8, 16, 32, 64, -32, -64].contains(&value) {
+            checker.error(Some(position + 1), format!("BITPIX = {} is not a valid value", value));
+        }
+    }
+    position += 1;
+    let naxis = mandatory_integer(checker, cards, "NAXIS", position, 0..=999);
+    position += 1;
+    let mut naxisn: Vec<Option<i64>> = Vec::new();
+    for i in 1..=naxis.unwrap_or(0) {
+        naxisn.push(mandatory_integer(checker, cards, &format!("NAXIS{}", i), position, 0..=i64::MAX));
+        position += 1;
+    }
+
+    let table = xtension == "TABLE" || xtension == "BINTABLE";
+    let (mut pcount, mut gcount) = (Some(0), Some(1));
+    if hdu > 0 {
+        pcount = mandatory_integer(checker, cards, "PCOUNT", position, 0..=i64::MAX);
+        gcount = mandatory_integer(checker, cards, "GCOUNT", position + 1, 0..=i64::MAX);
+        position += 2;
+        let expected_pcount_zero = xtension == "IMAGE" || xtension == "TABLE";
+        if expected_pcount_zero && pcount.is_some_and(|pcount| pcount != 0) {
+            checker.error(cards.position("PCOUNT").map(|p| p + 1), format!("PCOUNT must be 0 in a {} extension", xtension));
+        }
+        if matches!(xtension.as_str(), "IMAGE" | "TABLE" | "BINTABLE") && gcount.is_some_and(|gcount| gcount != 1) {
+            checker.error(cards.position("GCOUNT").map(|p| p + 1), format!("GCOUNT must be 1 in a {} extension", xtension));
+        }
+        if table {
+            mandatory_integer(checker, cards, "TFIELDS", position, 0..=999);
+            if bitpix.is_some_and(|bitpix| bitpix != 8) {
+                checker.error(Some(2), format!("BITPIX must be 8 in a {} extension", xtension));
+            }
+            if naxis.is_some_and(|naxis| naxis != 2) {
+                checker.error(Some(3), format!("NAXIS must be 2 in a {} extension", xtension));
+            }
+        }
+    } else if cards.value("GROUPS") == Some("T") {
+        pcount = Some(cards.integer("PCOUNT").unwrap_or(0));
+        gcount = Some(cards.integer("GCOUNT").unwrap_or(1));
+        if naxisn.first() != Some(&Some(0)) {
+            checker.error(None, "random groups require NAXIS1 = 0".to_string());
+        }
+    }
+
+    let bitpix = bitpix?.unsigned_abs() as usize;
+    if naxis? == 0 {
+        return Some(0);
+    }
+    let (pcount, gcount) = (pcount? as usize, gcount? as usize);
+    let groups = hdu == 0 && cards.value("GROUPS") == Some("T");
+    let mut product: Option<usize> = Some(1);
+    for (i, n) in naxisn.into_iter().enumerate() {
+        let n = n? as usize;
+        if !(i == 0 && groups && n == 0) {
+            product = product.and_then(|product| product.checked_mul(n));
+        }
+    }
+    let bits = product
+        .and_then(|product| product.checked_add(pcount))
+        .and_then(|size| size.checked_mul(gcount))
+        .and_then(|size| size.checked_mul(bitpix));
+    if bits.is_none() {
+        checker.error(None, "data size given by BITPIX, NAXISn, PCOUNT and GCOUNT is too large".to_string());
+    }
+    Some(bits? / 8)
+}
+
+/// Warn about column keywords numbered beyond TFIELDS and return TFIELDS.
+fn check_column_indices(checker: &mut Checker, cards: &Cards) -> usize {
+    let tfields = cards.integer("TFIELDS").unwrap_or(0).max(0) as usize;
+    const COLUMN: [&str; 11] = [
+        "TTYPE", "TFORM", "TUNIT", "TSCAL", "TZERO", "TNULL", "TDISP", "TDIM", "TBCOL", "TDMIN", "TDMAX",
+    ];
+    for (index, card) in cards.parsed.iter().enumerate() {
+        let prefix = COLUMN.iter().find(|prefix| card.keyword.starts_with(**prefix));
+        if let Some(prefix) = prefix {
+            if let Ok(n) = card.keyword[prefix.len()..].parse::<usize>() {
+                if n == 0 || n > tfields {
+                    checker.warning(
+                        Some(index + 1),
+                        format!("{} refers to column {} but TFIELDS = {}", card.keyword, n, tfields),
+                    );
+                }
+            }
+        }
+    }
+    tfields
+}
+
+fn check_ascii_table(checker: &mut Checker, cards: &Cards) {
+    let tfields = check_column_indices(checker, cards);
+    let naxis1 = cards.integer("NAXIS1").unwrap_or(0).max(0) as usize;
+    for i in 1..=tfields {
+        let tform = match cards.string(&format!("TFORM{}", i)) {
+            Some(tform) => tform,
+            None => {
+                checker.error(None, format!("TFORM{} is missing", i));
+                continue;
+            }
+        };
+        let card = cards.position(&format!("TFORM{}", i)).map(|p| p + 1);
+        let width = match ascii_width(&tform) {
+            Some(width) => width,
+            None => {
+                checker.error(card, format!("TFORM{} = '{}' is not a valid ASCII table format", i, tform));
+                continue;
+            }
+        };
+        match cards.integer(&format!("TBCOL{}", i)) {
+            None => checker.error(None, format!("TBCOL{} is missing", i)),
+            Some(tbcol) if tbcol < 1 || (tbcol as usize).checked_add(width - 1).is_none_or(|last| last > naxis1) => checker.error(
+                cards.position(&format!("TBCOL{}", i)).map(|p| p + 1),
+                format!("column {} ({} characters from TBCOL{} = {}) does not fit in NAXIS1 = {}", i, width, i, tbcol, naxis1),
+            ),
+            Some(_) => {}
+        }
+    }
+}
+
+/// Width of an ASCII table field `Aw`, `Iw`, `Fw.d`, `Ew.d` or `Dw.d`.
+fn ascii_width(tform: &str) -> Option<usize> {
+    let mut chars = tform.chars();
+    let code = chars.next()?;
+    let rest = chars.as_str();
+    match code {
+        'A' | 'I' => rest.parse::<usize>().ok().filter(|width| *width > 0),
+        'F' | 'E' | 'D' => {
+            let (width, decimals) = rest.split_once('.')?;
+            decimals.parse::<usize>().ok()?;
+            width.parse::<usize>().ok().filter(|width| *width > 0)
+        }
+        _ => None,
+    }
+}
+
+fn check_binary_table(checker: &mut Checker, cards: &Cards) {
+    let tfields = check_column_indices(checker, cards);
+    let naxis1 = cards.integer("NAXIS1").unwrap_or(0).max(0) as usize;
+    let mut row: usize = 0;
+    for i in 1..=tfields {
+        let tform = match cards.string(&format!("TFORM{}", i)) {
+            Some(tform) => tform,
+            None => {
+                checker.error(None, format!("TFORM{} is missing", i));
+                return;
+            }
+        };
+        let digits = tform.chars().take_while(|c| c.is_ascii_digit()).count();
+        let code = tform[digits..].chars().next();
+        if !code.is_some_and(|code| "LXBIJKAEDCMPQ".contains(code)) {
+            checker.error(
+                cards.position(&format!("TFORM{}", i)).map(|p| p + 1),
+                format!("TFORM{} = '{}' is not a valid binary table format", i, tform),
+            );
+            return;
+        }
+        row = match BinaryField::field_width(&tform).and_then(|width| row.checked_add(width)) {
+            Some(row) => row,
+            None => {
+                checker.error(
+                    cards.position(&format!("TFORM{}", i)).map(|p| p + 1),
+                    format!("TFORM{} = '{}' makes the row too wide", i, tform),
+                );
+                return;
+            }
+        };
+    }
+    if row != naxis1 {
+        checker.error(
+            cards.position("NAXIS1").map(|p| p + 1),
+            format!("NAXIS1 = {} but the TFORMn fields add up to {} bytes", naxis1, row),
+        );
+    }
+}
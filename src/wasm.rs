@@ -0,0 +1,339 @@
+//! JavaScript API for rustfits-web, exported with `wasm_bindgen`.
+
+use crate::data::data::Data;
+use crate::data::tables::BinaryField;
+use crate::fits::{FITS, HDU};
+use crate::header::{Card, Header};
+use crate::render::{self, Colormap, Limits, RenderOptions, Stretch};
+use js_sys::{Array, Float32Array, Float64Array, Object, Reflect, Uint32Array, Uint8ClampedArray};
+use wasm_bindgen::prelude::*;
+
+/// A FITS file held in WebAssembly memory.
+#[wasm_bindgen]
+pub struct FitsFile {
+    fits: FITS,
+}
+
+/// Decoded pixels of an image HDU. `shape` lists NAXIS1 first, and NAXIS1 varies fastest
+/// in `data` as in the file.
+#[wasm_bindgen]
+pub struct ImageData {
+    shape: Vec<u32>,
+    bitpix: i32,
+    data: JsValue,
+}
+
+/// RGBA pixels of a rendered image, ready for `new ImageData(pixels, width, height)`.
+#[wasm_bindgen]
+pub struct RenderedImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl FitsFile {
+    /// Parse a whole file, e.g. the contents of a `Uint8Array`.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(bytes: &[u8]) -> FitsFile {
+        #[cfg(feature = "console_error_panic_hook")]
+        console_error_panic_hook::set_once();
+        FitsFile {
+            fits: FITS::new_from_buffer(bytes),
+        }
+    }
+
+    #[wasm_bindgen(js_name = hduCount)]
+    pub fn hdu_count(&self) -> usize {
+        self.fits.hdus.len()
+    }
+
+    /// Type of an HDU: "Primary", "Image", "ASCII Table", "Binary Table" or the XTENSION value.
+    #[wasm_bindgen(js_name = hduType)]
+    pub fn hdu_type(&self, index: usize) -> Result<String, JsError> {
+        Ok(self.header_at(index)?.get_header_type().to_string())
+    }
+
+    /// Problems found while reading the file, as messages.
+    pub fn diagnostics(&self) -> Vec<String> {
+        self.fits.diagnostics.iter().map(|diagnostic| diagnostic.to_string()).collect()
+    }
+
+    /// Header of an HDU as a plain object in card order. Quoted values become `string`,
+    /// unquoted numbers `number` and unquoted T and F `boolean`.
+    pub fn header(&self, index: usize) -> Result<Object, JsError> {
+        let object = Object::new();
+        for (keyword, value) in header_entries(self.header_at(index)?) {
+            let value = match value {
+                Value::Number(number) => JsValue::from_f64(number),
+                Value::Logical(logical) => JsValue::from_bool(logical),
+                Value::Text(text) => JsValue::from_str(&text),
+            };
+            Reflect::set(&object, &JsValue::from_str(&keyword), &value).map_err(|_| JsError::new("Cannot set header key"))?;
+        }
+        Ok(object)
+    }
+
+    /// Pixels of an image HDU with BSCALE and BZERO applied. The data is a `Float32Array` for
+    /// BITPIX 8, 16 and -32, and a `Float64Array` for 32, 64 and -64 so no precision is lost.
+    #[wasm_bindgen(js_name = imageData)]
+    pub fn image_data(&self, index: usize) -> Result<ImageData, JsError> {
+        let hdu = self.fits.hdus.get(index).ok_or_else(|| out_of_range(index))?;
+        let (shape, bitpix, pixels) = image_pixels(hdu).map_err(|error| JsError::new(&error))?;
+        let data: JsValue = match pixels {
+            Pixels::F32(values) => Float32Array::from(values.as_slice()).into(),
+            Pixels::F64(values) => Float64Array::from(values.as_slice()).into(),
+        };
+        Ok(ImageData { shape, bitpix, data })
+    }
+
+    /// Number of rows of a table HDU.
+    #[wasm_bindgen(js_name = rowCount)]
+    pub fn row_count(&self, index: usize) -> Result<usize, JsError> {
+        match &self.data_at(index)? {
+            Data::BinaryTable(table) => Ok(table.n_rows()),
+            Data::ASCIITable(table) => Ok(table.n_rows()),
+            _ => Err(JsError::new("HDU is not a table")),
+        }
+    }
+
+    /// Column metadata of a table HDU: an array of `{name, format, unit, repeat, type}` where
+    /// `type` is "number", "string" or "unsupported".
+    #[wasm_bindgen(js_name = tableColumns)]
+    pub fn table_columns(&self, index: usize) -> Result<Array, JsError> {
+        let columns = Array::new();
+        for info in column_info(self.data_at(index)?).map_err(|error| JsError::new(&error))? {
+            let object = Object::new();
+            let set = |key: &str, value: JsValue| Reflect::set(&object, &JsValue::from_str(key), &value);
+            set("name", JsValue::from_str(&info.name))
+                .and(set("format", JsValue::from_str(&info.format)))
+                .and(set("unit", JsValue::from_str(&info.unit)))
+                .and(set("repeat", JsValue::from_f64(info.repeat as f64)))
+                .and(set("type", JsValue::from_str(info.kind)))
+                .map_err(|_| JsError::new("Cannot set column metadata"))?;
+            columns.push(&object);
+        }
+        Ok(columns)
+    }
+
+    /// Values of a table column in rows `[start, end)`, the whole column by default. Numbers
+    /// come as a `Float64Array` with TSCALn and TZEROn applied, `repeat` values per row, and
+    /// text as an array of strings.
+    pub fn column(&self, index: usize, name: &str, start: Option<usize>, end: Option<usize>) -> Result<JsValue, JsError> {
+        let data = self.data_at(index)?;
+        let rows = start.unwrap_or(0)..end.unwrap_or(usize::MAX);
+        match column_values(data, name, rows).map_err(|error| JsError::new(&error))? {
+            Column::Numbers(values) => Ok(Float64Array::from(values.as_slice()).into()),
+            Column::Strings(values) => Ok(values.iter().map(|value| JsValue::from_str(value)).collect::<Array>().into()),
+        }
+    }
+
+    /// Render the first plane of an image HDU to RGBA, flipped so the first row is at the
+    /// bottom. `stretch` is linear, sqrt, log, asinh or histogram; `limits` is minmax,
+    /// zscale, percentile (with `low` and `high` in percent) or manual (with `low` and
+    /// `high` values); `colormap` is gray, heat, viridis, inferno, magma or plasma.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &self,
+        index: usize,
+        stretch: &str,
+        limits: &str,
+        colormap: &str,
+        low: Option<f64>,
+        high: Option<f64>,
+        invert: Option<bool>,
+    ) -> Result<RenderedImage, JsError> {
+        let hdu = self.fits.hdus.get(index).ok_or_else(|| out_of_range(index))?;
+        let options = RenderOptions {
+            stretch: Stretch::from_name(stretch).ok_or_else(|| JsError::new(&format!("Unknown stretch {:?}", stretch)))?,
+            limits: match limits.to_ascii_lowercase().as_str() {
+                "minmax" => Limits::MinMax,
+                "zscale" => Limits::ZScale,
+                "percentile" => Limits::Percentile(low.unwrap_or(0.5), high.unwrap_or(99.5)),
+                "manual" => match (low, high) {
+                    (Some(low), Some(high)) => Limits::Manual(low, high),
+                    _ => return Err(JsError::new("Manual limits need low and high values")),
+                },
+                _ => return Err(JsError::new(&format!("Unknown limits {:?}", limits))),
+            },
+            colormap: Colormap::from_name(colormap).ok_or_else(|| JsError::new(&format!("Unknown colormap {:?}", colormap)))?,
+            invert: invert.unwrap_or(false),
+        };
+        let image = render::render(hdu, &options).map_err(|error| JsError::new(&error))?;
+        Ok(RenderedImage {
+            width: image.width as u32,
+            height: image.height as u32,
+            pixels: image.pixels,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl RenderedImage {
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pixels(&self) -> Uint8ClampedArray {
+        Uint8ClampedArray::from(self.pixels.as_slice())
+    }
+}
+
+impl FitsFile {
+    fn data_at(&self, index: usize) -> Result<&Data, JsError> {
+        self.fits.hdus.get(index).map(|hdu| &hdu.data).ok_or_else(|| out_of_range(index))
+    }
+
+    fn header_at(&self, index: usize) -> Result<&Header, JsError> {
+        self.fits.hdus.get(index).map(|hdu| &hdu.header).ok_or_else(|| out_of_range(index))
+    }
+}
+
+#[wasm_bindgen]
+impl ImageData {
+    #[wasm_bindgen(getter)]
+    pub fn shape(&self) -> Uint32Array {
+        Uint32Array::from(self.shape.as_slice())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bitpix(&self) -> i32 {
+        self.bitpix
+    }
+
+    /// `Float32Array` or `Float64Array` of the pixels.
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> JsValue {
+        self.data.clone()
+    }
+}
+
+fn out_of_range(index: usize) -> JsError {
+    JsError::new(&format!("HDU {} out of range", index))
+}
+
+enum Value {
+    Number(f64),
+    Logical(bool),
+    Text(String),
+}
+
+struct ColumnInfo {
+    name: String,
+    format: String,
+    unit: String,
+    repeat: usize,
+    kind: &'static str,
+}
+
+enum Column {
+    Numbers(Vec<f64>),
+    Strings(Vec<String>),
+}
+
+enum Pixels {
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+}
+
+/// Keywords and typed values of a header in card order, without commentary cards. Quoted
+/// values stay strings, as written apart from the trailing spaces.
+fn header_entries(header: &Header) -> Vec<(String, Value)> {
+    header
+        .cards()
+        .iter()
+        .map(|card| Card::parse(card))
+        .filter_map(|card| {
+            let text = card.value_text()?;
+            let raw = card.value.as_deref().unwrap_or("");
+            let value = if raw.starts_with('\'') {
+                Value::Text(text)
+            } else if raw == "T" || raw == "F" {
+                Value::Logical(raw == "T")
+            } else {
+                match raw.replace(['D', 'd'], "E").parse::<f64>() {
+                    Ok(number) => Value::Number(number),
+                    Err(_) => Value::Text(text),
+                }
+            };
+            Some((card.keyword, value))
+        })
+        .collect()
+}
+
+fn image_pixels(hdu: &HDU) -> Result<(Vec<u32>, i32, Pixels), String> {
+    let naxisn = hdu.header.get_naxisn();
+    let physical = render::physical_values(hdu, naxisn.iter().product())?.into_iter();
+    let bitpix = hdu.header.get_f64("BITPIX").unwrap_or(0.0) as i32;
+    let pixels = match bitpix {
+        8 | 16 | -32 => Pixels::F32(physical.map(|value| value as f32).collect()),
+        _ => Pixels::F64(physical.collect()),
+    };
+    let shape = naxisn.iter().map(|n| *n as u32).collect();
+    Ok((shape, bitpix, pixels))
+}
+
+fn column_info(data: &Data) -> Result<Vec<ColumnInfo>, String> {
+    let (names, formats, units, binary) = match data {
+        Data::BinaryTable(table) => (table.column_names(), table.get_tformn(), table.get_tunitn(), true),
+        Data::ASCIITable(table) => (table.column_names(), table.get_tformn(), table.get_tunitn(), false),
+        _ => return Err("HDU is not a table".to_string()),
+    };
+    let info = names
+        .iter()
+        .zip(formats.iter())
+        .zip(units.iter())
+        .map(|((name, format), unit)| {
+            let (repeat, kind) = if binary {
+                let (repeat, code) = BinaryField::parse_tform(format).unwrap_or((0, ' '));
+                let kind = match code {
+                    'A' => "string",
+                    code if "LBIJKED".contains(code) => "number",
+                    _ => "unsupported",
+                };
+                (if code == 'A' { 1 } else { repeat }, kind)
+            } else {
+                (1, if format.starts_with('A') { "string" } else { "number" })
+            };
+            ColumnInfo {
+                name: name.clone(),
+                format: format.clone(),
+                unit: unit.clone(),
+                repeat,
+                kind,
+            }
+        })
+        .collect();
+    Ok(info)
+}
+
+fn column_values(data: &Data, name: &str, rows: std::ops::Range<usize>) -> Result<Column, String> {
+    let missing = || format!("No column named {:?}", name);
+    match data {
+        Data::BinaryTable(table) => {
+            let column = table.column_index(name).ok_or_else(missing)?;
+            if let Some(values) = table.column_strings_rows(column, rows.clone()) {
+                return Ok(Column::Strings(values));
+            }
+            let values = table
+                .column_f64_rows(column, rows)
+                .ok_or_else(|| format!("Column {:?} has an unsupported format", name))?;
+            Ok(Column::Numbers(values.concat()))
+        }
+        Data::ASCIITable(table) => {
+            let column = table.column_index(name).ok_or_else(missing)?;
+            match table.column_f64_rows(column, rows.clone()) {
+                Some(values) => Ok(Column::Numbers(values)),
+                None => Ok(Column::Strings(table.column_strings_rows(column, rows))),
+            }
+        }
+        _ => Err("HDU is not a table".to_string()),
+    }
+}
@@ -0,0 +1,150 @@
+use crate::header::Header;
+
+/// Polynomial distortion applied on top of the linear WCS transformation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distortion {
+    SIP(SIP),
+    TPV(TPV),
+}
+
+/// Simple Imaging Polynomial (Shupe et al. 2005), applied to pixel offsets from CRPIX
+/// before the CD matrix. Used by HST, Spitzer and ZTF.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SIP {
+    /// Forward coefficients (p, q, value) of u^p v^q
+    pub a: Vec<(i32, i32, f64)>,
+    pub b: Vec<(i32, i32, f64)>,
+    /// Optional inverse coefficients, used as the starting point of the iterative inverse
+    pub ap: Vec<(i32, i32, f64)>,
+    pub bp: Vec<(i32, i32, f64)>,
+}
+
+impl SIP {
+    pub fn from_header(header: &Header) -> SIP {
+        SIP {
+            a: sip_coefficients(header, "A"),
+            b: sip_coefficients(header, "B"),
+            ap: sip_coefficients(header, "AP"),
+            bp: sip_coefficients(header, "BP"),
+        }
+    }
+
+    /// Distorted offsets (u + f(u, v), v + g(u, v)).
+    pub fn forward(&self, u: f64, v: f64) -> (f64, f64) {
+        (u + polynomial(&self.a, u, v), v + polynomial(&self.b, u, v))
+    }
+
+    /// Undistorted offsets (u, v) from distorted ones, refined iteratively against `forward`.
+    pub fn inverse(&self, u: f64, v: f64) -> (f64, f64) {
+        let guess = (u + polynomial(&self.ap, u, v), v + polynomial(&self.bp, u, v));
+        solve((u, v), guess, |u, v| self.forward(u, v))
+    }
+}
+
+/// TPV polynomial (the SCAMP/DECam convention), applied to the intermediate world
+/// coordinates in degrees, with coefficients PV1_k for the first axis and PV2_k for the second.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TPV {
+    /// Coefficients of the 40 TPV terms for each axis
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+}
+
+impl TPV {
+    /// Read PV{lng}_k{alt} and PV{lat}_k{alt}, where `lng` and `lat` are 1-based axis numbers.
+    pub fn from_header(header: &Header, lng: usize, lat: usize, alt: &str) -> TPV {
+        let read = |axis: usize| -> Vec<f64> {
+            let mut coefficients = vec![0.0; 40];
+            let mut any = false;
+            for (k, coefficient) in coefficients.iter_mut().enumerate() {
+                if let Some(value) = header.get_f64(&format!("PV{}_{}{}", axis, k, alt)) {
+                    *coefficient = value;
+                    any = true;
+                }
+            }
+            if !any {
+                coefficients[1] = 1.0;
+            }
+            coefficients
+        };
+        TPV {
+            x: read(lng),
+            y: read(lat),
+        }
+    }
+
+    pub fn forward(&self, x: f64, y: f64) -> (f64, f64) {
+        (tpv_polynomial(&self.x, x, y), tpv_polynomial(&self.y, y, x))
+    }
+
+    pub fn inverse(&self, x: f64, y: f64) -> (f64, f64) {
+        solve((x, y), (x, y), |x, y| self.forward(x, y))
+    }
+}
+
+fn sip_coefficients(header: &Header, prefix: &str) -> Vec<(i32, i32, f64)> {
+    let order = header
+        .get_f64(&format!("{}_ORDER", prefix))
+        .map(|order| order as i32)
+        .unwrap_or(-1);
+    let mut coefficients = Vec::new();
+    for p in 0..=order {
+        for q in 0..=(order - p) {
+            if let Some(value) = header.get_f64(&format!("{}_{}_{}", prefix, p, q)) {
+                coefficients.push((p, q, value));
+            }
+        }
+    }
+    coefficients
+}
+
+fn polynomial(coefficients: &[(i32, i32, f64)], u: f64, v: f64) -> f64 {
+    coefficients
+        .iter()
+        .map(|(p, q, value)| value * u.powi(*p) * v.powi(*q))
+        .sum()
+}
+
+/// TPV polynomial of (x, y); the second axis uses the same terms with x and y swapped.
+fn tpv_polynomial(coefficients: &[f64], x: f64, y: f64) -> f64 {
+    let r = x.hypot(y);
+    let mut terms = [0.0; 40];
+    terms[0] = 1.0;
+    let mut k = 1;
+    for degree in 1..=7 {
+        for i in 0..=degree {
+            terms[k] = x.powi(degree - i) * y.powi(i);
+            k += 1;
+        }
+        if degree % 2 == 1 {
+            terms[k] = r.powi(degree);
+            k += 1;
+        }
+    }
+    coefficients.iter().zip(terms.iter()).map(|(c, t)| c * t).sum()
+}
+
+/// Find (u, v) such that `function(u, v) == target` by Newton iteration with a numerical
+/// Jacobian, starting from `guess`.
+fn solve<F: Fn(f64, f64) -> (f64, f64)>(target: (f64, f64), guess: (f64, f64), function: F) -> (f64, f64) {
+    let (mut u, mut v) = guess;
+    for _ in 0..50 {
+        let (fu, fv) = function(u, v);
+        let (ru, rv) = (fu - target.0, fv - target.1);
+        if ru.abs().max(rv.abs()) < 1e-12 * (1.0 + target.0.abs().max(target.1.abs())) {
+            break;
+        }
+        let h = 1e-6 * (1.0 + u.abs().max(v.abs()));
+        let (du_u, dv_u) = function(u + h, v);
+        let (du_v, dv_v) = function(u, v + h);
+        let (j11, j21) = ((du_u - fu) / h, (dv_u - fv) / h);
+        let (j12, j22) = ((du_v - fu) / h, (dv_v - fv) / h);
+        let determinant = j11 * j22 - j12 * j21;
+        if determinant == 0.0 {
+            break;
+        }
+        u -= (j22 * ru - j12 * rv) / determinant;
+        v -= (-j21 * ru + j11 * rv) / determinant;
+    }
+    (u, v)
+}
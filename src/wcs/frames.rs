@@ -0,0 +1,276 @@
+use crate::header::Header;
+
+/// Celestial reference frames. Equinoxes are Julian epochs for FK5 and ecliptic coordinates,
+/// and Besselian epochs for FK4.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Frame {
+    ICRS,
+    FK5 { equinox: f64 },
+    FK4 { equinox: f64 },
+    /// FK4 without the elliptic terms of aberration, RADESYS = 'FK4-NO-E'
+    FK4NoETerms { equinox: f64 },
+    Galactic,
+    /// Mean ecliptic and equinox, relative to FK5 at the same equinox
+    Ecliptic { equinox: f64 },
+}
+
+impl Frame {
+    /// Frame of the celestial axes of a header, from the CTYPE prefix, RADESYSa and EQUINOXa,
+    /// with the defaults of Calabretta & Greisen (2002). `None` for frames that cannot be
+    /// converted: RADESYS values other than ICRS, FK5, FK4 and FK4-NO-E (e.g. GAPPT), and
+    /// celestial axes other than RA/DEC, GLON/GLAT and ELON/ELAT (helioprojective,
+    /// supergalactic, planetary, ...).
+    pub fn from_header(header: &Header, ctype: &str, alt: &str) -> Option<Frame> {
+        let equinox = header
+            .get_f64(&format!("EQUINOX{}", alt))
+            .or_else(|| header.get_f64("EPOCH"));
+        match ctype.get(..4).unwrap_or("") {
+            "GLON" | "GLAT" => return Some(Frame::Galactic),
+            "ELON" | "ELAT" => {
+                return Some(Frame::Ecliptic {
+                    equinox: equinox.unwrap_or(2000.0),
+                })
+            }
+            "RA--" | "DEC-" => {}
+            _ => return None,
+        }
+        let radesys = header.get_value(&format!("RADESYS{}", alt)).or_else(|| header.get_value("RADECSYS"));
+        match radesys {
+            Some("ICRS") => Some(Frame::ICRS),
+            Some("FK5") => Some(Frame::FK5 {
+                equinox: equinox.unwrap_or(2000.0),
+            }),
+            Some("FK4") => Some(Frame::FK4 {
+                equinox: equinox.unwrap_or(1950.0),
+            }),
+            Some("FK4-NO-E") => Some(Frame::FK4NoETerms {
+                equinox: equinox.unwrap_or(1950.0),
+            }),
+            Some(_) => None,
+            None => match equinox {
+                Some(equinox) if equinox < 1984.0 => Some(Frame::FK4 { equinox }),
+                Some(equinox) => Some(Frame::FK5 { equinox }),
+                None => Some(Frame::ICRS),
+            },
+        }
+    }
+
+    /// Convert (longitude, latitude) in degrees from this frame to another.
+    pub fn convert(&self, lon: f64, lat: f64, target: Frame) -> (f64, f64) {
+        if *self == target {
+            return (lon, lat);
+        }
+        let vector = target.rotate_from_icrs(self.rotate_to_icrs(to_vector(lon, lat)));
+        from_vector(vector)
+    }
+
+    fn rotate_to_icrs(&self, vector: [f64; 3]) -> [f64; 3] {
+        match *self {
+            Frame::ICRS => vector,
+            Frame::FK5 { equinox } => fk5_j2000_to_icrs(apply(&precession_iau1976(equinox, 2000.0), vector)),
+            Frame::FK4 { equinox } => {
+                let vector = apply(&precession_newcomb(equinox, 1950.0), vector);
+                fk5_j2000_to_icrs(apply(&FK4_TO_FK5, remove_e_terms(vector)))
+            }
+            Frame::FK4NoETerms { equinox } => {
+                let vector = apply(&precession_newcomb(equinox, 1950.0), vector);
+                fk5_j2000_to_icrs(apply(&FK4_TO_FK5, vector))
+            }
+            Frame::Galactic => fk5_j2000_to_icrs(apply(&transpose(&galactic_matrix()), vector)),
+            Frame::Ecliptic { equinox } => {
+                let vector = apply(&transpose(&rotation_x(obliquity(equinox))), vector);
+                Frame::FK5 { equinox }.rotate_to_icrs(vector)
+            }
+        }
+    }
+
+    fn rotate_from_icrs(&self, vector: [f64; 3]) -> [f64; 3] {
+        match *self {
+            Frame::ICRS => vector,
+            Frame::FK5 { equinox } => apply(&precession_iau1976(2000.0, equinox), icrs_to_fk5_j2000(vector)),
+            Frame::FK4 { equinox } => {
+                let vector = add_e_terms(apply(&invert(&FK4_TO_FK5), icrs_to_fk5_j2000(vector)));
+                apply(&precession_newcomb(1950.0, equinox), vector)
+            }
+            Frame::FK4NoETerms { equinox } => {
+                let vector = apply(&invert(&FK4_TO_FK5), icrs_to_fk5_j2000(vector));
+                apply(&precession_newcomb(1950.0, equinox), vector)
+            }
+            Frame::Galactic => apply(&galactic_matrix(), icrs_to_fk5_j2000(vector)),
+            Frame::Ecliptic { equinox } => apply(&rotation_x(obliquity(equinox)), Frame::FK5 { equinox }.rotate_from_icrs(vector)),
+        }
+    }
+}
+
+/// FK4 B1950 (without E-terms) to FK5 J2000 at epoch B1950, with no proper motion (Aoki et al. 1983).
+const FK4_TO_FK5: [[f64; 3]; 3] = [
+    [0.999_925_678_2, -0.011_182_061_1, -0.004_857_947_7],
+    [0.011_182_061_0, 0.999_937_478_4, -0.000_027_176_5],
+    [0.004_857_947_9, -0.000_027_147_4, 0.999_988_199_7],
+];
+
+/// Elliptic terms of aberration at B1950, in radians.
+const E_TERMS: [f64; 3] = [-1.62557e-6, -0.31919e-6, -0.13843e-6];
+
+const ARCSEC: f64 = std::f64::consts::PI / (180.0 * 3600.0);
+
+fn to_vector(lon: f64, lat: f64) -> [f64; 3] {
+    let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+    let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+    [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat]
+}
+
+fn from_vector(vector: [f64; 3]) -> (f64, f64) {
+    let lon = vector[1].atan2(vector[0]).to_degrees().rem_euclid(360.0);
+    let lat = vector[2].atan2(vector[0].hypot(vector[1])).to_degrees();
+    (lon, lat)
+}
+
+fn apply(matrix: &[[f64; 3]; 3], vector: [f64; 3]) -> [f64; 3] {
+    let mut result = [0.0; 3];
+    for (i, row) in matrix.iter().enumerate() {
+        result[i] = row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2];
+    }
+    result
+}
+
+fn product(a: &[[f64; 3]; 3], b: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut result = [[0.0; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            result[i][j] = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    result
+}
+
+fn transpose(matrix: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut result = [[0.0; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            result[i][j] = matrix[j][i];
+        }
+    }
+    result
+}
+
+/// Inverse of a 3x3 matrix by cofactors. `FK4_TO_FK5` is not exactly orthogonal, so its
+/// transpose is not an exact inverse.
+fn invert(matrix: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let m = matrix;
+    let cofactor = |i: usize, j: usize| {
+        let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+        let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let determinant: f64 = (0..3).map(|j| m[0][j] * cofactor(0, j)).sum();
+    let row = |j: usize| [cofactor(0, j) / determinant, cofactor(1, j) / determinant, cofactor(2, j) / determinant];
+    [row(0), row(1), row(2)]
+}
+
+/// Rotation of the coordinate frame by `angle` radians about the x axis.
+fn rotation_x(angle: f64) -> [[f64; 3]; 3] {
+    let (sin, cos) = angle.sin_cos();
+    [[1.0, 0.0, 0.0], [0.0, cos, sin], [0.0, -sin, cos]]
+}
+
+fn rotation_y(angle: f64) -> [[f64; 3]; 3] {
+    let (sin, cos) = angle.sin_cos();
+    [[cos, 0.0, -sin], [0.0, 1.0, 0.0], [sin, 0.0, cos]]
+}
+
+fn rotation_z(angle: f64) -> [[f64; 3]; 3] {
+    let (sin, cos) = angle.sin_cos();
+    [[cos, sin, 0.0], [-sin, cos, 0.0], [0.0, 0.0, 1.0]]
+}
+
+/// Frame bias from ICRS to FK5 J2000 (IERS Conventions 2003).
+fn icrs_to_fk5_matrix() -> [[f64; 3]; 3] {
+    let eta_0 = -0.006_819_2 * ARCSEC;
+    let xi_0 = -0.016_617_0 * ARCSEC;
+    let da_0 = -0.014_6 * ARCSEC;
+    product(&product(&rotation_x(-eta_0), &rotation_y(xi_0)), &rotation_z(da_0))
+}
+
+fn icrs_to_fk5_j2000(vector: [f64; 3]) -> [f64; 3] {
+    apply(&icrs_to_fk5_matrix(), vector)
+}
+
+fn fk5_j2000_to_icrs(vector: [f64; 3]) -> [f64; 3] {
+    apply(&transpose(&icrs_to_fk5_matrix()), vector)
+}
+
+/// FK5 J2000 to Galactic, from the north galactic pole and the galactic longitude of the
+/// north celestial pole in FK5 J2000.
+fn galactic_matrix() -> [[f64; 3]; 3] {
+    let (ra_ngp, dec_ngp, lon_ncp) = (192.859_481_206_534_8_f64, 27.128_251_180_856_22_f64, 122.931_918_568_002_6_f64);
+    product(
+        &product(&rotation_z((180.0 - lon_ncp).to_radians()), &rotation_y((90.0 - dec_ngp).to_radians())),
+        &rotation_z(ra_ngp.to_radians()),
+    )
+}
+
+/// Mean obliquity of the ecliptic at a Julian epoch (IAU 1976), in radians.
+fn obliquity(equinox: f64) -> f64 {
+    let t = (equinox - 2000.0) / 100.0;
+    (84381.448 + (-46.8150 + (-0.00059 + 0.001813 * t) * t) * t) * ARCSEC
+}
+
+/// Precession matrix between two Julian epochs (IAU 1976, Lieske et al. 1977).
+fn precession_iau1976(from: f64, to: f64) -> [[f64; 3]; 3] {
+    let t0 = (from - 2000.0) / 100.0;
+    let t = (to - from) / 100.0;
+    let w = 2306.2181 + (1.39656 - 0.000139 * t0) * t0;
+    let zeta = (w + ((0.30188 - 0.000344 * t0) + 0.017998 * t) * t) * t * ARCSEC;
+    let z = (w + ((1.09468 + 0.000066 * t0) + 0.018203 * t) * t) * t * ARCSEC;
+    let theta = ((2004.3109 + (-0.85330 - 0.000217 * t0) * t0) + ((-0.42665 - 0.000217 * t0) - 0.041833 * t) * t) * t * ARCSEC;
+    euler_zyz(-zeta, theta, -z)
+}
+
+/// Precession matrix between two Besselian epochs (Newcomb, as used for FK4).
+fn precession_newcomb(from: f64, to: f64) -> [[f64; 3]; 3] {
+    let t0 = (from - 1850.0) / 100.0;
+    let t = (to - from) / 100.0;
+    let w = 2303.5548 + (1.39720 + 0.000059 * t0) * t0;
+    let zeta = (w + (0.30242 - 0.000269 * t0 + 0.017996 * t) * t) * t * ARCSEC;
+    let z = (w + (1.09478 + 0.000387 * t0 + 0.018324 * t) * t) * t * ARCSEC;
+    let theta = (2005.1125 + (-0.85294 - 0.000365 * t0) * t0 + (-0.42647 - 0.000365 * t0 - 0.041802 * t) * t) * t * ARCSEC;
+    euler_zyz(-zeta, theta, -z)
+}
+
+/// Successive frame rotations about z, then y, then z.
+fn euler_zyz(first: f64, second: f64, third: f64) -> [[f64; 3]; 3] {
+    product(&rotation_z(third), &product(&rotation_y(second), &rotation_z(first)))
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn normalize(vector: [f64; 3]) -> [f64; 3] {
+    let norm = dot(vector, vector).sqrt();
+    [vector[0] / norm, vector[1] / norm, vector[2] / norm]
+}
+
+fn remove_e_terms(vector: [f64; 3]) -> [f64; 3] {
+    let projection = dot(vector, E_TERMS);
+    normalize([
+        vector[0] - E_TERMS[0] + projection * vector[0],
+        vector[1] - E_TERMS[1] + projection * vector[1],
+        vector[2] - E_TERMS[2] + projection * vector[2],
+    ])
+}
+
+/// Inverse of `remove_e_terms`, iterated since the E-terms depend on the apparent position.
+fn add_e_terms(vector: [f64; 3]) -> [f64; 3] {
+    let mut apparent = vector;
+    for _ in 0..5 {
+        let projection = dot(apparent, E_TERMS);
+        apparent = normalize([
+            vector[0] + E_TERMS[0] - projection * apparent[0],
+            vector[1] + E_TERMS[1] - projection * apparent[1],
+            vector[2] + E_TERMS[2] - projection * apparent[2],
+        ]);
+    }
+    apparent
+}
@@ -0,0 +1,661 @@
+pub mod distortion;
+pub mod frames;
+pub mod projection;
+pub mod spectral;
+pub mod table;
+
+use crate::data::data::Data;
+use crate::fits::FITS;
+use crate::header::Header;
+use distortion::{Distortion, SIP, TPV};
+use frames::Frame;
+use ndarray::{Array2, ArrayView2};
+use projection::Projection;
+use spectral::{Algorithm, Rest, SpectralAxis, TableReference};
+
+/// World Coordinate System read from an image header, following Greisen & Calabretta (2002)
+/// (Paper I) for the linear part and Calabretta & Greisen (2002) (Paper II) for celestial axes.
+///
+/// Pixel coordinates are 1-based as in the FITS standard, so the centre of the first pixel
+/// is 1.0. Axes are in header order (axis 0 is NAXIS1). Celestial coordinates are in degrees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WCS {
+    /// Letter of the description, empty for the primary one
+    pub alt: String,
+    /// WCSNAMEa
+    pub name: Option<String>,
+    pub naxis: usize,
+    pub crpix: Vec<f64>,
+    pub crval: Vec<f64>,
+    pub cdelt: Vec<f64>,
+    pub ctype: Vec<String>,
+    pub cunit: Vec<String>,
+    /// Linear transformation matrix from pixel offsets to intermediate world coordinates,
+    /// i.e. CDi_j, or CDELTi * PCi_j
+    pub matrix: Vec<Vec<f64>>,
+    inverse: Vec<Vec<f64>>,
+    celestial: Option<Celestial>,
+    spectral: Option<SpectralAxis>,
+    pub distortion: Option<Distortion>,
+}
+
+/// Celestial axis pair and the spherical rotation between native and celestial coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Celestial {
+    /// Index of the longitude axis
+    pub lng: usize,
+    /// Index of the latitude axis
+    pub lat: usize,
+    pub projection: Projection,
+    /// Native coordinates of the fiducial point
+    pub phi_0: f64,
+    pub theta_0: f64,
+    /// Celestial coordinates of the native pole
+    pub alpha_p: f64,
+    pub delta_p: f64,
+    /// Native longitude of the celestial pole (LONPOLE)
+    pub phi_p: f64,
+    /// Reference frame of the celestial coordinates, `None` if it is not one that can be
+    /// converted (see [`Frame::from_header`])
+    pub frame: Option<Frame>,
+    /// Factor from the axis units to degrees
+    unit_scale: [f64; 2],
+}
+
+impl WCS {
+    /// Read the WCS of a header. Spectral `-TAB` axes need the lookup table from another HDU,
+    /// so they are only supported through [`WCS::from_fits`].
+    pub fn from_header(header: &Header) -> Result<WCS, String> {
+        WCS::build(header, None, ' ')
+    }
+
+    /// Read the alternate description `alt` ('A' to 'Z') of a header, or the primary one for ' '.
+    pub fn from_header_alt(header: &Header, alt: char) -> Result<WCS, String> {
+        WCS::build(header, None, alt)
+    }
+
+    /// Read the description of a header whose WCSNAMEa is `name`.
+    pub fn from_header_name(header: &Header, name: &str) -> Result<WCS, String> {
+        WCS::build(header, None, WCS::find_name(header, name)?)
+    }
+
+    /// Read the WCS of one HDU, resolving `-TAB` lookup tables from the other HDUs of the file.
+    pub fn from_fits(fits: &FITS, index: usize) -> Result<WCS, String> {
+        WCS::from_fits_alt(fits, index, ' ')
+    }
+
+    /// Read the alternate description `alt` of one HDU, see [`WCS::from_fits`].
+    pub fn from_fits_alt(fits: &FITS, index: usize, alt: char) -> Result<WCS, String> {
+        let hdu = fits.hdus.get(index).ok_or_else(|| format!("HDU {} out of range", index))?;
+        WCS::build(&hdu.header, Some(fits), alt)
+    }
+
+    /// WCS of table columns from their pixel-list keywords (TCTYPn, TCRPXn, TCRVLn, TCDLTn,
+    /// TPn_ka, ...). `columns` are 0-based column indices in axis order, and `alt` selects an
+    /// alternate description as in [`WCS::from_header_alt`].
+    pub fn from_table(header: &Header, columns: &[usize], alt: char) -> Result<WCS, String> {
+        WCS::build(&WCS::table_header(header, columns, alt)?, None, alt)
+    }
+
+    /// WCS of table columns given by TTYPEn name, ignoring case, see [`WCS::from_table`].
+    pub fn from_columns(header: &Header, columns: &[&str], alt: char) -> Result<WCS, String> {
+        let tfields = header.get_f64("TFIELDS").unwrap_or(0.0) as usize;
+        let indices = columns
+            .iter()
+            .map(|name| {
+                (0..tfields)
+                    .find(|i| {
+                        header
+                            .get_value(&format!("TTYPE{}", i + 1))
+                            .is_some_and(|ttype| ttype.eq_ignore_ascii_case(name))
+                    })
+                    .ok_or_else(|| format!("No column named {:?}", name))
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+        WCS::from_table(header, &indices, alt)
+    }
+
+    /// Image header holding the WCS keywords of table columns, see [`WCS::from_table`].
+    pub fn table_header(header: &Header, columns: &[usize], alt: char) -> Result<Header, String> {
+        if alt != ' ' && !alt.is_ascii_uppercase() {
+            return Err(format!("Invalid alternate WCS letter {:?}", alt));
+        }
+        let numbers: Vec<usize> = columns.iter().map(|column| column + 1).collect();
+        let mut cards = vec![
+            Header::format_card("SIMPLE", "T"),
+            Header::format_card("NAXIS", "0"),
+            Header::format_card(&format!("WCSAXES{}", suffix(alt)), &columns.len().to_string()),
+        ];
+        for (keyword, value) in table::image_keywords(header, &numbers, suffix(alt)) {
+            cards.push(Header::format_card(&keyword, &value));
+        }
+        Ok(Header::from_cards(&cards))
+    }
+
+    /// Letters of the descriptions present in a header, ' ' for the primary one first.
+    /// The primary description is always listed since every keyword has a default.
+    pub fn alternates(header: &Header) -> Vec<char> {
+        let mut letters: Vec<char> = header
+            .list_keywords(false)
+            .iter()
+            .filter_map(|(keyword, _)| alternate_letter(keyword))
+            .collect();
+        letters.sort_unstable();
+        letters.dedup();
+        letters.insert(0, ' ');
+        letters
+    }
+
+    /// Names (WCSNAMEa) of the descriptions present in a header, in the order of [`WCS::alternates`].
+    pub fn names(header: &Header) -> Vec<(char, Option<String>)> {
+        WCS::alternates(header)
+            .into_iter()
+            .map(|alt| (alt, header.get_value(&format!("WCSNAME{}", suffix(alt))).map(|name| name.to_string())))
+            .collect()
+    }
+
+    fn find_name(header: &Header, name: &str) -> Result<char, String> {
+        let names = WCS::names(header);
+        // Header values are stored without whitespace, so compare names the same way
+        let wanted: String = name.chars().filter(|c| !c.is_whitespace()).collect();
+        names
+            .iter()
+            .find(|(_, candidate)| candidate.as_deref().is_some_and(|candidate| candidate.eq_ignore_ascii_case(&wanted)))
+            .map(|(alt, _)| *alt)
+            .ok_or_else(|| {
+                let available: Vec<&str> = names.iter().filter_map(|(_, name)| name.as_deref()).collect();
+                format!("No WCS named {:?}, available: {:?}", name, available)
+            })
+    }
+
+    fn build(header: &Header, fits: Option<&FITS>, alt: char) -> Result<WCS, String> {
+        if alt != ' ' && !alt.is_ascii_uppercase() {
+            return Err(format!("Invalid alternate WCS letter {:?}", alt));
+        }
+        let name = header.get_value(&format!("WCSNAME{}", suffix(alt))).map(|name| name.to_string());
+        let alt = suffix(alt);
+        let naxis = match header.get_f64(&format!("WCSAXES{}", alt)) {
+            Some(value) => value as usize,
+            None => header.get_f64("NAXIS").unwrap_or(0.0) as usize,
+        };
+        let float = |keyword: String, default: f64| header.get_f64(&(keyword + alt)).unwrap_or(default);
+        let text = |keyword: String| header.get_value(&(keyword + alt)).unwrap_or("").to_string();
+
+        let crpix: Vec<f64> = (1..=naxis).map(|i| float(format!("CRPIX{}", i), 0.0)).collect();
+        let crval: Vec<f64> = (1..=naxis).map(|i| float(format!("CRVAL{}", i), 0.0)).collect();
+        let mut cdelt: Vec<f64> = (1..=naxis).map(|i| float(format!("CDELT{}", i), 1.0)).collect();
+        let ctype: Vec<String> = (1..=naxis).map(|i| text(format!("CTYPE{}", i))).collect();
+        let cunit: Vec<String> = (1..=naxis).map(|i| text(format!("CUNIT{}", i))).collect();
+
+        let has_cd = (1..=naxis).any(|i| (1..=naxis).any(|j| header.contains_keyword(&format!("CD{}_{}{}", i, j, alt))));
+        let has_pc = (1..=naxis).any(|i| (1..=naxis).any(|j| header.contains_keyword(&format!("PC{}_{}{}", i, j, alt))));
+        let mut matrix = vec![vec![0.0; naxis]; naxis];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, element) in row.iter_mut().enumerate() {
+                let identity = if i == j { 1.0 } else { 0.0 };
+                *element = if has_cd && !has_pc {
+                    float(format!("CD{}_{}", i + 1, j + 1), 0.0)
+                } else {
+                    cdelt[i] * float(format!("PC{}_{}", i + 1, j + 1), identity)
+                };
+            }
+        }
+        if has_cd && !has_pc {
+            cdelt = vec![1.0; naxis];
+        }
+
+        let celestial = Celestial::from_header(header, &ctype, &cunit, &crval, alt)?;
+        // Old-style CROTAi rotation of the celestial axes, given on the latitude axis i and
+        // only used without PC or CD
+        let crota = celestial.as_ref().and_then(|celestial| header.get_f64(&format!("CROTA{}", celestial.lat + 1)));
+        if let (Some(celestial), Some(crota)) = (&celestial, crota) {
+            if !has_cd && !has_pc && alt.is_empty() {
+                let (lng, lat) = (celestial.lng, celestial.lat);
+                let (sin, cos) = crota.to_radians().sin_cos();
+                matrix[lng][lng] = cdelt[lng] * cos;
+                matrix[lng][lat] = -cdelt[lat] * sin;
+                matrix[lat][lng] = cdelt[lng] * sin;
+                matrix[lat][lat] = cdelt[lat] * cos;
+            }
+        }
+        let inverse = invert(&matrix).ok_or("The WCS linear transformation matrix is singular")?;
+        let mut spectral: Option<SpectralAxis> = None;
+        for i in 0..naxis {
+            if let Some(axis) = SpectralAxis::new(i, &ctype[i], crval[i], &cunit[i], Rest::from_header(header, alt))? {
+                spectral = Some(axis);
+                break;
+            }
+        }
+        if let Some(axis) = &mut spectral {
+            if let Algorithm::Table { .. } = axis.algorithm {
+                let reference = table_reference(header, axis.axis, alt);
+                let fits = fits.ok_or("-TAB spectral axes need the whole file, use WCS::from_fits")?;
+                let (index, coordinates) = read_table(fits, &reference)?;
+                axis.set_table(index, coordinates);
+            }
+        }
+        let distortion = match &celestial {
+            Some(celestial) if ctype[celestial.lng].ends_with("-SIP") => {
+                if naxis < 2 {
+                    return Err("SIP distortion needs two pixel axes".to_string());
+                }
+                Some(Distortion::SIP(SIP::from_header(header)))
+            }
+            Some(celestial) if ctype[celestial.lng].get(5..8) == Some("TPV") => {
+                Some(Distortion::TPV(TPV::from_header(header, celestial.lng + 1, celestial.lat + 1, alt)))
+            }
+            _ => None,
+        };
+        Ok(WCS {
+            alt: alt.to_string(),
+            name,
+            naxis,
+            crpix,
+            crval,
+            cdelt,
+            ctype,
+            cunit,
+            matrix,
+            inverse,
+            celestial,
+            spectral,
+            distortion,
+        })
+    }
+
+    pub fn celestial(&self) -> Option<&Celestial> {
+        self.celestial.as_ref()
+    }
+
+    pub fn spectral(&self) -> Option<&SpectralAxis> {
+        self.spectral.as_ref()
+    }
+
+    /// Intermediate world coordinates of a pixel, including any distortion.
+    pub fn pix2intermediate(&self, pixel: &[f64]) -> Vec<f64> {
+        let mut offset: Vec<f64> = (0..self.naxis).map(|j| pixel[j] - self.crpix[j]).collect();
+        if let Some(Distortion::SIP(sip)) = &self.distortion {
+            let (u, v) = sip.forward(offset[0], offset[1]);
+            offset[0] = u;
+            offset[1] = v;
+        }
+        let mut intermediate = multiply(&self.matrix, &offset);
+        if let (Some(Distortion::TPV(tpv)), Some(celestial)) = (&self.distortion, &self.celestial) {
+            let (x, y) = tpv.forward(intermediate[celestial.lng], intermediate[celestial.lat]);
+            intermediate[celestial.lng] = x;
+            intermediate[celestial.lat] = y;
+        }
+        intermediate
+    }
+
+    /// Pixel of intermediate world coordinates, inverting any distortion iteratively.
+    pub fn intermediate2pix(&self, intermediate: &[f64]) -> Vec<f64> {
+        let mut intermediate = intermediate.to_vec();
+        if let (Some(Distortion::TPV(tpv)), Some(celestial)) = (&self.distortion, &self.celestial) {
+            let (x, y) = tpv.inverse(intermediate[celestial.lng], intermediate[celestial.lat]);
+            intermediate[celestial.lng] = x;
+            intermediate[celestial.lat] = y;
+        }
+        let mut offset = multiply(&self.inverse, &intermediate);
+        if let Some(Distortion::SIP(sip)) = &self.distortion {
+            let (u, v) = sip.inverse(offset[0], offset[1]);
+            offset[0] = u;
+            offset[1] = v;
+        }
+        (0..self.naxis).map(|j| offset[j] + self.crpix[j]).collect()
+    }
+
+    /// World coordinates of one pixel, or `None` if it falls outside the projection or has
+    /// fewer than `naxis` coordinates.
+    pub fn pix2world_point(&self, pixel: &[f64]) -> Option<Vec<f64>> {
+        if pixel.len() < self.naxis {
+            return None;
+        }
+        let intermediate = self.pix2intermediate(pixel);
+        let mut world: Vec<f64> = (0..self.naxis).map(|i| self.crval[i] + intermediate[i]).collect();
+        if let Some(celestial) = &self.celestial {
+            let (alpha, delta) = celestial.intermediate2world(intermediate[celestial.lng], intermediate[celestial.lat])?;
+            world[celestial.lng] = alpha;
+            world[celestial.lat] = delta;
+        }
+        if let Some(spectral) = &self.spectral {
+            world[spectral.axis] = spectral.intermediate2world(intermediate[spectral.axis])?;
+        }
+        Some(world)
+    }
+
+    /// Pixel of one set of world coordinates, or `None` if it cannot be projected or has
+    /// fewer than `naxis` coordinates.
+    pub fn world2pix_point(&self, world: &[f64]) -> Option<Vec<f64>> {
+        if world.len() < self.naxis {
+            return None;
+        }
+        let mut intermediate: Vec<f64> = (0..self.naxis).map(|i| world[i] - self.crval[i]).collect();
+        if let Some(celestial) = &self.celestial {
+            let (x, y) = celestial.world2intermediate(world[celestial.lng], world[celestial.lat])?;
+            intermediate[celestial.lng] = x;
+            intermediate[celestial.lat] = y;
+        }
+        if let Some(spectral) = &self.spectral {
+            intermediate[spectral.axis] = spectral.world2intermediate(world[spectral.axis])?;
+        }
+        Some(self.intermediate2pix(&intermediate))
+    }
+
+    /// World coordinates of one pixel with the celestial pair converted to another frame.
+    /// `None` if the pixel cannot be projected or the frame of the header is unknown.
+    pub fn pix2world_frame(&self, pixel: &[f64], frame: Frame) -> Option<Vec<f64>> {
+        let mut world = self.pix2world_point(pixel)?;
+        if let Some(celestial) = &self.celestial {
+            let (lng, lat) = celestial.frame?.convert(world[celestial.lng], world[celestial.lat], frame);
+            world[celestial.lng] = lng;
+            world[celestial.lat] = lat;
+        }
+        Some(world)
+    }
+
+    /// Pixel of one set of world coordinates whose celestial pair is given in another frame.
+    /// `None` if the point cannot be projected or the frame of the header is unknown.
+    pub fn world2pix_frame(&self, world: &[f64], frame: Frame) -> Option<Vec<f64>> {
+        let mut world = world.to_vec();
+        if let Some(celestial) = &self.celestial {
+            let (lng, lat) = frame.convert(world[celestial.lng], world[celestial.lat], celestial.frame?);
+            world[celestial.lng] = lng;
+            world[celestial.lat] = lat;
+        }
+        self.world2pix_point(&world)
+    }
+
+    /// World coordinates of many pixels, one pixel per row. Points outside the projection and
+    /// rows with fewer than `naxis` columns are NaN.
+    pub fn pix2world(&self, pixels: ArrayView2<f64>) -> Array2<f64> {
+        self.map_rows(pixels, |row| self.pix2world_point(row))
+    }
+
+    /// Pixels of many world coordinates, one point per row. Points that cannot be projected and
+    /// rows with fewer than `naxis` columns are NaN.
+    pub fn world2pix(&self, world: ArrayView2<f64>) -> Array2<f64> {
+        self.map_rows(world, |row| self.world2pix_point(row))
+    }
+
+    fn map_rows<F: Fn(&[f64]) -> Option<Vec<f64>>>(&self, input: ArrayView2<f64>, function: F) -> Array2<f64> {
+        let mut output = Array2::from_elem((input.nrows(), self.naxis), f64::NAN);
+        for (i, row) in input.rows().into_iter().enumerate() {
+            let row: Vec<f64> = row.to_vec();
+            if let Some(result) = function(&row) {
+                for (j, value) in result.into_iter().enumerate() {
+                    output[[i, j]] = value;
+                }
+            }
+        }
+        output
+    }
+}
+
+impl Celestial {
+    fn from_header(header: &Header, ctype: &[String], cunit: &[String], crval: &[f64], alt: &str) -> Result<Option<Celestial>, String> {
+        let lng = ctype.iter().position(|ctype| is_longitude(ctype));
+        let lat = ctype.iter().position(|ctype| is_latitude(ctype));
+        let (lng, lat) = match (lng, lat) {
+            (Some(lng), Some(lat)) => (lng, lat),
+            (None, None) => return Ok(None),
+            _ => return Err("Celestial axes must come in longitude/latitude pairs".to_string()),
+        };
+        let code = ctype[lng].get(5..8).unwrap_or("");
+        // TPV is a gnomonic projection whose PVi_m keywords hold distortion coefficients
+        let tpv = code == "TPV";
+        let projection = if tpv { Some(Projection::TAN) } else { Projection::from_code(code) }
+            .ok_or_else(|| format!("Unsupported projection {:?} in CTYPE{}", code, lng + 1))?;
+        let pv = |m: usize| if tpv { None } else { header.get_f64(&format!("PV{}_{}{}", lng + 1, m, alt)) };
+
+        let (mut phi_0, mut theta_0) = projection.native_reference();
+        if let Some(value) = pv(1) {
+            phi_0 = value;
+        }
+        if let Some(value) = pv(2) {
+            theta_0 = value;
+        }
+        let unit_scale = [unit_to_degrees(&cunit[lng])?, unit_to_degrees(&cunit[lat])?];
+        let (alpha_0, delta_0) = (crval[lng] * unit_scale[0], crval[lat] * unit_scale[1]);
+        let phi_p = header
+            .get_f64(&format!("LONPOLE{}", alt))
+            .or_else(|| pv(3))
+            .unwrap_or(if delta_0 >= theta_0 { 0.0 } else { 180.0 });
+        let latpole = header
+            .get_f64(&format!("LATPOLE{}", alt))
+            .or_else(|| pv(4))
+            .unwrap_or(90.0);
+        let (alpha_p, delta_p) = native_pole(alpha_0, delta_0, phi_0, theta_0, phi_p, latpole)?;
+        Ok(Some(Celestial {
+            lng,
+            lat,
+            projection,
+            phi_0,
+            theta_0,
+            alpha_p,
+            delta_p,
+            phi_p,
+            frame: Frame::from_header(header, &ctype[lng], alt),
+            unit_scale,
+        }))
+    }
+
+    /// Celestial coordinates from intermediate world coordinates in the axis units.
+    pub fn intermediate2world(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let (phi, theta) = self.projection.deproject(x * self.unit_scale[0], y * self.unit_scale[1])?;
+        Some(self.native2celestial(phi, theta))
+    }
+
+    /// Intermediate world coordinates in the axis units from celestial coordinates.
+    pub fn world2intermediate(&self, alpha: f64, delta: f64) -> Option<(f64, f64)> {
+        let (phi, theta) = self.celestial2native(alpha, delta);
+        let (x, y) = self.projection.project(phi, theta)?;
+        Some((x / self.unit_scale[0], y / self.unit_scale[1]))
+    }
+
+    /// Rotate native spherical coordinates to celestial coordinates (Paper II, eq. 2).
+    pub fn native2celestial(&self, phi: f64, theta: f64) -> (f64, f64) {
+        let (sin_theta, cos_theta) = theta.to_radians().sin_cos();
+        let (sin_dp, cos_dp) = self.delta_p.to_radians().sin_cos();
+        let (sin_dphi, cos_dphi) = (phi - self.phi_p).to_radians().sin_cos();
+        let (x, y) = (-cos_theta * sin_dphi, sin_theta * cos_dp - cos_theta * sin_dp * cos_dphi);
+        let alpha = self.alpha_p + x.atan2(y).to_degrees();
+        // atan2 rather than asin keeps full precision close to the poles
+        let delta = (sin_theta * sin_dp + cos_theta * cos_dp * cos_dphi).atan2(x.hypot(y));
+        (alpha.rem_euclid(360.0), delta.to_degrees())
+    }
+
+    /// Rotate celestial coordinates to native spherical coordinates (Paper II, eq. 5).
+    pub fn celestial2native(&self, alpha: f64, delta: f64) -> (f64, f64) {
+        let (sin_delta, cos_delta) = delta.to_radians().sin_cos();
+        let (sin_dp, cos_dp) = self.delta_p.to_radians().sin_cos();
+        let (sin_da, cos_da) = (alpha - self.alpha_p).to_radians().sin_cos();
+        let (x, y) = (-cos_delta * sin_da, sin_delta * cos_dp - cos_delta * sin_dp * cos_da);
+        let phi = self.phi_p + x.atan2(y).to_degrees();
+        let theta = (sin_delta * sin_dp + cos_delta * cos_dp * cos_da).atan2(x.hypot(y));
+        ((phi + 180.0).rem_euclid(360.0) - 180.0, theta.to_degrees())
+    }
+}
+
+/// Where the `-TAB` lookup table of an axis is stored (Paper III, section 6.1.2).
+fn table_reference(header: &Header, axis: usize, alt: &str) -> TableReference {
+    let i = axis + 1;
+    let text = |keyword: String| header.get_value(&(keyword + alt)).map(|value| value.to_string());
+    TableReference {
+        extname: text(format!("PS{}_0", i)).unwrap_or_default(),
+        extver: header.get_f64(&format!("PV{}_1{}", i, alt)).unwrap_or(1.0) as i64,
+        extlevel: header.get_f64(&format!("PV{}_2{}", i, alt)).unwrap_or(1.0) as i64,
+        coordinates: text(format!("PS{}_1", i)).unwrap_or_default(),
+        index: text(format!("PS{}_2", i)),
+    }
+}
+
+/// Keyword suffix of a description letter, empty for the primary one.
+fn suffix(alt: char) -> &'static str {
+    const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    match LETTERS.find(alt) {
+        Some(index) => &LETTERS[index..index + 1],
+        None => "",
+    }
+}
+
+/// Letter of an alternate description keyword such as CTYPE1A, CD1_2B or WCSNAMEC.
+fn alternate_letter(keyword: &str) -> Option<char> {
+    let letter = keyword.chars().last().filter(|letter| letter.is_ascii_uppercase())?;
+    let base = &keyword[..keyword.len() - 1];
+    let indexed = |prefix: &str| {
+        base.strip_prefix(prefix)
+            .is_some_and(|rest| !rest.is_empty() && rest.split('_').all(|index| !index.is_empty() && index.chars().all(|c| c.is_ascii_digit())))
+    };
+    let is_wcs = base == "WCSAXES"
+        || base == "WCSNAME"
+        || ["CTYPE", "CRPIX", "CRVAL", "CDELT", "CUNIT", "CD", "PC"].iter().any(|prefix| indexed(prefix));
+    if is_wcs {
+        Some(letter)
+    } else {
+        None
+    }
+}
+
+/// Read the index vector and the coordinate array of a one-dimensional `-TAB` axis.
+fn read_table(fits: &FITS, reference: &TableReference) -> Result<(Option<Vec<f64>>, Vec<f64>), String> {
+    let hdu = fits
+        .hdus
+        .iter()
+        .find(|hdu| {
+            hdu.header.get_extname() == Some(reference.extname.as_str())
+                && hdu.header.get_extver() == reference.extver
+                && hdu.header.get_f64("EXTLEVEL").unwrap_or(1.0) as i64 == reference.extlevel
+        })
+        .ok_or_else(|| format!("No -TAB table extension named {:?}", reference.extname))?;
+    let table = match &hdu.data {
+        Data::BinaryTable(table) => table,
+        _ => return Err(format!("Extension {:?} is not a binary table", reference.extname)),
+    };
+    let column = |name: &str| -> Result<Vec<f64>, String> {
+        table
+            .column_index(name)
+            .and_then(|column| table.column_f64(column))
+            .and_then(|rows| rows.into_iter().next())
+            .ok_or_else(|| format!("No numerical column {:?} in {:?}", name, reference.extname))
+    };
+    let coordinates = column(&reference.coordinates)?;
+    let index = match &reference.index {
+        Some(name) => Some(column(name)?),
+        None => None,
+    };
+    Ok((index, coordinates))
+}
+
+/// Celestial coordinates of the native pole (Paper II, eqs. 8 to 10).
+fn native_pole(alpha_0: f64, delta_0: f64, phi_0: f64, theta_0: f64, phi_p: f64, latpole: f64) -> Result<(f64, f64), String> {
+    if theta_0 == 90.0 {
+        return Ok((alpha_0, delta_0));
+    }
+    let (sin_t0, cos_t0) = theta_0.to_radians().sin_cos();
+    let (sin_d0, cos_d0) = delta_0.to_radians().sin_cos();
+    let dphi = (phi_p - phi_0).to_radians();
+    let base = sin_t0.atan2(cos_t0 * dphi.cos()).to_degrees();
+    let denominator = (1.0 - cos_t0 * cos_t0 * dphi.sin() * dphi.sin()).sqrt();
+    let ratio = if denominator == 0.0 { f64::INFINITY } else { sin_d0 / denominator };
+    if ratio.abs() > 1.0 + 1e-12 {
+        return Err("No valid celestial pole for the given CRVAL and LONPOLE".to_string());
+    }
+    let spread = ratio.clamp(-1.0, 1.0).acos().to_degrees();
+    let candidates = [base + spread, base - spread];
+    let delta_p = candidates
+        .iter()
+        .cloned()
+        .filter(|delta| delta.abs() <= 90.0 + 1e-10)
+        .min_by(|a, b| (a - latpole).abs().partial_cmp(&(b - latpole).abs()).unwrap())
+        .ok_or("No valid celestial pole for the given CRVAL and LONPOLE")?
+        .clamp(-90.0, 90.0);
+
+    let alpha_p = if cos_d0.abs() < 1e-10 {
+        // Celestial pole at the reference point, which fixes the longitude of the native pole
+        alpha_0
+    } else if (delta_p - 90.0).abs() < 1e-10 {
+        alpha_0 + phi_p - phi_0 - 180.0
+    } else if (delta_p + 90.0).abs() < 1e-10 {
+        alpha_0 - phi_p + phi_0
+    } else {
+        let (sin_dp, cos_dp) = delta_p.to_radians().sin_cos();
+        let x = (sin_t0 - sin_dp * sin_d0) / (cos_dp * cos_d0);
+        let y = dphi.sin() * cos_t0 / cos_d0;
+        alpha_0 - y.atan2(x).to_degrees()
+    };
+    Ok((alpha_p, delta_p))
+}
+
+/// CTYPE prefixes of celestial longitude/latitude pairs: equatorial, galactic, ecliptic,
+/// helioecliptic and supergalactic (Paper II), then helioprojective, Stonyhurst and
+/// Carrington heliographic coordinates (Thompson 2006).
+const CELESTIAL_PAIRS: [(&str, &str); 8] = [
+    ("RA--", "DEC-"),
+    ("GLON", "GLAT"),
+    ("ELON", "ELAT"),
+    ("HLON", "HLAT"),
+    ("SLON", "SLAT"),
+    ("HPLN", "HPLT"),
+    ("HGLN", "HGLT"),
+    ("CRLN", "CRLT"),
+];
+
+fn is_longitude(ctype: &str) -> bool {
+    let prefix = ctype.get(..4).unwrap_or("");
+    CELESTIAL_PAIRS.iter().any(|(lng, _)| *lng == prefix)
+}
+
+fn is_latitude(ctype: &str) -> bool {
+    let prefix = ctype.get(..4).unwrap_or("");
+    CELESTIAL_PAIRS.iter().any(|(_, lat)| *lat == prefix)
+}
+
+/// Factor converting an angular CUNIT to degrees. An empty unit means degrees.
+fn unit_to_degrees(unit: &str) -> Result<f64, String> {
+    match unit {
+        "" | "deg" => Ok(1.0),
+        "arcmin" => Ok(1.0 / 60.0),
+        "arcsec" => Ok(1.0 / 3600.0),
+        "mas" => Ok(1.0 / 3_600_000.0),
+        "rad" => Ok(180.0 / std::f64::consts::PI),
+        _ => Err(format!("Unsupported unit {:?} for a celestial axis", unit)),
+    }
+}
+
+fn multiply(matrix: &[Vec<f64>], vector: &[f64]) -> Vec<f64> {
+    matrix
+        .iter()
+        .map(|row| row.iter().zip(vector.iter()).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+/// Invert a square matrix by Gauss-Jordan elimination with partial pivoting.
+fn invert(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut left: Vec<Vec<f64>> = matrix.to_vec();
+    let mut right: Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| left[*a][column].abs().partial_cmp(&left[*b][column].abs()).unwrap())?;
+        if left[pivot][column] == 0.0 {
+            return None;
+        }
+        left.swap(column, pivot);
+        right.swap(column, pivot);
+        let scale = left[column][column];
+        for j in 0..n {
+            left[column][j] /= scale;
+            right[column][j] /= scale;
+        }
+        for row in 0..n {
+            if row != column {
+                let factor = left[row][column];
+                for j in 0..n {
+                    left[row][j] -= factor * left[column][j];
+                    right[row][j] -= factor * right[column][j];
+                }
+            }
+        }
+    }
+    Some(right)
+}
@@ -0,0 +1,250 @@
+use std::f64::consts::{FRAC_PI_2, PI, SQRT_2};
+
+/// Degrees per radian, the R0 of Calabretta & Greisen (2002).
+const R0: f64 = 180.0 / PI;
+
+/// Celestial projections from Calabretta & Greisen (2002), "Representations of celestial
+/// coordinates in FITS" (Paper II). Angles and projection plane coordinates are in degrees.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Projection {
+    /// Gnomonic
+    TAN,
+    /// Orthographic
+    SIN,
+    /// Zenithal equidistant
+    ARC,
+    /// Stereographic
+    STG,
+    /// Zenithal equal-area
+    ZEA,
+    /// Plate carrée
+    CAR,
+    /// Mercator
+    MER,
+    /// Hammer-Aitoff
+    AIT,
+    /// Mollweide
+    MOL,
+    /// HEALPix with the default H = 4, K = 3
+    HPX,
+}
+
+impl Projection {
+    pub fn from_code(code: &str) -> Option<Projection> {
+        match code {
+            "TAN" => Some(Projection::TAN),
+            "SIN" => Some(Projection::SIN),
+            "ARC" => Some(Projection::ARC),
+            "STG" => Some(Projection::STG),
+            "ZEA" => Some(Projection::ZEA),
+            "CAR" => Some(Projection::CAR),
+            "MER" => Some(Projection::MER),
+            "AIT" => Some(Projection::AIT),
+            "MOL" => Some(Projection::MOL),
+            "HPX" => Some(Projection::HPX),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Projection::TAN => "TAN",
+            Projection::SIN => "SIN",
+            Projection::ARC => "ARC",
+            Projection::STG => "STG",
+            Projection::ZEA => "ZEA",
+            Projection::CAR => "CAR",
+            Projection::MER => "MER",
+            Projection::AIT => "AIT",
+            Projection::MOL => "MOL",
+            Projection::HPX => "HPX",
+        }
+    }
+
+    fn is_zenithal(&self) -> bool {
+        matches!(
+            self,
+            Projection::TAN | Projection::SIN | Projection::ARC | Projection::STG | Projection::ZEA
+        )
+    }
+
+    /// Default native coordinates (phi_0, theta_0) of the fiducial point.
+    pub fn native_reference(&self) -> (f64, f64) {
+        if self.is_zenithal() {
+            (0.0, 90.0)
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    /// Native spherical (phi, theta) to projection plane (x, y).
+    /// Returns `None` where the projection is undefined, e.g. the far hemisphere of TAN.
+    pub fn project(&self, phi: f64, theta: f64) -> Option<(f64, f64)> {
+        if self.is_zenithal() {
+            let r = match self {
+                Projection::TAN => {
+                    if theta <= 0.0 {
+                        return None;
+                    }
+                    R0 / theta.to_radians().tan()
+                }
+                Projection::SIN => {
+                    if theta < 0.0 {
+                        return None;
+                    }
+                    R0 * theta.to_radians().cos()
+                }
+                Projection::ARC => 90.0 - theta,
+                Projection::STG => {
+                    if theta <= -90.0 {
+                        return None;
+                    }
+                    2.0 * R0 * ((90.0 - theta) / 2.0).to_radians().tan()
+                }
+                _ => 2.0 * R0 * ((90.0 - theta) / 2.0).to_radians().sin(),
+            };
+            let phi = phi.to_radians();
+            return Some((r * phi.sin(), -r * phi.cos()));
+        }
+        match self {
+            Projection::CAR => Some((phi, theta)),
+            Projection::MER => {
+                if theta.abs() >= 90.0 {
+                    return None;
+                }
+                Some((phi, R0 * ((90.0 + theta) / 2.0).to_radians().tan().ln()))
+            }
+            Projection::AIT => {
+                let (phi, theta) = (phi.to_radians(), theta.to_radians());
+                let gamma = R0 * (2.0 / (1.0 + theta.cos() * (phi / 2.0).cos())).sqrt();
+                Some((2.0 * gamma * theta.cos() * (phi / 2.0).sin(), gamma * theta.sin()))
+            }
+            Projection::MOL => {
+                let gamma = mollweide_gamma(theta.to_radians());
+                Some((
+                    2.0 * SQRT_2 / PI * phi * gamma.cos(),
+                    SQRT_2 * R0 * gamma.sin(),
+                ))
+            }
+            _ => {
+                let sin_theta = theta.to_radians().sin();
+                if sin_theta.abs() <= 2.0 / 3.0 {
+                    return Some((phi, 67.5 * sin_theta));
+                }
+                let sigma = (3.0 * (1.0 - sin_theta.abs())).sqrt();
+                let phi_c = healpix_facet_centre(phi);
+                let y = 90.0 - 45.0 * sigma;
+                Some((phi_c + (phi - phi_c) * sigma, y.copysign(theta)))
+            }
+        }
+    }
+
+    /// Projection plane (x, y) to native spherical (phi, theta).
+    /// Returns `None` for points outside the boundary of the projection.
+    pub fn deproject(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        if self.is_zenithal() {
+            let r = x.hypot(y);
+            let phi = if r == 0.0 { 0.0 } else { x.atan2(-y).to_degrees() };
+            let theta = match self {
+                Projection::TAN => (R0 / r).atan().to_degrees(),
+                Projection::SIN => {
+                    if r > R0 {
+                        return None;
+                    }
+                    (r / R0).acos().to_degrees()
+                }
+                Projection::ARC => {
+                    if r > 180.0 {
+                        return None;
+                    }
+                    90.0 - r
+                }
+                Projection::STG => 90.0 - 2.0 * (r / (2.0 * R0)).atan().to_degrees(),
+                _ => {
+                    if r > 2.0 * R0 {
+                        return None;
+                    }
+                    90.0 - 2.0 * (r / (2.0 * R0)).asin().to_degrees()
+                }
+            };
+            return Some((phi, theta));
+        }
+        match self {
+            Projection::CAR => {
+                if y.abs() > 90.0 {
+                    return None;
+                }
+                Some((x, y))
+            }
+            Projection::MER => Some((x, 2.0 * (y / R0).exp().atan().to_degrees() - 90.0)),
+            Projection::AIT => {
+                let u = x / (4.0 * R0);
+                let v = y / (2.0 * R0);
+                let z2 = 1.0 - u * u - v * v;
+                if z2 < 0.5 {
+                    return None;
+                }
+                let z = z2.sqrt();
+                let phi = 2.0 * (z * x / (2.0 * R0)).atan2(2.0 * z2 - 1.0);
+                let theta = (y * z / R0).clamp(-1.0, 1.0).asin();
+                Some((phi.to_degrees(), theta.to_degrees()))
+            }
+            Projection::MOL => {
+                let sin_gamma = y / (SQRT_2 * R0);
+                if sin_gamma.abs() > 1.0 {
+                    return None;
+                }
+                let gamma = sin_gamma.asin();
+                let phi = if gamma.cos() == 0.0 {
+                    0.0
+                } else {
+                    PI * x / (2.0 * SQRT_2 * gamma.cos())
+                };
+                if phi.abs() > 180.0 + 1e-9 {
+                    return None;
+                }
+                let theta = ((2.0 * gamma + (2.0 * gamma).sin()) / PI).clamp(-1.0, 1.0).asin();
+                Some((phi, theta.to_degrees()))
+            }
+            _ => {
+                if y.abs() <= 45.0 {
+                    return Some((x, (y / 67.5).asin().to_degrees()));
+                }
+                if y.abs() > 90.0 {
+                    return None;
+                }
+                let sigma = 2.0 - y.abs() / 45.0;
+                let phi_c = healpix_facet_centre(x);
+                if (x - phi_c).abs() > 45.0 * sigma + 1e-9 {
+                    return None;
+                }
+                let phi = if sigma == 0.0 { phi_c } else { phi_c + (x - phi_c) / sigma };
+                let theta = (1.0 - sigma * sigma / 3.0).asin().to_degrees();
+                Some((phi, theta.copysign(y)))
+            }
+        }
+    }
+}
+
+/// Solve 2 gamma + sin(2 gamma) = pi sin(theta) by Newton iteration.
+fn mollweide_gamma(theta: f64) -> f64 {
+    if (theta.abs() - FRAC_PI_2).abs() < 1e-12 {
+        return theta;
+    }
+    let target = PI * theta.sin();
+    let mut gamma = theta;
+    for _ in 0..100 {
+        let delta = (2.0 * gamma + (2.0 * gamma).sin() - target) / (2.0 + 2.0 * (2.0 * gamma).cos());
+        gamma -= delta;
+        if delta.abs() < 1e-14 {
+            break;
+        }
+    }
+    gamma
+}
+
+/// Longitude of the centre of the polar HEALPix facet containing `phi`.
+fn healpix_facet_centre(phi: f64) -> f64 {
+    let facet = ((phi + 180.0) / 90.0).floor().clamp(0.0, 3.0);
+    -180.0 + (2.0 * facet + 1.0) * 45.0
+}
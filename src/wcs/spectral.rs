@@ -0,0 +1,382 @@
+use crate::header::Header;
+
+/// Speed of light in m/s
+pub const C: f64 = 299_792_458.0;
+/// Planck constant in J s
+pub const H: f64 = 6.626_070_15e-34;
+
+/// Spectral coordinate types from Greisen et al. (2006), "Representations of spectral
+/// coordinates in FITS" (Paper III). Values are in SI units unless stated otherwise.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpectralType {
+    /// Frequency (Hz)
+    FREQ,
+    /// Energy (J)
+    ENER,
+    /// Wavenumber (1/m)
+    WAVN,
+    /// Radio velocity (m/s)
+    VRAD,
+    /// Vacuum wavelength (m)
+    WAVE,
+    /// Optical velocity (m/s)
+    VOPT,
+    /// Redshift
+    ZOPT,
+    /// Air wavelength (m)
+    AWAV,
+    /// Apparent radial velocity (m/s)
+    VELO,
+    /// Apparent radial velocity divided by c
+    BETA,
+}
+
+/// Basic spectral variables that the other types are derived from.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Basic {
+    Frequency,
+    Wavelength,
+    AirWavelength,
+    Velocity,
+}
+
+/// Rest frequency and wavelength from RESTFRQ and RESTWAV, each derived from the other if missing.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rest {
+    pub frequency: Option<f64>,
+    pub wavelength: Option<f64>,
+}
+
+impl Rest {
+    pub fn from_header(header: &Header, alt: &str) -> Rest {
+        let frequency = header
+            .get_f64(&format!("RESTFRQ{}", alt))
+            .or_else(|| header.get_f64("RESTFREQ"));
+        let wavelength = header.get_f64(&format!("RESTWAV{}", alt));
+        Rest {
+            frequency: frequency.or(wavelength.map(|wavelength| C / wavelength)),
+            wavelength: wavelength.or(frequency.map(|frequency| C / frequency)),
+        }
+    }
+
+    fn frequency(&self) -> Result<f64, String> {
+        self.frequency.ok_or_else(|| "RESTFRQ or RESTWAV is needed for this conversion".to_string())
+    }
+
+    fn wavelength(&self) -> Result<f64, String> {
+        self.wavelength.ok_or_else(|| "RESTFRQ or RESTWAV is needed for this conversion".to_string())
+    }
+}
+
+impl SpectralType {
+    pub fn from_code(code: &str) -> Option<SpectralType> {
+        match code {
+            "FREQ" => Some(SpectralType::FREQ),
+            "ENER" => Some(SpectralType::ENER),
+            "WAVN" => Some(SpectralType::WAVN),
+            "VRAD" => Some(SpectralType::VRAD),
+            "WAVE" => Some(SpectralType::WAVE),
+            "VOPT" => Some(SpectralType::VOPT),
+            "ZOPT" => Some(SpectralType::ZOPT),
+            "AWAV" => Some(SpectralType::AWAV),
+            "VELO" => Some(SpectralType::VELO),
+            "BETA" => Some(SpectralType::BETA),
+            _ => None,
+        }
+    }
+
+    pub fn basic(&self) -> Basic {
+        match self {
+            SpectralType::FREQ | SpectralType::ENER | SpectralType::WAVN | SpectralType::VRAD => Basic::Frequency,
+            SpectralType::WAVE | SpectralType::VOPT | SpectralType::ZOPT => Basic::Wavelength,
+            SpectralType::AWAV => Basic::AirWavelength,
+            SpectralType::VELO | SpectralType::BETA => Basic::Velocity,
+        }
+    }
+
+    /// SI value of this type to the value of its basic variable.
+    pub fn to_basic(&self, value: f64, rest: &Rest) -> Result<f64, String> {
+        Ok(match self {
+            SpectralType::FREQ | SpectralType::WAVE | SpectralType::AWAV | SpectralType::VELO => value,
+            SpectralType::ENER => value / H,
+            SpectralType::WAVN => value * C,
+            SpectralType::VRAD => rest.frequency()? * (1.0 - value / C),
+            SpectralType::VOPT => rest.wavelength()? * (1.0 + value / C),
+            SpectralType::ZOPT => rest.wavelength()? * (1.0 + value),
+            SpectralType::BETA => value * C,
+        })
+    }
+
+    /// Value of the basic variable to the SI value of this type.
+    pub fn from_basic(&self, value: f64, rest: &Rest) -> Result<f64, String> {
+        Ok(match self {
+            SpectralType::FREQ | SpectralType::WAVE | SpectralType::AWAV | SpectralType::VELO => value,
+            SpectralType::ENER => value * H,
+            SpectralType::WAVN => value / C,
+            SpectralType::VRAD => C * (1.0 - value / rest.frequency()?),
+            SpectralType::VOPT => C * (value / rest.wavelength()? - 1.0),
+            SpectralType::ZOPT => value / rest.wavelength()? - 1.0,
+            SpectralType::BETA => value / C,
+        })
+    }
+
+    /// Convert an SI value of this type to an SI value of another type.
+    pub fn convert(&self, value: f64, target: SpectralType, rest: &Rest) -> Result<f64, String> {
+        let basic = self.to_basic(value, rest)?;
+        let basic = convert_basic(basic, self.basic(), target.basic(), rest)?;
+        target.from_basic(basic, rest)
+    }
+}
+
+impl Basic {
+    fn from_char(code: char) -> Option<Basic> {
+        match code {
+            'F' => Some(Basic::Frequency),
+            'W' => Some(Basic::Wavelength),
+            'A' => Some(Basic::AirWavelength),
+            'V' => Some(Basic::Velocity),
+            _ => None,
+        }
+    }
+}
+
+/// Convert between basic spectral variables, going through frequency.
+pub fn convert_basic(value: f64, from: Basic, to: Basic, rest: &Rest) -> Result<f64, String> {
+    if from == to {
+        return Ok(value);
+    }
+    let frequency = match from {
+        Basic::Frequency => value,
+        Basic::Wavelength => C / value,
+        Basic::AirWavelength => C / air_to_vacuum(value),
+        Basic::Velocity => rest.frequency()? * ((C - value) / (C + value)).sqrt(),
+    };
+    Ok(match to {
+        Basic::Frequency => frequency,
+        Basic::Wavelength => C / frequency,
+        Basic::AirWavelength => vacuum_to_air(C / frequency),
+        Basic::Velocity => {
+            let rest = rest.frequency()?;
+            C * (rest * rest - frequency * frequency) / (rest * rest + frequency * frequency)
+        }
+    })
+}
+
+/// Refractive index of air at a vacuum wavelength in m (Paper III, eq. 65).
+fn refractive_index(wavelength: f64) -> f64 {
+    let inverse_square = 1.0 / (wavelength * 1e6).powi(2);
+    1.0 + 1e-6 * (287.6155 + 1.62887 * inverse_square + 0.01360 * inverse_square * inverse_square)
+}
+
+fn vacuum_to_air(wavelength: f64) -> f64 {
+    wavelength / refractive_index(wavelength)
+}
+
+fn air_to_vacuum(wavelength: f64) -> f64 {
+    let mut vacuum = wavelength;
+    for _ in 0..10 {
+        vacuum = wavelength * refractive_index(vacuum);
+    }
+    vacuum
+}
+
+/// Factor from a spectral unit to SI. An empty unit is taken to be SI already.
+pub fn unit_to_si(unit: &str) -> Result<f64, String> {
+    match unit {
+        "" | "Hz" | "m" | "m/s" | "J" | "/m" | "m-1" | "m^-1" => Ok(1.0),
+        "kHz" => Ok(1e3),
+        "MHz" => Ok(1e6),
+        "GHz" => Ok(1e9),
+        "THz" => Ok(1e12),
+        "km" => Ok(1e3),
+        "cm" => Ok(1e-2),
+        "mm" => Ok(1e-3),
+        "um" => Ok(1e-6),
+        "nm" => Ok(1e-9),
+        "Angstrom" | "angstrom" => Ok(1e-10),
+        "km/s" | "km s-1" => Ok(1e3),
+        "eV" => Ok(1.602_176_634e-19),
+        "keV" => Ok(1.602_176_634e-16),
+        "MeV" => Ok(1.602_176_634e-13),
+        "/cm" | "cm-1" | "cm^-1" => Ok(1e2),
+        _ => Err(format!("Unsupported spectral unit {:?}", unit)),
+    }
+}
+
+/// How intermediate world coordinates map to spectral coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Algorithm {
+    Linear,
+    /// Logarithmic, `-LOG`
+    Log,
+    /// Linear in another basic variable, `-X2P` codes such as `-F2W` or `-V2F`
+    NonLinear(Basic),
+    /// Lookup table, `-TAB`, with an optional index vector and the coordinate array
+    Table { index: Option<Vec<f64>>, coordinates: Vec<f64> },
+}
+
+/// Location of the `-TAB` lookup table, from PSi_0, PVi_1, PVi_2, PSi_1 and PSi_2.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableReference {
+    pub extname: String,
+    pub extver: i64,
+    pub extlevel: i64,
+    pub coordinates: String,
+    pub index: Option<String>,
+}
+
+/// Spectral axis of a WCS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpectralAxis {
+    /// Index of the axis in the WCS
+    pub axis: usize,
+    pub spectral_type: SpectralType,
+    pub algorithm: Algorithm,
+    pub rest: Rest,
+    /// CRVAL in the axis units
+    pub crval: f64,
+    /// CUNIT of the axis
+    pub unit: String,
+    unit_scale: f64,
+    /// Basic variable X at the reference point and its derivative dX/dS there, for `-X2P` axes
+    reference: (f64, f64),
+}
+
+impl SpectralAxis {
+    /// Build a spectral axis from its CTYPE. `-TAB` axes get an empty table that must be
+    /// filled in with [`SpectralAxis::set_table`].
+    pub fn new(axis: usize, ctype: &str, crval: f64, unit: &str, rest: Rest) -> Result<Option<SpectralAxis>, String> {
+        let spectral_type = match ctype.get(..4).and_then(SpectralType::from_code) {
+            Some(spectral_type) => spectral_type,
+            None => return Ok(None),
+        };
+        let code = ctype.get(5..8).unwrap_or("");
+        let algorithm = match code {
+            "" => Algorithm::Linear,
+            "LOG" => Algorithm::Log,
+            "TAB" => Algorithm::Table {
+                index: None,
+                coordinates: Vec::new(),
+            },
+            _ => {
+                let characters: Vec<char> = code.chars().collect();
+                match characters[..] {
+                    [x, '2', p] => match (Basic::from_char(x), Basic::from_char(p)) {
+                        (Some(x), Some(p)) if p == spectral_type.basic() => Algorithm::NonLinear(x),
+                        _ => return Err(format!("Unsupported spectral algorithm {:?}", ctype)),
+                    },
+                    // AIPS-convention suffixes such as VELO-LSR or FELO-HEL name a velocity
+                    // frame rather than an algorithm, so the axis is linear
+                    _ => Algorithm::Linear,
+                }
+            }
+        };
+        let unit_scale = unit_to_si(unit)?;
+        let mut spectral = SpectralAxis {
+            axis,
+            spectral_type,
+            algorithm,
+            rest,
+            crval,
+            unit: unit.to_string(),
+            unit_scale,
+            reference: (0.0, 1.0),
+        };
+        if let Algorithm::NonLinear(x) = spectral.algorithm {
+            let s_r = crval * unit_scale;
+            let to_x = |s: f64| -> Result<f64, String> {
+                let p = spectral_type.to_basic(s, &rest)?;
+                convert_basic(p, spectral_type.basic(), x, &rest)
+            };
+            let step = s_r.abs().max(f64::MIN_POSITIVE) * 1e-6;
+            let derivative = (to_x(s_r + step)? - to_x(s_r - step)?) / (2.0 * step);
+            spectral.reference = (to_x(s_r)?, derivative);
+        }
+        Ok(Some(spectral))
+    }
+
+    pub fn set_table(&mut self, index: Option<Vec<f64>>, coordinates: Vec<f64>) {
+        self.algorithm = Algorithm::Table { index, coordinates };
+    }
+
+    /// Spectral coordinate in the axis units from the intermediate world coordinate.
+    pub fn intermediate2world(&self, w: f64) -> Option<f64> {
+        match &self.algorithm {
+            Algorithm::Linear => Some(self.crval + w),
+            Algorithm::Log => Some(self.crval * (w / self.crval).exp()),
+            Algorithm::NonLinear(x) => {
+                let value = self.reference.0 + w * self.unit_scale * self.reference.1;
+                let p = convert_basic(value, *x, self.spectral_type.basic(), &self.rest).ok()?;
+                let s = self.spectral_type.from_basic(p, &self.rest).ok()?;
+                Some(s / self.unit_scale)
+            }
+            Algorithm::Table { index, coordinates } => {
+                let psi = w + self.crval;
+                let upsilon = match index {
+                    Some(index) => inverse_interpolate(index, psi)?,
+                    None => psi,
+                };
+                interpolate(coordinates, upsilon)
+            }
+        }
+    }
+
+    /// Intermediate world coordinate from the spectral coordinate in the axis units.
+    pub fn world2intermediate(&self, s: f64) -> Option<f64> {
+        match &self.algorithm {
+            Algorithm::Linear => Some(s - self.crval),
+            Algorithm::Log => Some(self.crval * (s / self.crval).ln()),
+            Algorithm::NonLinear(x) => {
+                let p = self.spectral_type.to_basic(s * self.unit_scale, &self.rest).ok()?;
+                let value = convert_basic(p, self.spectral_type.basic(), *x, &self.rest).ok()?;
+                Some((value - self.reference.0) / self.reference.1 / self.unit_scale)
+            }
+            Algorithm::Table { index, coordinates } => {
+                let upsilon = inverse_interpolate(coordinates, s)?;
+                let psi = match index {
+                    Some(index) => interpolate(index, upsilon)?,
+                    None => upsilon,
+                };
+                Some(psi - self.crval)
+            }
+        }
+    }
+
+    /// Convert a coordinate of this axis, in its units, to another spectral type in `unit`.
+    /// Used to label a cube slice with e.g. wavelength or velocity.
+    pub fn convert(&self, s: f64, target: SpectralType, unit: &str) -> Result<f64, String> {
+        let value = self.spectral_type.convert(s * self.unit_scale, target, &self.rest)?;
+        Ok(value / unit_to_si(unit)?)
+    }
+}
+
+/// Linear interpolation of `values` at a 1-based fractional index.
+fn interpolate(values: &[f64], upsilon: f64) -> Option<f64> {
+    if values.is_empty() || upsilon < 0.5 || upsilon > values.len() as f64 + 0.5 {
+        return None;
+    }
+    if values.len() == 1 {
+        return Some(values[0]);
+    }
+    let position = (upsilon - 1.0).clamp(0.0, (values.len() - 1) as f64);
+    let lower = (position.floor() as usize).min(values.len() - 2);
+    let fraction = upsilon - 1.0 - lower as f64;
+    Some(values[lower] + fraction * (values[lower + 1] - values[lower]))
+}
+
+/// 1-based fractional index at which monotonic `values` reach `target`.
+fn inverse_interpolate(values: &[f64], target: f64) -> Option<f64> {
+    if values.len() < 2 {
+        return values.first().filter(|value| **value == target).map(|_| 1.0);
+    }
+    let increasing = values[values.len() - 1] >= values[0];
+    for k in 0..values.len() - 1 {
+        let (a, b) = (values[k], values[k + 1]);
+        let inside = if increasing { a <= target && target <= b } else { b <= target && target <= a };
+        if inside {
+            let fraction = if b == a { 0.0 } else { (target - a) / (b - a) };
+            return Some(k as f64 + 1.0 + fraction);
+        }
+    }
+    None
+}
@@ -0,0 +1,69 @@
+use crate::header::Header;
+
+/// Translate the pixel-list WCS keywords of table columns (TCTYPn, TCRPXn, TCRVLn, TCDLTn,
+/// TPn_ka, ...; Paper I table 2) into the image keywords of the equivalent image axes.
+///
+/// `columns` are 1-based column numbers in axis order, so the first column becomes axis 1.
+/// Keywords that only exist once per description (LONPOLEa, RADESYSa, ...) are taken from the
+/// first column defining them, falling back to the table-wide image keyword.
+pub fn image_keywords(header: &Header, columns: &[usize], alt: &str) -> Vec<(String, String)> {
+    let mut keywords: Vec<(String, String)> = Vec::new();
+    let mut copy = |from: String, to: String| {
+        if let Some(value) = header.get_value(&from) {
+            keywords.push((to, value.to_string()));
+        }
+    };
+    let primary = alt.is_empty();
+    for (i, n) in columns.iter().enumerate() {
+        let i = i + 1;
+        let names: [(&str, &str, &str); 5] = [
+            ("TCTYP", "TCTY", "CTYPE"),
+            ("TCUNI", "TCUN", "CUNIT"),
+            ("TCRPX", "TCRP", "CRPIX"),
+            ("TCRVL", "TCRV", "CRVAL"),
+            ("TCDLT", "TCDE", "CDELT"),
+        ];
+        for (long, short, image) in names.iter() {
+            let table = if primary { long } else { short };
+            copy(format!("{}{}{}", table, n, alt), format!("{}{}{}", image, i, alt));
+        }
+        if primary {
+            copy(format!("TCROT{}", n), format!("CROTA{}", i));
+        }
+        for (j, k) in columns.iter().enumerate() {
+            copy(format!("TP{}_{}{}", n, k, alt), format!("PC{}_{}{}", i, j + 1, alt));
+            copy(format!("TC{}_{}{}", n, k, alt), format!("CD{}_{}{}", i, j + 1, alt));
+        }
+        for m in 0..40 {
+            copy(format!("TV{}_{}{}", n, m, alt), format!("PV{}_{}{}", i, m, alt));
+        }
+        for m in 0..3 {
+            copy(format!("TS{}_{}{}", n, m, alt), format!("PS{}_{}{}", i, m, alt));
+        }
+    }
+    let shared: [(&str, &str); 7] = [
+        ("WCSN", "WCSNAME"),
+        ("LONP", "LONPOLE"),
+        ("LATP", "LATPOLE"),
+        ("RADE", "RADESYS"),
+        ("EQUI", "EQUINOX"),
+        ("RFRQ", "RESTFRQ"),
+        ("RWAV", "RESTWAV"),
+    ];
+    for (table, image) in shared.iter() {
+        let image = format!("{}{}", image, alt);
+        let value = columns
+            .iter()
+            .find_map(|n| header.get_value(&format!("{}{}{}", table, n, alt)))
+            .or_else(|| header.get_value(&image));
+        if let Some(value) = value {
+            keywords.push((image, value.to_string()));
+        }
+    }
+    for keyword in ["RADECSYS", "EPOCH", "RESTFREQ"].iter() {
+        if let Some(value) = header.get_value(keyword) {
+            keywords.push((keyword.to_string(), value.to_string()));
+        }
+    }
+    keywords
+}
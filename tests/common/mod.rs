@@ -0,0 +1,75 @@
+#![allow(dead_code)]
+
+/// Pad a list of 80-character cards, followed by END, into whole fitsblocks.
+pub fn header_bytes(cards: &[&str]) -> Vec<u8> {
+    header_from_cards(cards).get_fitsblocks().as_flattened().to_vec()
+}
+
+/// Pad data bytes with zeros to a whole number of fitsblocks.
+pub fn data_bytes(data: &[u8]) -> Vec<u8> {
+    pad(data.to_vec(), 0)
+}
+
+fn pad(mut bytes: Vec<u8>, fill: u8) -> Vec<u8> {
+    let n_blocks = bytes.len().div_ceil(2880);
+    bytes.resize(n_blocks * 2880, fill);
+    bytes
+}
+
+/// Format a keyword/value card the way fixed-format FITS writers do.
+pub fn card(keyword: &str, value: &str) -> String {
+    format!("{:<8}= {:>20}", keyword, value)
+}
+
+/// Build an initialized header from cards.
+pub fn header_from_cards(cards: &[&str]) -> rustfits::header::Header {
+    let cards: Vec<String> = cards.iter().map(|card| card.to_string()).collect();
+    rustfits::header::Header::from_cards(&cards)
+}
+
+/// Primary header followed by a binary table of `E` (f32) columns, one row per entry of `rows`.
+pub fn event_list_bytes(names: &[&str], extra: &[String], rows: &[Vec<f32>]) -> Vec<u8> {
+    let mut buffer = header_bytes(&[&card("SIMPLE", "T"), &card("BITPIX", "8"), &card("NAXIS", "0")]);
+    let mut cards = vec![
+        "XTENSION= 'BINTABLE'".to_string(),
+        card("BITPIX", "8"),
+        card("NAXIS", "2"),
+        card("NAXIS1", &(4 * names.len()).to_string()),
+        card("NAXIS2", &rows.len().to_string()),
+        card("PCOUNT", "0"),
+        card("GCOUNT", "1"),
+        card("TFIELDS", &names.len().to_string()),
+    ];
+    for (i, name) in names.iter().enumerate() {
+        cards.push(card(&format!("TTYPE{}", i + 1), &format!("'{}'", name)));
+        cards.push(card(&format!("TFORM{}", i + 1), "'E'"));
+    }
+    cards.extend(extra.iter().cloned());
+    buffer.extend(header_bytes(&cards.iter().map(|card| card.as_str()).collect::<Vec<&str>>()));
+    let data: Vec<u8> = rows.iter().flatten().flat_map(|value| value.to_be_bytes()).collect();
+    buffer.extend(data_bytes(&data));
+    buffer
+}
+
+/// Primary image of `naxisn` pixels with `extra` cards after the mandatory ones. `data` holds
+/// the pixels already in FITS (big-endian) byte order.
+pub fn image_bytes(bitpix: i32, naxisn: &[usize], extra: &[String], data: &[u8]) -> Vec<u8> {
+    let mut cards = vec![
+        card("SIMPLE", "T"),
+        card("BITPIX", &bitpix.to_string()),
+        card("NAXIS", &naxisn.len().to_string()),
+    ];
+    for (i, n) in naxisn.iter().enumerate() {
+        cards.push(card(&format!("NAXIS{}", i + 1), &n.to_string()));
+    }
+    cards.extend(extra.iter().cloned());
+    let mut buffer = header_bytes(&cards.iter().map(|card| card.as_str()).collect::<Vec<&str>>());
+    buffer.extend(data_bytes(data));
+    buffer
+}
+
+/// BITPIX = -32 image of `naxisn` pixels, see [`image_bytes`].
+pub fn float_image(values: &[f32], naxisn: &[usize], extra: &[String]) -> rustfits::fits::FITS {
+    let data: Vec<u8> = values.iter().flat_map(|value| value.to_be_bytes()).collect();
+    rustfits::fits::FITS::new_from_buffer(&image_bytes(-32, naxisn, extra, &data))
+}
@@ -0,0 +1,101 @@
+"""Reference pixel/world pairs for the SIP and TPV tests in tests/test_wcs.rs.
+
+By default the headers are evaluated in plain Python: the SIP (Shupe et al. 2005) and TPV
+polynomials followed by the classical gnomonic standard-coordinate formulae. Only the
+projection step differs from the Rust code, so a mistake in reading the polynomial
+coefficients would be shared. `--astropy` evaluates the same headers with astropy.wcs
+(`all_pix2world`, backed by wcslib) instead, and is what the test pairs should come from.
+The pairs are printed as Rust array entries, ready to paste into the tests.
+
+    python3 tests/data/wcs_reference.py [--astropy]
+"""
+
+import math
+import sys
+
+# Two-chip ACS/WFC style header: 0.05"/pixel, rotated, fourth order SIP
+SIP_HEADER = {
+    "CTYPE1": "RA---TAN-SIP", "CTYPE2": "DEC--TAN-SIP",
+    "CRPIX1": 2048.0, "CRPIX2": 1024.0,
+    "CRVAL1": 150.1163213, "CRVAL2": 2.200973097,
+    "CD1_1": 8.1303e-06, "CD1_2": 1.1418e-05, "CD2_1": 1.1480e-05, "CD2_2": -8.3444e-06,
+    "A_ORDER": 4, "B_ORDER": 4,
+    "A_0_2": 2.16615e-06, "A_1_1": -5.28229e-06, "A_2_0": 8.55893e-06,
+    "A_0_3": -5.86727e-11, "A_1_2": -4.69853e-10, "A_2_1": -2.81216e-11, "A_3_0": -4.69377e-10,
+    "A_0_4": 2.64016e-14, "A_1_3": -1.63838e-14, "A_2_2": 3.36418e-14, "A_3_1": 7.91436e-15, "A_4_0": 2.58745e-14,
+    "B_0_2": -7.23588e-06, "B_1_1": 6.18459e-06, "B_2_0": -1.74222e-06,
+    "B_0_3": 9.50461e-12, "B_1_2": -5.05553e-11, "B_2_1": -4.36003e-10, "B_3_0": 9.94049e-11,
+    "B_0_4": -2.73101e-14, "B_1_3": 2.15939e-14, "B_2_2": -5.90447e-15, "B_3_1": 3.10366e-14, "B_4_0": 1.34227e-15,
+}
+SIP_PIXELS = [(1.0, 1.0), (4096.0, 2048.0), (100.0, 1900.0), (3000.5, 500.25)]
+
+# DECam CCD style header: 0.263"/pixel, reference point off the chip, third order TPV
+TPV_HEADER = {
+    "CTYPE1": "RA---TPV", "CTYPE2": "DEC--TPV",
+    "CRPIX1": -9120.8, "CRPIX2": 4177.667,
+    "CRVAL1": 36.3765, "CRVAL2": -4.4535,
+    "CD1_1": -1.4939e-07, "CD1_2": 7.2861e-05, "CD2_1": -7.2854e-05, "CD2_2": -1.2838e-07,
+    "PV1_0": 0.00330386, "PV1_1": 1.010275, "PV1_2": -0.01023213, "PV1_4": -0.01061528,
+    "PV1_5": 0.009189862, "PV1_6": -0.004131189, "PV1_7": -0.007853096, "PV1_8": 0.005980253,
+    "PV1_9": -0.005035698, "PV1_10": 0.002418946,
+    "PV2_0": -0.00634268, "PV2_1": 1.016405, "PV2_2": -0.01460743, "PV2_4": -0.02119478,
+    "PV2_5": 0.01450355, "PV2_6": -0.003955219, "PV2_7": 0.01229432, "PV2_8": -0.005519004,
+    "PV2_9": 0.007052133, "PV2_10": -0.002092016,
+}
+TPV_PIXELS = [(1.0, 1.0), (2048.0, 4096.0), (1024.5, 2048.5), (500.0, 3500.0)]
+
+
+def sip(header, prefix, u, v):
+    order = header.get(prefix + "_ORDER", -1)
+    return sum(
+        header.get("%s_%d_%d" % (prefix, p, q), 0.0) * u ** p * v ** q
+        for p in range(order + 1)
+        for q in range(order + 1 - p)
+    )
+
+
+def tpv_terms(x, y):
+    r = math.hypot(x, y)
+    terms = [1.0]
+    for degree in range(1, 8):
+        terms.extend(x ** (degree - i) * y ** i for i in range(degree + 1))
+        if degree % 2 == 1:
+            terms.append(r ** degree)
+    return terms
+
+
+def tpv(header, axis, x, y):
+    return sum(header.get("PV%d_%d" % (axis, k), 0.0) * term for k, term in enumerate(tpv_terms(x, y)))
+
+
+def pix2world(header, pixel):
+    u, v = pixel[0] - header["CRPIX1"], pixel[1] - header["CRPIX2"]
+    if header["CTYPE1"].endswith("-SIP"):
+        u, v = u + sip(header, "A", u, v), v + sip(header, "B", u, v)
+    x = header["CD1_1"] * u + header["CD1_2"] * v
+    y = header["CD2_1"] * u + header["CD2_2"] * v
+    if header["CTYPE1"].endswith("TPV"):
+        x, y = tpv(header, 1, x, y), tpv(header, 2, y, x)
+    # Standard coordinates (xi, eta) about the tangent point (CRVAL1, CRVAL2)
+    xi, eta = math.radians(x), math.radians(y)
+    ra0, dec0 = math.radians(header["CRVAL1"]), math.radians(header["CRVAL2"])
+    denominator = math.cos(dec0) - eta * math.sin(dec0)
+    ra = ra0 + math.atan2(xi, denominator)
+    dec = math.atan2(math.sin(dec0) + eta * math.cos(dec0), math.hypot(xi, denominator))
+    return math.degrees(ra) % 360.0, math.degrees(dec)
+
+
+def astropy_pix2world(header, pixel):
+    from astropy.io import fits
+    from astropy.wcs import WCS
+
+    wcs = WCS(fits.Header(header))
+    return tuple(float(value) for value in wcs.all_pix2world([pixel], 1)[0])
+
+
+if __name__ == "__main__":
+    evaluate = astropy_pix2world if "--astropy" in sys.argv else pix2world
+    for name, header, pixels in (("SIP", SIP_HEADER, SIP_PIXELS), ("TPV", TPV_HEADER, TPV_PIXELS)):
+        print(name)
+        for pixel in pixels:
+            print("        ([%r, %r], [%r, %r])," % (pixel + evaluate(header, pixel)))
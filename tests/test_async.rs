@@ -0,0 +1,24 @@
+#![cfg(feature = "async")]
+
+use futures_executor::block_on;
+use futures_util::io::AllowStdIo;
+use rustfits::fits::FITS;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+
+#[test]
+fn test_async_reader_matches_buffer() -> io::Result<()> {
+    let mut buffer = Vec::new();
+    File::open("tests/data/WFPC2u5780205r_c0fx.fits")?.read_to_end(&mut buffer)?;
+    let in_memory = FITS::new_from_buffer(&buffer);
+
+    block_on(async {
+        let mut reader = FITS::open_async(AllowStdIo::new(io::Cursor::new(&buffer))).await?;
+        assert_eq!(reader.list_headers(), in_memory.list_headers());
+        assert_eq!(reader.header(1), &in_memory.hdus[1].header);
+        assert_eq!(reader.read_hdu(1).await?, in_memory.hdus[1]);
+        assert_eq!(reader.read_all().await?, in_memory);
+        Ok(())
+    })
+}
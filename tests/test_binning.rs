@@ -0,0 +1,101 @@
+mod common;
+
+use common::{card, event_list_bytes};
+use rustfits::data::binning::BinOptions;
+use rustfits::data::data::Data;
+use rustfits::data::tables::BinaryTable;
+use rustfits::fits::FITS;
+use rustfits::wcs::WCS;
+
+fn event_list() -> FITS {
+    let extra = vec![
+        card("TLMIN2", "1"),
+        card("TLMAX2", "8"),
+        card("TLMIN3", "1"),
+        card("TLMAX3", "4"),
+        card("TCTYP2", "'RA---TAN'"),
+        card("TCRPX2", "4.5"),
+        card("TCRVL2", "83.6"),
+        card("TCDLT2", "-0.001"),
+        card("TCTYP3", "'DEC--TAN'"),
+        card("TCRPX3", "2.5"),
+        card("TCRVL3", "22.0"),
+        card("TCDLT3", "0.001"),
+        card("TCTY2A", "'DETX'"),
+        card("TCRP2A", "0.0"),
+        card("TCDE2A", "0.5"),
+        card("TCTY3A", "'DETY'"),
+        card("TCDE3A", "0.5"),
+        card("OBJECT", "'CRAB'"),
+    ];
+    let rows = vec![
+        vec![100.0, 1.0, 1.0],
+        vec![200.0, 2.0, 1.0],
+        vec![300.0, 8.0, 4.0],
+        vec![400.0, 7.6, 3.9],
+        vec![900.0, 8.0, 4.0],
+        vec![100.0, 20.0, 1.0],
+    ];
+    FITS::new_from_buffer(&event_list_bytes(&["PI", "X", "Y"], &extra, &rows))
+}
+
+fn table(fits: &FITS) -> &BinaryTable {
+    match &fits.hdus[1].data {
+        Data::BinaryTable(table) => table,
+        _ => panic!("Expected a binary table"),
+    }
+}
+
+#[test]
+fn test_bin_counts_and_filters() {
+    let fits = event_list();
+    let hdu = table(&fits).bin_columns(&fits.hdus[1].header, "X", "Y", 2.0, None, &BinOptions::default()).unwrap();
+    assert_eq!(hdu.header.get_naxisn(), vec![4, 2]);
+    assert_eq!(hdu.header.get_value("BITPIX"), Some("32"));
+    assert_eq!(hdu.header.get_value("OBJECT"), Some("CRAB"));
+    let image = match &hdu.data {
+        Data::Array(array) => array.view().to_f64().unwrap(),
+        _ => panic!("Expected image data"),
+    };
+    // C order: NAXIS2 rows of NAXIS1 pixels
+    let expected: Vec<f64> = vec![2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 3.0];
+    assert_eq!(image.iter().cloned().collect::<Vec<f64>>(), expected);
+
+    let options = BinOptions {
+        weight: Some("PI".to_string()),
+        filters: vec![("PI".to_string(), 0.0, 500.0)],
+    };
+    let hdu = table(&fits).bin_columns(&fits.hdus[1].header, "X", "Y", 2.0, Some([(0.5, 8.5), (0.5, 4.5)]), &options).unwrap();
+    assert_eq!(hdu.header.get_value("BITPIX"), Some("-32"));
+    let image = match &hdu.data {
+        Data::Array(array) => array.view().to_f64().unwrap(),
+        _ => panic!("Expected image data"),
+    };
+    assert_eq!(image.iter().sum::<f64>(), 1000.0);
+    assert_eq!(image.iter().cloned().fold(0.0, f64::max), 700.0);
+
+    assert!(table(&fits).bin_columns(&fits.hdus[1].header, "X", "Z", 1.0, None, &BinOptions::default()).is_err());
+    assert!(table(&fits).bin_columns(&fits.hdus[1].header, "X", "Y", 0.0, None, &BinOptions::default()).is_err());
+}
+
+#[test]
+fn test_binned_image_wcs() {
+    let fits = event_list();
+    let table = table(&fits);
+    let hdu = table.bin_columns(&fits.hdus[1].header, "X", "Y", 2.0, None, &BinOptions::default()).unwrap();
+    let events = WCS::from_columns(&fits.hdus[1].header, &["X", "Y"], ' ').unwrap();
+    let image = WCS::from_header(&hdu.header).unwrap();
+    // Pixel (2, 1) of the image covers X in [2.5, 4.5) and Y in [0.5, 2.5)
+    let expected = events.pix2world_point(&[3.5, 1.5]).unwrap();
+    let world = image.pix2world_point(&[2.0, 1.0]).unwrap();
+    assert!((world[0] - expected[0]).abs() < 1e-10);
+    assert!((world[1] - expected[1]).abs() < 1e-10);
+
+    let detector = WCS::from_header_alt(&hdu.header, 'A').unwrap();
+    assert_eq!(detector.ctype[0], "DETX");
+    assert!((detector.pix2world_point(&[2.0, 1.0]).unwrap()[0] - 1.75).abs() < 1e-12);
+
+    let mut written: Vec<u8> = Vec::new();
+    FITS { hdus: vec![hdu.clone()], diagnostics: Vec::new() }.write(&mut written).unwrap();
+    assert_eq!(FITS::new_from_buffer(&written).hdus[0], hdu);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use rustfits::checksum::{self, Status};
+use rustfits::fits::{Diagnostic, FITS};
+
+fn image() -> Vec<u8> {
+    let data: Vec<u8> = (0..100i32).flat_map(|value| value.to_be_bytes()).collect();
+    common::image_bytes(32, &[100], &[], &data)
+}
+
+#[test]
+fn test_sum_and_encoding() {
+    // End-around carry: 0xFFFFFFFF + 2 wraps to 2
+    assert_eq!(checksum::checksum(&[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 2], 0), 2);
+    assert_eq!(checksum::checksum(&[0, 0, 0, 1], 0xFFFF_FFFF), 1);
+    assert_eq!(checksum::encode(0), "0000000000000000");
+    assert_eq!(checksum::encode(0xFFFF_FFFF), "orrrrooooooooooo");
+    // Characters between '9' and 'A' and between 'Z' and 'a' are avoided
+    for value in [0x3A3A_3A3A, 0x1234_5678, 0xDEAD_BEEF, 0x8000_0001] {
+        assert!(checksum::encode(value).chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+
+    let fits = FITS::new_from_buffer(&image());
+    assert_eq!(checksum::datasum(&fits.hdus[0]), 4950);
+}
+
+#[test]
+fn test_update_and_verify() {
+    let mut fits = FITS::new_from_buffer(&image());
+    let verification = fits.hdus[0].verify_checksum();
+    assert_eq!(verification.datasum, Status::Absent);
+    assert_eq!(verification.checksum, Status::Absent);
+
+    fits.update_checksums();
+    let header = &fits.hdus[0].header;
+    assert_eq!(header.get_value("DATASUM"), Some("4950"));
+    let card = header.cards().into_iter().find(|card| card.starts_with("CHECKSUM")).unwrap();
+    assert_eq!(&card[10..11], "'");
+    assert_eq!(&card[27..28], "'");
+    let verification = fits.hdus[0].verify_checksum();
+    assert_eq!(verification.datasum, Status::Valid);
+    assert_eq!(verification.checksum, Status::Valid);
+
+    // Updating again replaces the cards instead of adding new ones
+    fits.update_checksums();
+    assert_eq!(fits.hdus[0].header.cards().len(), 6);
+
+    let bytes = fits.to_bytes();
+    let read = FITS::new_from_buffer(&bytes);
+    assert!(read.diagnostics.is_empty());
+    assert!(read.hdus[0].verify_checksum().is_valid());
+    assert!(rustfits::verify::verify(&bytes).is_empty());
+
+    // A changed pixel breaks both sums, a changed header card only CHECKSUM
+    let mut corrupted = bytes.clone();
+    corrupted[2880 + 7] ^= 0x01;
+    let read = FITS::new_from_buffer(&corrupted);
+    assert_eq!(
+        read.diagnostics,
+        vec![
+            Diagnostic::ChecksumMismatch { hdu: 0, keyword: "DATASUM".to_string() },
+            Diagnostic::ChecksumMismatch { hdu: 0, keyword: "CHECKSUM".to_string() },
+        ]
+    );
+    let mut corrupted = bytes.clone();
+    corrupted[3 * 80 + 29] = b'1';
+    let read = FITS::open(std::io::Cursor::new(corrupted.clone())).unwrap().read_all().unwrap();
+    assert_eq!(read.diagnostics, vec![Diagnostic::ChecksumMismatch { hdu: 0, keyword: "CHECKSUM".to_string() }]);
+    let issues = rustfits::verify::verify(&corrupted);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].to_string(), "HDU 0, card 6: error: CHECKSUM does not match the contents of the HDU");
+}
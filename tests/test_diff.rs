@@ -0,0 +1,148 @@
+mod common;
+
+use common::{card, float_image};
+use rustfits::data::array::ArrayData;
+use rustfits::data::data::Data;
+use rustfits::diff::{compare, compare_headers, CardDifference, DataDifference, DiffOptions};
+use rustfits::fits::FITS;
+
+#[test]
+fn test_header_differences() {
+    let a = vec![
+        card("SIMPLE", "T"),
+        "OBJECT  = 'M31     '           / target".to_string(),
+        card("EXPTIME", "100.0"),
+        "DATE    = '2024-01-01'".to_string(),
+        card("CHECKSUM", "'abc'"),
+        "HISTORY first".to_string(),
+        "HISTORY second".to_string(),
+    ];
+    let b = vec![
+        card("SIMPLE", "T"),
+        "OBJECT  = 'M31'                / source".to_string(),
+        card("EXPTIME", "1.0E2"),
+        "DATE    = '2025-06-30'".to_string(),
+        card("CHECKSUM", "'xyz'"),
+        "HISTORY first".to_string(),
+        "HISTORY changed".to_string(),
+        card("GAIN", "2.5"),
+    ];
+    let options = DiffOptions {
+        ignore_keywords: vec!["DATE".to_string(), "CHECK*".to_string()],
+        ..DiffOptions::default()
+    };
+    let differences = compare_headers(&a, &b, &options);
+    assert_eq!(
+        differences,
+        vec![
+            CardDifference::Comment {
+                keyword: "OBJECT".to_string(),
+                a: "target".to_string(),
+                b: "source".to_string()
+            },
+            CardDifference::Value {
+                keyword: "HISTORY (2)".to_string(),
+                a: "second".to_string(),
+                b: "changed".to_string()
+            },
+            CardDifference::OnlyInB {
+                keyword: "GAIN".to_string(),
+                value: "2.5".to_string()
+            },
+        ]
+    );
+
+    let options = DiffOptions {
+        ignore_comments: true,
+        ..options
+    };
+    assert_eq!(compare_headers(&a, &b, &options).len(), 2);
+    assert_eq!(compare_headers(&a, &b, &DiffOptions::default()).len(), 5);
+}
+
+#[test]
+fn test_pixel_differences() {
+    let a = float_image(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[3, 2], &[]);
+    let b = float_image(&[1.0, 2.0, 3.1, 4.0, 5.0, 6.6], &[3, 2], &[]);
+    assert!(compare(&a, &a, &DiffOptions::default()).is_identical());
+
+    let report = compare(&a, &b, &DiffOptions::default());
+    assert!(!report.is_identical());
+    assert_eq!(report.hdus.len(), 1);
+    match &report.hdus[0].data[0] {
+        DataDifference::Pixels {
+            count,
+            total,
+            max_absolute,
+            examples,
+            ..
+        } => {
+            assert_eq!((*count, *total), (2, 6));
+            assert!((max_absolute - 0.6).abs() < 1e-5);
+            assert_eq!(examples[0].0, vec![3, 1]);
+            assert_eq!(examples[1].0, vec![3, 2]);
+        }
+        other => panic!("unexpected difference {:?}", other),
+    }
+
+    let tolerant = DiffOptions {
+        rtol: 0.05,
+        ..DiffOptions::default()
+    };
+    let report = compare(&a, &b, &tolerant);
+    assert!(matches!(report.hdus[0].data[0], DataDifference::Pixels { count: 1, .. }));
+    let tolerant = DiffOptions {
+        atol: 0.7,
+        ..DiffOptions::default()
+    };
+    assert!(compare(&a, &b, &tolerant).is_identical());
+
+    // BZERO applies before comparing, and the card itself shows up as a header difference
+    let shifted = float_image(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0], &[3, 2], &[card("BZERO", "1")]);
+    let report = compare(&a, &shifted, &DiffOptions::default());
+    assert!(report.hdus[0].data.is_empty());
+    assert_eq!(report.hdus[0].header.len(), 1);
+
+    let smaller = FITS::new_from_buffer(&common::header_bytes(&[&card("SIMPLE", "T"), &card("BITPIX", "8"), &card("NAXIS", "0")]));
+    let report = compare(&a, &smaller, &DiffOptions::default());
+    assert!(report.to_string().contains("HDU 0:"));
+
+    // Data shorter than the header declares is reported rather than read past
+    let mut truncated = b.clone();
+    let header = &truncated.hdus[0].header;
+    truncated.hdus[0].data = Data::Array(ArrayData::new(Vec::new(), header, None, None));
+    let report = compare(&a, &truncated, &DiffOptions::default());
+    assert_eq!(report.hdus[0].data, vec![DataDifference::Truncated { a: (24, 24), b: (0, 24) }]);
+}
+
+#[test]
+fn test_table_differences() {
+    let rows_a = vec![vec![1.0, 10.0], vec![2.0, 20.0], vec![3.0, 30.0]];
+    let rows_b = vec![vec![1.0, 10.0], vec![2.0, 25.0]];
+    let a = FITS::new_from_buffer(&common::event_list_bytes(&["X", "PHA"], &[], &rows_a));
+    let b = FITS::new_from_buffer(&common::event_list_bytes(&["X", "PI"], &[], &rows_b));
+    let report = compare(&a, &b, &DiffOptions::default());
+    assert_eq!(report.hdus.len(), 1);
+    let table = &report.hdus[0];
+    assert_eq!(table.index, 1);
+    assert_eq!(
+        table.data,
+        vec![
+            DataDifference::Rows { a: 3, b: 2 },
+            DataDifference::ColumnOnlyInA("PHA".to_string()),
+            DataDifference::ColumnOnlyInB("PI".to_string()),
+        ]
+    );
+
+    let b = FITS::new_from_buffer(&common::event_list_bytes(&["X", "PHA"], &[], &[vec![1.0, 10.0], vec![2.5, 20.0], vec![3.0, 30.0]]));
+    let report = compare(&a, &b, &DiffOptions::default());
+    assert_eq!(
+        report.hdus[0].data,
+        vec![DataDifference::Column {
+            column: "X".to_string(),
+            count: 1,
+            examples: vec![(1, "2".to_string(), "2.5".to_string())]
+        }]
+    );
+    assert!(report.to_string().contains("Column X: 1 rows differ\n    row 1: 2 vs 2.5"));
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "export")]
+
+mod common;
+
+use common::float_image;
+use rustfits::export::{encode_jpeg, encode_png, write_png, PngOptions};
+use rustfits::render::{render, Limits, RenderOptions};
+
+fn decode(bytes: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+    let mut reader = png::Decoder::new(bytes).read_info().unwrap();
+    let mut buffer = vec![0u8; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    buffer.truncate(info.buffer_size());
+    (info, buffer)
+}
+
+#[test]
+fn test_png_previews() {
+    let fits = float_image(&[0.0, 5.0, 10.0, 2.5], &[2, 2], &[]);
+    let render_options = RenderOptions {
+        limits: Limits::MinMax,
+        ..RenderOptions::default()
+    };
+
+    let sixteen = PngOptions {
+        render: render_options,
+        sixteen_bit: true,
+    };
+    let (info, samples) = decode(&encode_png(&fits.hdus[0], &sixteen).unwrap());
+    assert_eq!((info.width, info.height), (2, 2));
+    assert_eq!(info.color_type, png::ColorType::Grayscale);
+    assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+    let samples: Vec<u16> = samples.chunks(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+    assert_eq!(samples, vec![65535, 16384, 0, 32768]);
+
+    let path = std::env::temp_dir().join(format!("rustfits-preview-{}.png", std::process::id()));
+    write_png(&path, &fits.hdus[0], &PngOptions { render: render_options, sixteen_bit: false }).unwrap();
+    let (info, pixels) = decode(&std::fs::read(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(&pixels[..4], &[255, 255, 255, 255]);
+}
+
+#[test]
+fn test_jpeg_preview() {
+    let values: Vec<f32> = (0..64 * 32).map(|i| (i % 64) as f32).collect();
+    let fits = float_image(&values, &[64, 32], &[]);
+    let jpeg = encode_jpeg(&render(&fits.hdus[0], &RenderOptions::default()).unwrap(), 90).unwrap();
+    assert_eq!(&jpeg[..2], &[0xff, 0xd8]);
+    assert_eq!(&jpeg[jpeg.len() - 2..], &[0xff, 0xd9]);
+}
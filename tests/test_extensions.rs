@@ -0,0 +1,158 @@
+mod common;
+
+use common::{card, data_bytes, header_bytes};
+use rustfits::data::data::Data;
+use rustfits::fits::{Diagnostic, FITS};
+use rustfits::header::HeaderType;
+use std::io;
+
+#[test]
+fn test_conforming_extension_round_trip() {
+    let mut buffer = header_bytes(&[
+        &card("SIMPLE", "T"),
+        &card("BITPIX", "8"),
+        &card("NAXIS", "0"),
+        &card("EXTEND", "T"),
+    ]);
+    buffer.extend(header_bytes(&[
+        &card("XTENSION", "'FOREIGN '"),
+        &card("BITPIX", "8"),
+        &card("NAXIS", "1"),
+        &card("NAXIS1", "3000"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+    ]));
+    let payload: Vec<u8> = (0..3000).map(|i| (i % 251) as u8).collect();
+    buffer.extend(data_bytes(&payload));
+
+    let fits = FITS::new_from_buffer(&buffer);
+    assert_eq!(fits.hdus.len(), 2);
+    assert_eq!(
+        fits.hdus[1].header.get_header_type(),
+        HeaderType::Other("FOREIGN".to_string())
+    );
+    assert_eq!(fits.hdus[1].header.data_size(), Some(3000));
+    match &fits.hdus[1].data {
+        Data::Raw(raw) => {
+            assert_eq!(raw.n_bytes(), 3000);
+            assert_eq!(raw.bytes(), payload);
+        }
+        _ => panic!("Expected raw data"),
+    }
+    assert_eq!(fits.to_bytes(), buffer);
+}
+
+fn primary_header() -> Vec<u8> {
+    header_bytes(&[
+        &card("SIMPLE", "T"),
+        &card("BITPIX", "8"),
+        &card("NAXIS", "0"),
+        &card("EXTEND", "T"),
+    ])
+}
+
+#[test]
+fn test_data_starting_with_xtension_is_not_a_header() {
+    let mut buffer = primary_header();
+    buffer.extend(header_bytes(&[
+        &card("XTENSION", "'TABLE   '"),
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "10"),
+        &card("NAXIS2", "1"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+        &card("TFIELDS", "1"),
+        &card("TFORM1", "'A10     '"),
+        &card("TBCOL1", "1"),
+    ]));
+    buffer.extend(data_bytes(b"XTENSION A"));
+
+    let fits = FITS::new_from_buffer(&buffer);
+    assert_eq!(fits.hdus.len(), 2);
+    assert!(fits.diagnostics.is_empty());
+    match &fits.hdus[1].data {
+        Data::ASCIITable(table) => {
+            assert_eq!(table.format_data().get_row(0)[0].to_string(), "XTENSION A");
+        }
+        _ => panic!("Expected an ASCII table"),
+    }
+}
+
+#[test]
+fn test_truncated_data_is_reported() {
+    let mut buffer = primary_header();
+    buffer.extend(header_bytes(&[
+        &card("XTENSION", "'IMAGE   '"),
+        &card("BITPIX", "16"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "100"),
+        &card("NAXIS2", "100"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+    ]));
+    buffer.extend(vec![1u8; 5000]);
+
+    let fits = FITS::new_from_buffer(&buffer);
+    assert_eq!(fits.hdus.len(), 2);
+    assert_eq!(
+        fits.diagnostics,
+        vec![Diagnostic::Truncated {
+            hdu: 1,
+            expected: 20000,
+            available: 5000,
+        }]
+    );
+    assert_eq!(fits.hdus[1].data.get_fitsblocks().len(), 7);
+
+    // The missing pixels decode as zeros, whichever way the file is read
+    let read = FITS::open(io::Cursor::new(buffer)).unwrap().read_all().unwrap();
+    assert_eq!(read, fits);
+    let data = match &fits.hdus[1].data {
+        Data::Array(data) => data,
+        _ => panic!("Expected an image"),
+    };
+    let decoded = data.format_data();
+    assert_eq!(decoded.shape(), &[100, 100]);
+    assert_eq!((decoded[[0, 0]].to_f64(), decoded[[99, 99]].to_f64()), (257.0, 0.0));
+    let values = data.view().to_f64().unwrap();
+    assert_eq!(values.iter().filter(|value| **value == 257.0).count(), 2500);
+    assert_eq!(values.iter().filter(|value| **value == 0.0).count(), 7500);
+}
+
+#[test]
+fn test_overflowing_data_size_is_reported() {
+    let mut buffer = primary_header();
+    buffer.extend(header_bytes(&[
+        &card("XTENSION", "'IMAGE   '"),
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "9223372036854775807"),
+        &card("NAXIS2", "4"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+    ]));
+    buffer.extend(vec![0u8; 2880]);
+    let header = common::header_from_cards(&[
+        &card("SIMPLE", "T"),
+        &card("BITPIX", "8"),
+        &card("NAXIS", "1"),
+        &card("NAXIS1", "18446744073709551615"),
+        &card("GCOUNT", "2"),
+    ]);
+    assert_eq!(header.data_size(), None);
+
+    let expected = vec![Diagnostic::InvalidDataSize { hdu: 1 }];
+    let fits = FITS::new_from_buffer(&buffer);
+    assert_eq!(fits.hdus.len(), 2);
+    assert_eq!(fits.diagnostics, expected);
+    assert!(fits.hdus[1].data.get_fitsblocks().is_empty());
+
+    let mut reader = FITS::open(std::io::Cursor::new(buffer.clone())).unwrap();
+    assert_eq!(reader.diagnostics, expected);
+    assert_eq!(reader.read_all().unwrap().hdus.len(), 2);
+
+    let mut parser = rustfits::stream::FitsStreamParser::new();
+    assert_eq!(parser.feed(&buffer).len(), 4);
+    assert_eq!(parser.finish(), expected);
+}
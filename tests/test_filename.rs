@@ -0,0 +1,145 @@
+mod common;
+
+use common::card;
+use rustfits::data::data::Data;
+use rustfits::filename::{AxisRange, ExtendedFilename, HduSelector};
+use rustfits::fits::FITS;
+use std::path::PathBuf;
+
+fn write_temp(name: &str, bytes: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rustfits-{}-{}.fits", name, std::process::id()));
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+#[test]
+fn test_parse() {
+    let filename = ExtendedFilename::parse("events.fits[EVENTS, 2][PI > 100][col X; Y][#ROW <= 10]").unwrap();
+    assert_eq!(filename.path, "events.fits");
+    assert_eq!(
+        filename.hdu,
+        Some(HduSelector::Name {
+            name: "EVENTS".to_string(),
+            version: Some(2)
+        })
+    );
+    assert_eq!(filename.columns, Some(vec!["X".to_string(), "Y".to_string()]));
+    assert_eq!(filename.row_filter.unwrap().text(), "(PI > 100) && (#ROW <= 10)");
+
+    let filename = ExtendedFilename::parse("image.fits+1[100:200, *:4]").unwrap();
+    assert_eq!(filename.path, "image.fits");
+    assert_eq!(filename.hdu, Some(HduSelector::Index(1)));
+    assert_eq!(
+        filename.section,
+        Some(vec![
+            AxisRange { start: 100, end: Some(200), step: 1 },
+            AxisRange { start: 1, end: None, step: 4 },
+        ])
+    );
+
+    // A first bracket that is not an HDU is a filter on the first table
+    let filename = ExtendedFilename::parse("events.fits[X > 2 || !(Y == 1)]").unwrap();
+    assert_eq!(filename.hdu, None);
+    assert!(filename.row_filter.is_some());
+
+    assert!(ExtendedFilename::parse("image.fits[1][200:100]").is_err());
+    assert!(ExtendedFilename::parse("image.fits[1][0:10]").is_err());
+    assert!(ExtendedFilename::parse("image.fits[1][1:10][X > 1]").is_err());
+    assert!(ExtendedFilename::parse("events.fits[1][X >]").is_err());
+    assert!(ExtendedFilename::parse("events.fits[1").is_err());
+    assert!(ExtendedFilename::parse("[1]").is_err());
+    // Non-ASCII text is an error, not a panic
+    assert!(ExtendedFilename::parse("a.fits[abcé>1]").is_err());
+    assert!(ExtendedFilename::parse("a.fits[1][é]").is_err());
+}
+
+#[test]
+fn test_image_section() {
+    // Pixel (x, y) holds 10 * y + x
+    let mut bytes = common::header_bytes(&[
+        &card("SIMPLE", "T"),
+        &card("BITPIX", "16"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "4"),
+        &card("NAXIS2", "3"),
+        &card("CRPIX1", "2.0"),
+        &card("CDELT1", "0.5"),
+        &card("CRPIX2", "3.0"),
+        &card("CDELT2", "0.25"),
+        &card("OBJECT", "'grid'"),
+    ]);
+    let data: Vec<u8> = (1..=3i16).flat_map(|y| (1..=4i16).flat_map(move |x| (10 * y + x).to_be_bytes())).collect();
+    bytes.extend(common::data_bytes(&data));
+    let path = write_temp("section", &bytes);
+
+    let fits = FITS::open_extended(&format!("{}[0][2:4:2, 2:3]", path.display())).unwrap();
+    assert_eq!(fits.hdus.len(), 1);
+    let header = &fits.hdus[0].header;
+    assert_eq!(header.get_naxisn(), vec![2, 2]);
+    assert_eq!(header.get_f64("CRPIX1"), Some(1.0));
+    assert_eq!(header.get_f64("CDELT1"), Some(1.0));
+    assert_eq!(header.get_f64("CRPIX2"), Some(2.0));
+    assert_eq!(header.get_f64("CDELT2"), Some(0.25));
+    assert_eq!(header.get_value("OBJECT"), Some("grid"));
+    let pixels: Vec<i16> = fits.hdus[0].data.get_fitsblocks()[0][..8]
+        .chunks(2)
+        .map(|pair| i16::from_be_bytes([pair[0], pair[1]]))
+        .collect();
+    assert_eq!(pixels, vec![22, 24, 32, 34]);
+
+    // The written file reads back as a conforming image
+    let written = FITS::new_from_buffer(&fits.to_bytes());
+    assert!(written.diagnostics.is_empty());
+    assert!(rustfits::verify::verify(&fits.to_bytes()).is_empty());
+
+    assert!(FITS::open_extended(&format!("{}[1:5, *]", path.display())).is_err());
+    assert!(FITS::open_extended(&format!("{}[*]", path.display())).is_err());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_table_selection() {
+    let extra = vec![
+        card("TUNIT3", "'chan'"),
+        card("TTYPE4", "'FLAG'"),
+        card("EXTNAME", "'EVENTS'"),
+    ];
+    let rows = vec![
+        vec![1.0, 10.0, 50.0],
+        vec![2.0, 20.0, 150.0],
+        vec![7.0, 30.0, 250.0],
+        vec![4.0, 40.0, 350.0],
+    ];
+    let path = write_temp("table", &common::event_list_bytes(&["X", "Y", "PI"], &extra, &rows));
+
+    let fits = FITS::open_extended(&format!("{}[EVENTS][PI > 100 && x < 5][col PI;X]", path.display())).unwrap();
+    assert_eq!(fits.hdus.len(), 2);
+    let header = &fits.hdus[1].header;
+    assert_eq!(header.get_f64("NAXIS1"), Some(8.0));
+    assert_eq!(header.get_f64("NAXIS2"), Some(2.0));
+    assert_eq!(header.get_f64("TFIELDS"), Some(2.0));
+    assert_eq!(header.get_value("TTYPE1"), Some("PI"));
+    assert_eq!(header.get_value("TUNIT1"), Some("chan"));
+    assert_eq!(header.get_value("TTYPE2"), Some("X"));
+    assert!(!header.contains_keyword("TTYPE3"));
+    assert!(!header.contains_keyword("TTYPE4"));
+    assert_eq!(header.get_value("EXTNAME"), Some("EVENTS"));
+    let table = match &fits.hdus[1].data {
+        Data::BinaryTable(table) => table,
+        _ => panic!("Expected a binary table"),
+    };
+    assert_eq!(table.column_f64(0), Some(vec![vec![150.0], vec![350.0]]));
+    assert_eq!(table.column_f64(1), Some(vec![vec![2.0], vec![4.0]]));
+
+    // Without a selection the whole file is copied
+    let fits = FITS::open_extended(&path.display().to_string()).unwrap();
+    assert_eq!(fits.hdus[1].header.get_f64("NAXIS2"), Some(4.0));
+    let fits = FITS::open_extended(&format!("{}[#ROW == 3 || Y / 2 == 5]", path.display())).unwrap();
+    assert_eq!(fits.hdus[1].header.get_f64("NAXIS2"), Some(2.0));
+
+    let err = FITS::open_extended(&format!("{}[1][col ENERGY]", path.display())).unwrap_err();
+    assert_eq!(err.to_string(), "No column ENERGY, the table has X, Y, PI");
+    assert!(FITS::open_extended(&format!("{}[SCI]", path.display())).is_err());
+    assert!(FITS::open_extended(&format!("{}[0][X > 1]", path.display())).is_err());
+    std::fs::remove_file(path).unwrap();
+}
@@ -1,5 +1,5 @@
 use rustfits::data::data::Data::{ASCIITable, BinaryTable, Array};
-use rustfits::fits::{FITS, HDU};
+use rustfits::fits::FITS;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
@@ -0,0 +1,65 @@
+mod common;
+
+use common::card;
+use rustfits::fits::FITS;
+
+fn extension(name: &str, version: i64, classes: &[&str]) -> Vec<u8> {
+    let mut cards = vec![
+        "XTENSION= 'IMAGE   '".to_string(),
+        card("BITPIX", "8"),
+        card("NAXIS", "0"),
+        card("PCOUNT", "0"),
+        card("GCOUNT", "1"),
+        card("EXTNAME", &format!("'{}'", name)),
+        card("EXTVER", &version.to_string()),
+    ];
+    if let Some((class, levels)) = classes.split_first() {
+        cards.push(card("HDUCLASS", &format!("'{}'", class)));
+        for (i, level) in levels.iter().enumerate() {
+            cards.push(card(&format!("HDUCLAS{}", i + 1), &format!("'{}'", level)));
+        }
+    }
+    common::header_bytes(&cards.iter().map(|card| card.as_str()).collect::<Vec<&str>>())
+}
+
+fn multi_extension() -> Vec<u8> {
+    let mut bytes = common::header_bytes(&[&card("SIMPLE", "T"), &card("BITPIX", "8"), &card("NAXIS", "0")]);
+    bytes.extend(extension("SCI", 1, &["OGIP", "IMAGE", "TOTAL"]));
+    bytes.extend(extension("ERR", 1, &["OGIP", "IMAGE", "ERROR"]));
+    bytes.extend(extension("SCI", 2, &["OGIP", "IMAGE", "TOTAL"]));
+    bytes.extend(extension("EVENTS", 1, &[]));
+    bytes
+}
+
+#[test]
+fn test_lookup_by_name_and_version() {
+    let fits = FITS::new_from_buffer(&multi_extension());
+    assert_eq!(fits.hdu_index("SCI", None), Ok(1));
+    assert_eq!(fits.hdu_index("sci", Some(2)), Ok(3));
+    assert_eq!(fits.hdu("ERR").unwrap().header.get_extname(), Some("ERR"));
+    assert_eq!(fits.hdu_ver("SCI", 2).unwrap().header.get_extver(), 2);
+    assert_eq!(
+        fits.hdu_ver("SCI", 3).unwrap_err(),
+        "No HDU named SCI with EXTVER 3, the file has SCI (EXTVER 1), ERR (EXTVER 1), SCI (EXTVER 2), EVENTS (EXTVER 1)"
+    );
+    assert_eq!(
+        FITS::new_from_buffer(&multi_extension()[..2880]).hdu("SCI").unwrap_err(),
+        "No HDU named SCI, the file has no named HDUs"
+    );
+
+    // The lazy reader resolves names from the headers alone
+    let reader = FITS::open(std::io::Cursor::new(multi_extension())).unwrap();
+    assert_eq!(reader.hdu_index("EVENTS", Some(1)), Ok(4));
+}
+
+#[test]
+fn test_iteration_by_class() {
+    let fits = FITS::new_from_buffer(&multi_extension());
+    assert_eq!(fits.hdus[2].header.get_hduclas(), vec!["OGIP", "IMAGE", "ERROR"]);
+    assert!(fits.hdus[4].header.get_hduclas().is_empty());
+    let images: Vec<usize> = fits.hdus_with_class("image").map(|(index, _)| index).collect();
+    assert_eq!(images, vec![1, 2, 3]);
+    let totals: Vec<i64> = fits.hdus_with_class("TOTAL").map(|(_, hdu)| hdu.header.get_extver()).collect();
+    assert_eq!(totals, vec![1, 2]);
+    assert_eq!(fits.hdus_with_class("SPECTRUM").count(), 0);
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::{card, header_from_cards};
+
+#[test]
+fn test_value_starts_after_value_indicator() {
+    // Free-format values may start in column 11, right after "= "
+    let header = header_from_cards(&[
+        &card("SIMPLE", "T"),
+        &card("MJDREFF", "7.428703703703703E-04"),
+        "EXPOSURE= 1234.5 / free-format value",
+    ]);
+    assert_eq!(header.get_f64("MJDREFF"), Some(7.428703703703703E-04));
+    assert_eq!(header.get_f64("EXPOSURE"), Some(1234.5));
+}
+
+#[test]
+fn test_slash_inside_quotes_is_not_a_comment() {
+    let header = header_from_cards(&[
+        &card("SIMPLE", "T"),
+        "DATE-OBS= '04/03/95'           / old-style date",
+        "FILTER  = 'F555W/F814W'",
+        "OBJECT  = 'NGC 4151'           / name / with slashes",
+    ]);
+    assert_eq!(header.get_value("DATE-OBS"), Some("04/03/95"));
+    assert_eq!(header.get_value("FILTER"), Some("F555W/F814W"));
+    assert_eq!(header.get_value("OBJECT"), Some("NGC4151"));
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{card, header_from_cards};
+use rustfits::fits::FITS;
+use rustfits::header::Card;
+use rustfits::info;
+
+#[test]
+fn test_summaries() {
+    let mut bytes = common::header_bytes(&[
+        &card("SIMPLE", "T"),
+        &card("BITPIX", "16"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "3"),
+        &card("NAXIS2", "2"),
+        &card("BZERO", "32768"),
+    ]);
+    bytes.extend(common::data_bytes(&[0u8; 12]));
+    bytes.extend(common::event_list_bytes(
+        &["X", "Y"],
+        &["EXTNAME = 'EVENTS  '".to_string(), card("EXTVER", "2")],
+        &[vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]],
+    ));
+    let fits = FITS::new_from_buffer(&bytes);
+    // event_list_bytes writes its own primary HDU
+    let summaries = info::summaries(fits.list_headers(), fits.hdus.iter().map(|hdu| &hdu.header));
+    assert_eq!(summaries.len(), 3);
+
+    assert_eq!(summaries[0].name, "PRIMARY");
+    assert_eq!(summaries[0].kind, "Primary");
+    assert_eq!(summaries[0].cards, 6);
+    assert_eq!(summaries[0].format, "uint16");
+    assert_eq!(summaries[0].shape(), "3 x 2");
+
+    let table = &summaries[2];
+    assert_eq!(table.name, "EVENTS");
+    assert_eq!(table.version, 2);
+    assert_eq!(table.kind, "Binary Table");
+    assert_eq!(table.columns, 2);
+    assert_eq!(table.format, "[E, E]");
+    assert_eq!(table.shape(), "3R x 2C");
+
+    let text = info::format_summaries(&summaries);
+    assert_eq!(text.lines().count(), 4);
+    assert!(text.lines().nth(3).unwrap().contains("EVENTS"));
+}
+
+#[test]
+fn test_header_listing() {
+    let header = header_from_cards(&[
+        &card("SIMPLE", "T"),
+        &card("NAXIS", "0"),
+        "OBJECT  = 'M31 ''core'''      / target, with a comment",
+        "DATE-OBS= '1995/01/02'",
+        "HISTORY processed",
+    ]);
+    let cards = header.cards();
+    assert_eq!(cards.len(), 5);
+
+    let object = Card::parse(&cards[2]);
+    assert_eq!(object.keyword, "OBJECT");
+    assert_eq!(object.value.as_deref(), Some("'M31 ''core'''"));
+    assert_eq!(object.value_text().as_deref(), Some("M31 'core'"));
+    assert_eq!(object.comment, "target, with a comment");
+    assert_eq!(Card::parse(&cards[3]).value_text().as_deref(), Some("1995/01/02"));
+    assert_eq!(Card::parse(&cards[4]).value, None);
+
+    assert!(info::glob_match("date*", "DATE-OBS"));
+    assert!(info::glob_match("NAXIS?", "NAXIS1"));
+    assert!(!info::glob_match("NAXIS?", "NAXIS"));
+    let selected = info::select_cards(&header, &["OBJ*".to_string(), "naxis".to_string()]);
+    assert_eq!(selected.len(), 2);
+
+    let listing = vec![(0, selected)];
+    assert_eq!(
+        info::cards_json(&listing),
+        "[{\"hdu\": 0, \"cards\": [{\"keyword\": \"NAXIS\", \"value\": 0, \"comment\": \"\"}, \
+         {\"keyword\": \"OBJECT\", \"value\": \"M31 'core'\", \"comment\": \"target, with a comment\"}]}]\n"
+    );
+    assert_eq!(
+        info::cards_csv(&listing),
+        "hdu,keyword,value,comment\n0,NAXIS,0,\n0,OBJECT,M31 'core',\"target, with a comment\"\n"
+    );
+    assert!(info::cards_text(&listing).starts_with("# HDU 0\nNAXIS   =                    0\n"));
+}
@@ -0,0 +1,71 @@
+use rustfits::data::data::Data;
+use rustfits::data::endian::{BeF32, BigEndianScalar};
+use rustfits::data::view::ImageView;
+use rustfits::fits::FITS;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+
+#[test]
+fn test_image_view_matches_format_data() -> io::Result<()> {
+    let mut buffer = Vec::new();
+    File::open("tests/data/WFPC2u5780205r_c0fx.fits")?.read_to_end(&mut buffer)?;
+    let fits = FITS::new_from_buffer(&buffer);
+    match &fits.hdus[0].data {
+        Data::Array(data) => {
+            let view = data.view();
+            let decoded = data.format_data().map(|value| value.to_f64());
+            assert_eq!(view.to_f64().unwrap(), decoded);
+            let big_endian = view.view::<BeF32>().unwrap();
+            assert_eq!(big_endian.shape(), decoded.shape());
+            assert_eq!(big_endian[[3, 7, 1]].to_f64(), decoded[[3, 7, 1]]);
+        }
+        _ => panic!("Expected an image"),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_image_view_of_short_data() {
+    let bytes = [0u8, 1, 0, 2, 0, 3];
+    assert_eq!(ImageView::new(&bytes, 16, &[2, 2]).to_f64(), None);
+    assert_eq!(ImageView::new(&bytes, 12, &[3]).to_f64(), None);
+    assert_eq!(ImageView::new(&bytes, 16, &[3]).to_f64().unwrap().iter().cloned().collect::<Vec<f64>>(), vec![1.0, 2.0, 3.0]);
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_mmap_matches_buffer() -> io::Result<()> {
+    let mut buffer = Vec::new();
+    File::open("tests/data/WFPC2u5780205r_c0fx.fits")?.read_to_end(&mut buffer)?;
+    let fits = FITS::new_from_buffer(&buffer);
+    let mapped = FITS::open_mmap("tests/data/WFPC2u5780205r_c0fx.fits")?;
+    assert_eq!(mapped.len(), fits.hdus.len());
+    assert_eq!(mapped.read_hdu(1)?, fits.hdus[1]);
+    match &fits.hdus[0].data {
+        Data::Array(data) => assert_eq!(mapped.image(0)?.unwrap().to_f64(), data.view().to_f64()),
+        _ => panic!("Expected an image"),
+    }
+    assert!(mapped.image(1)?.is_none());
+    for error in [mapped.data_bytes(2).err(), mapped.image(2).err(), mapped.read_hdu(2).err()].iter() {
+        assert_eq!(error.as_ref().map(|error| error.kind()), Some(io::ErrorKind::InvalidInput));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_mmap_truncated_file() -> io::Result<()> {
+    let mut buffer = Vec::new();
+    File::open("tests/data/WFPC2u5780205r_c0fx.fits")?.read_to_end(&mut buffer)?;
+    buffer.truncate(2880 * 20);
+    let path = std::env::temp_dir().join(format!("rustfits-truncated-{}.fits", std::process::id()));
+    std::fs::write(&path, &buffer)?;
+    let mapped = FITS::open_mmap(&path);
+    std::fs::remove_file(&path)?;
+    let mapped = mapped?;
+    let fits = FITS::new_from_buffer(&buffer);
+    assert_eq!(mapped.read_hdu(0)?, fits.hdus[0]);
+    assert_eq!(mapped.image(0)?.unwrap().to_f64(), None);
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use rustfits::fits::FITS;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+
+#[test]
+fn test_lazy_reader_matches_buffer() -> io::Result<()> {
+    let mut buffer = Vec::new();
+    File::open("tests/data/WFPC2u5780205r_c0fx.fits")?.read_to_end(&mut buffer)?;
+    let in_memory = FITS::new_from_buffer(&buffer);
+
+    let mut reader = FITS::open(File::open("tests/data/WFPC2u5780205r_c0fx.fits")?)?;
+    assert_eq!(reader.len(), in_memory.hdus.len());
+    assert_eq!(reader.list_headers(), in_memory.list_headers());
+    assert_eq!(reader.entries()[0].data_offset % 2880, 0);
+
+    // Read the second HDU first to make sure access does not depend on order
+    assert_eq!(reader.read_hdu(1)?, in_memory.hdus[1]);
+    assert_eq!(reader.read_all()?, in_memory);
+
+    let bytes = reader.read_data_bytes(0, 0, 4)?;
+    assert_eq!(bytes[..], in_memory.hdus[0].data.get_fitsblocks()[0][..4]);
+    assert!(reader.read_data_bytes(0, u32::MAX as u64, 4).is_err());
+    Ok(())
+}
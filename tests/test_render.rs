@@ -0,0 +1,81 @@
+mod common;
+
+use common::{card, float_image, header_bytes};
+use rustfits::fits::FITS;
+use rustfits::render::{limits, lupton_rgb, render, render_values, Colormap, Limits, LuptonOptions, RenderOptions, Stretch};
+
+#[test]
+fn test_render_gray_linear() {
+    let fits = float_image(&[0.0, 1.0, 2.0, f32::NAN], &[2, 2], &[]);
+    let options = RenderOptions {
+        limits: Limits::MinMax,
+        ..RenderOptions::default()
+    };
+    let rendered = render(&fits.hdus[0], &options).unwrap();
+    assert_eq!((rendered.width, rendered.height), (2, 2));
+    // The first FITS row is displayed at the bottom, and NaN is transparent
+    assert_eq!(rendered.pixels, vec![255, 255, 255, 255, 0, 0, 0, 0, 0, 0, 0, 255, 128, 128, 128, 255]);
+
+    let inverted = RenderOptions { invert: true, ..options };
+    assert_eq!(render(&fits.hdus[0], &inverted).unwrap().pixels[..4], [0, 0, 0, 255]);
+    let empty = FITS::new_from_buffer(&header_bytes(&[&card("SIMPLE", "T"), &card("BITPIX", "8"), &card("NAXIS", "0")]));
+    assert!(render(&empty.hdus[0], &options).is_err());
+
+    // The missing pixels of a truncated file are zero-filled
+    let bytes = common::image_bytes(8, &[100, 80], &[], &[1u8; 8000]);
+    let truncated = FITS::new_from_buffer(&bytes[..2880 + 3000]);
+    let rendered = render(&truncated.hdus[0], &options).unwrap();
+    assert_eq!(rendered.pixels[..4], [0, 0, 0, 255]);
+    assert_eq!(rendered.pixels[rendered.pixels.len() - 4..], [255, 255, 255, 255]);
+}
+
+#[test]
+fn test_limits_and_stretches() {
+    let ramp: Vec<f64> = (0..=100).map(|v| v as f64).collect();
+    assert_eq!(limits(&ramp, Limits::MinMax), (0.0, 100.0));
+    assert_eq!(limits(&ramp, Limits::Percentile(1.0, 99.0)), (1.0, 99.0));
+
+    let mut outliers: Vec<f64> = (0..1000).map(|v| v as f64).collect();
+    outliers.extend([1e6, 2e6, f64::NAN].iter());
+    let (low, high) = limits(&outliers, Limits::ZScale);
+    assert!(low >= 0.0 && high < 1e4, "{} {}", low, high);
+
+    for stretch in [Stretch::Linear, Stretch::Sqrt, Stretch::Log, Stretch::Asinh].iter() {
+        assert!(stretch.apply(0.0).abs() < 1e-12);
+        assert!((stretch.apply(1.0) - 1.0).abs() < 1e-12);
+        assert!(stretch.apply(0.25) < stretch.apply(0.5));
+    }
+
+    let options = RenderOptions {
+        stretch: Stretch::Histogram,
+        limits: Limits::MinMax,
+        colormap: Colormap::Gray,
+        invert: false,
+    };
+    let rendered = render_values(&[1.0, 10.0, 100.0, 1000.0], 4, 1, &options);
+    let gray: Vec<u8> = rendered.pixels.chunks(4).map(|pixel| pixel[0]).collect();
+    assert_eq!(gray, vec![0, 85, 170, 255]);
+
+    assert_eq!(Colormap::Viridis.color(0.0), [0x44, 0x01, 0x54]);
+    assert_eq!(Colormap::Viridis.color(1.0), [0xfd, 0xe7, 0x25]);
+    assert_eq!(Colormap::from_name("Inferno"), Some(Colormap::Inferno));
+}
+
+#[test]
+fn test_lupton_composite() {
+    let red = float_image(&[0.0, 1.0, 1000.0, 1000.0], &[2, 2], &[]);
+    let green = float_image(&[0.0, 1.0, 1000.0, 400.0], &[2, 2], &[]);
+    let blue = float_image(&[0.0, 1.0, 1000.0, 0.0], &[2, 2], &[]);
+    let rgb = lupton_rgb(&red.hdus[0], &green.hdus[0], &blue.hdus[0], &LuptonOptions::default()).unwrap();
+    let pixel = |x: usize, y: usize| rgb.pixels[(y * 2 + x) * 4..(y * 2 + x) * 4 + 4].to_vec();
+    // First FITS row at the bottom
+    assert_eq!(pixel(0, 1), vec![0, 0, 0, 255]);
+    let faint = pixel(1, 1);
+    assert!(faint[0] > 0 && faint[0] == faint[1] && faint[1] == faint[2]);
+    // Saturated pixels keep their colour ratios
+    assert_eq!(pixel(0, 0), vec![255, 255, 255, 255]);
+    assert_eq!(pixel(1, 0), vec![255, 102, 0, 255]);
+
+    let small = float_image(&[0.0], &[1, 1], &[]);
+    assert!(lupton_rgb(&red.hdus[0], &green.hdus[0], &small.hdus[0], &LuptonOptions::default()).is_err());
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::card;
+use ndarray::{s, IxDyn};
+use rustfits::data::data::Data;
+use rustfits::fits::FITS;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+
+#[test]
+fn test_section_matches_sliced_format_data() -> io::Result<()> {
+    let mut buffer = Vec::new();
+    File::open("tests/data/WFPC2u5780205r_c0fx.fits")?.read_to_end(&mut buffer)?;
+    let fits = FITS::new_from_buffer(&buffer);
+    let data = match &fits.hdus[0].data {
+        Data::Array(data) => data,
+        _ => panic!("Expected an image"),
+    };
+    let full = data.format_data().into_dimensionality::<ndarray::Ix3>().unwrap();
+
+    let section = data.read_section(&[10..30, 50..60, 1..3]).unwrap();
+    assert_eq!(section, full.slice(s![10..30, 50..60, 1..3]).into_dyn());
+
+    let strided = data.read_section_strided(&[0..200, 5..17, 0..4], &[7, 3, 2]).unwrap();
+    assert_eq!(strided, full.slice(s![0..200;7, 5..17;3, 0..4;2]).into_dyn());
+
+    let mut reader = FITS::open(File::open("tests/data/WFPC2u5780205r_c0fx.fits")?)?;
+    assert_eq!(reader.read_section(0, &[10..30, 50..60, 1..3])?, section);
+    assert_eq!(reader.read_section_strided(0, &[0..200, 5..17, 0..4], &[7, 3, 2])?, strided);
+    assert_eq!(strided.raw_dim(), IxDyn(&[29, 4, 2]));
+    assert!(reader.read_section(0, &[0..201, 0..1, 0..1]).is_err());
+    assert!(reader.read_section(1, &[0..1, 0..1]).is_err());
+    assert!(data.read_section(&[0..201, 0..1, 0..1]).is_err());
+    assert!(data.view().read_section(&[0..1, 0..1]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_section_errors_instead_of_panicking() -> io::Result<()> {
+    // No axes at all
+    let empty = common::header_bytes(&[&card("SIMPLE", "T"), &card("BITPIX", "8"), &card("NAXIS", "0")]);
+    assert!(FITS::open(io::Cursor::new(empty))?.read_section(0, &[]).is_err());
+
+    // 4000 data bytes of which only 2880 are present
+    let mut truncated = common::header_bytes(&[
+        &card("SIMPLE", "T"),
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "2000"),
+        &card("NAXIS2", "2"),
+    ]);
+    truncated.extend(vec![1u8; 2880]);
+    let fits = FITS::new_from_buffer(&truncated);
+    let data = match &fits.hdus[0].data {
+        Data::Array(data) => data,
+        _ => panic!("Expected an image"),
+    };
+    assert!(data.read_section(&[0..1000, 0..2]).is_ok());
+    // In memory the missing bytes are zero-filled, a lazy reader refuses to read past the end
+    assert_eq!(data.read_section(&[1999..2000, 0..2]).unwrap()[[0, 1]].to_f64(), 0.0);
+    let mut reader = FITS::open(io::Cursor::new(truncated))?;
+    assert!(reader.read_section(0, &[0..1000, 0..2]).is_ok());
+    assert!(reader.read_section(0, &[1999..2000, 0..2]).is_err());
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use rustfits::fits::FITS;
+use rustfits::stream::{FitsStreamParser, StreamEvent};
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+
+#[test]
+fn test_stream_parser_matches_buffer() -> io::Result<()> {
+    let mut buffer = Vec::new();
+    File::open("tests/data/WFPC2u5780205r_c0fx.fits")?.read_to_end(&mut buffer)?;
+    let in_memory = FITS::new_from_buffer(&buffer);
+
+    let mut parser = FitsStreamParser::new();
+    let mut headers = Vec::new();
+    let mut data: Vec<Vec<u8>> = Vec::new();
+    let mut completed = Vec::new();
+    let mut cursor = 0;
+    for (i, size) in [1, 7, 2879, 4096, 100000].iter().cycle().enumerate() {
+        if cursor >= buffer.len() || i > 10000 {
+            break;
+        }
+        let end = (cursor + size).min(buffer.len());
+        for event in parser.feed(&buffer[cursor..end]) {
+            match event {
+                StreamEvent::HeaderComplete(index, header) => {
+                    assert_eq!(index, headers.len());
+                    headers.push(header);
+                    data.push(Vec::new());
+                }
+                StreamEvent::DataChunk(index, bytes) => data[index].extend_from_slice(bytes),
+                StreamEvent::HduComplete(index) => completed.push(index),
+            }
+        }
+        cursor = end;
+    }
+    assert!(parser.finish().is_empty());
+
+    assert_eq!(completed, (0..in_memory.hdus.len()).collect::<Vec<usize>>());
+    for (i, hdu) in in_memory.hdus.iter().enumerate() {
+        assert_eq!(headers[i], hdu.header);
+        let expected = &hdu.data.get_fitsblocks().as_flattened()[..hdu.header.data_size().unwrap()];
+        assert_eq!(data[i], expected);
+    }
+
+    let mut truncated = FitsStreamParser::new();
+    truncated.feed(&buffer[..buffer.len() - 3000]);
+    assert_eq!(truncated.finish().len(), 1);
+    Ok(())
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::{card, data_bytes, event_list_bytes, header_bytes};
+use rustfits::data::data::Data;
+use rustfits::data::tables::BinaryField;
+use rustfits::fits::FITS;
+
+#[test]
+fn test_ascii_table_columns() {
+    let mut buffer = header_bytes(&[&card("SIMPLE", "T"), &card("BITPIX", "8"), &card("NAXIS", "0")]);
+    buffer.extend(header_bytes(&[
+        &card("XTENSION", "'TABLE'"),
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "16"),
+        &card("NAXIS2", "3"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+        &card("TFIELDS", "2"),
+        &card("TTYPE1", "'NAME'"),
+        &card("TFORM1", "'A6'"),
+        &card("TBCOL1", "1"),
+        &card("TTYPE2", "'FLUX'"),
+        &card("TFORM2", "'D10.3'"),
+        &card("TBCOL2", "7"),
+        &card("TUNIT2", "'Jy'"),
+    ]));
+    buffer.extend(data_bytes(b"M31     1.5D+00 M87       -2.25 NGC1  "));
+    let fits = FITS::new_from_buffer(&buffer);
+    let table = match &fits.hdus[1].data {
+        Data::ASCIITable(table) => table,
+        _ => panic!("Expected an ASCII table"),
+    };
+    assert_eq!(table.n_rows(), 3);
+    assert_eq!(table.column_index("flux"), Some(1));
+    assert_eq!(table.get_tunitn()[1], "Jy");
+    assert_eq!(table.column_strings_rows(0, 1..10), vec!["M87".to_string(), "NGC1".to_string()]);
+    let flux = table.column_f64_rows(1, 0..3).unwrap();
+    assert_eq!(&flux[..2], &[1.5, -2.25]);
+    assert!(flux[2].is_nan());
+    assert!(table.column_f64_rows(0, 0..3).is_none());
+}
+
+#[test]
+fn test_binary_table_row_ranges() {
+    let rows: Vec<Vec<f32>> = (0..10).map(|i| vec![i as f32, 2.0 * i as f32]).collect();
+    let fits = FITS::new_from_buffer(&event_list_bytes(&["A", "B"], &[card("TSCAL2", "0.5")], &rows));
+    let table = match &fits.hdus[1].data {
+        Data::BinaryTable(table) => table,
+        _ => panic!("Expected a binary table"),
+    };
+    assert_eq!(table.column_f64_rows(1, 3..6).unwrap(), vec![vec![3.0], vec![4.0], vec![5.0]]);
+    assert_eq!(table.column_f64_rows(0, 8..20).unwrap().len(), 2);
+    assert!(table.column_f64_rows(0, 12..20).unwrap().is_empty());
+}
+
+#[test]
+fn test_binary_table_format_data() {
+    // Every column is decoded, including the last one and TFORMs without a repeat count,
+    // fields start at their byte offsets within the row and repeated fields keep every element
+    let mut buffer = header_bytes(&[&card("SIMPLE", "T"), &card("BITPIX", "8"), &card("NAXIS", "0")]);
+    buffer.extend(header_bytes(&[
+        "XTENSION= 'BINTABLE'",
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "20"),
+        &card("NAXIS2", "2"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+        &card("TFIELDS", "4"),
+        &card("TFORM1", "'1J'"),
+        &card("TFORM2", "'E'"),
+        &card("TFORM3", "'2I'"),
+        &card("TFORM4", "'1D'"),
+    ]));
+    let mut data: Vec<u8> = Vec::new();
+    for row in 0..2i32 {
+        data.extend((7 + row).to_be_bytes());
+        data.extend((1.5f32 * (row + 1) as f32).to_be_bytes());
+        data.extend([3i16 + row as i16, 4].iter().flat_map(|value| value.to_be_bytes()));
+        data.extend((2.25f64 - row as f64).to_be_bytes());
+    }
+    buffer.extend(data_bytes(&data));
+    let fits = FITS::new_from_buffer(&buffer);
+    let table = match &fits.hdus[1].data {
+        Data::BinaryTable(table) => table,
+        _ => panic!("Expected a binary table"),
+    };
+    let formatted = table.format_data();
+    assert_eq!(
+        formatted.get_row(0),
+        vec![
+            BinaryField::I32(7),
+            BinaryField::F32(1.5),
+            BinaryField::Vector(vec![BinaryField::I16(3), BinaryField::I16(4)]),
+            BinaryField::F64(2.25),
+        ]
+    );
+    assert_eq!(
+        formatted.get_row(1),
+        vec![
+            BinaryField::I32(8),
+            BinaryField::F32(3.0),
+            BinaryField::Vector(vec![BinaryField::I16(4), BinaryField::I16(4)]),
+            BinaryField::F64(1.25),
+        ]
+    );
+    assert_eq!(formatted.get_row(0)[2].to_string(), "3 4");
+}
+
+#[test]
+fn test_tform_parsing() {
+    assert_eq!(BinaryField::parse_tform("20A"), Some((20, 'A')));
+    assert_eq!(BinaryField::parse_tform("1PE(100)"), Some((1, 'P')));
+    assert_eq!(BinaryField::field_width("3I"), Some(6));
+    assert_eq!(BinaryField::field_width("11X"), Some(2));
+    // Repeat counts that overflow are rejected instead of panicking
+    assert_eq!(BinaryField::parse_tform("99999999999999999999999E"), None);
+    assert_eq!(BinaryField::field_width("9999999999999999999E"), None);
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::{card, event_list_bytes, header_from_cards};
+use rustfits::data::data::Data;
+use rustfits::fits::FITS;
+use rustfits::time::{date_from_mjd, date_obs, leap_seconds, mjd_from_date, Time, TimeReference, TimeScale};
+
+#[test]
+fn test_calendar_and_scales() {
+    assert_eq!(mjd_from_date(1858, 11, 17), 0);
+    assert_eq!(mjd_from_date(2000, 1, 1), 51544);
+    assert_eq!(date_from_mjd(57754), (2017, 1, 1));
+    assert_eq!(date_from_mjd(-1), (1858, 11, 16));
+    assert_eq!(leap_seconds(57753), 36.0);
+    assert_eq!(leap_seconds(57754), 37.0);
+
+    let utc = Time::parse("2017-01-01T00:00:00", TimeScale::UTC).unwrap();
+    assert_eq!(utc.to_scale(TimeScale::TT).to_iso(3), "2017-01-01T00:01:09.184");
+    assert_eq!(utc.to_scale(TimeScale::GPS).to_iso(0), "2017-01-01T00:00:18");
+
+    // The leap second at the end of 2016
+    let leap = Time::parse("2016-12-31T23:59:60.5", TimeScale::UTC).unwrap();
+    assert_eq!(leap.to_scale(TimeScale::TAI).to_scale(TimeScale::UTC).to_iso(1), "2016-12-31T23:59:60.5");
+    let before = Time::parse("2016-12-31T23:59:59", TimeScale::UTC).unwrap();
+    assert_eq!(before.add_seconds(2.0).to_iso(0), "2017-01-01T00:00:00");
+
+    let tt = Time::from_mjd(51544.5, TimeScale::TT);
+    let seconds = |time: Time| {
+        let (day, seconds) = time.mjd_parts();
+        (day - 51544) as f64 * 86400.0 + seconds
+    };
+    assert!((seconds(tt.to_scale(TimeScale::TCG)) - seconds(tt) - 0.5058).abs() < 1e-3);
+    assert!((seconds(tt.to_scale(TimeScale::TCB)) - seconds(tt) - 11.25).abs() < 0.02);
+    assert!((seconds(tt.to_scale(TimeScale::TDB)) - seconds(tt)).abs() < 2e-3);
+    let scales = [TimeScale::TAI, TimeScale::TT, TimeScale::UTC, TimeScale::GPS, TimeScale::TDB, TimeScale::TCG, TimeScale::TCB];
+    for scale in scales.iter() {
+        let back = tt.to_scale(*scale).to_scale(TimeScale::TT);
+        assert!((seconds(back) - seconds(tt)).abs() < 1e-6, "{}", scale);
+    }
+}
+
+#[test]
+fn test_time_reference_and_date_obs() {
+    // Chandra: MJDREF is 1998-01-01T00:01:04.184 TT, one second after midnight UTC
+    let extra = vec![
+        card("TIMESYS", "'TT'"),
+        card("MJDREFI", "50814"),
+        card("MJDREFF", "7.428703703703703E-04"),
+        card("TIMEZERO", "1.0"),
+        card("TIMEUNIT", "'s'"),
+    ];
+    let fits = FITS::new_from_buffer(&event_list_bytes(&["TIME"], &extra, &[vec![-1.0], vec![86399.0]]));
+    let table = match &fits.hdus[1].data {
+        Data::BinaryTable(table) => table,
+        _ => panic!("Expected a binary table"),
+    };
+    let times = table.column_times(&fits.hdus[1].header, 0, TimeScale::UTC).unwrap();
+    assert_eq!(times[0].to_iso(3), "1998-01-01T00:00:01.000");
+    assert_eq!(times[1].to_iso(3), "1998-01-02T00:00:01.000");
+
+    let reference = TimeReference::from_header(&header_from_cards(&[
+        &card("SIMPLE", "T"),
+        &card("JDREF", "2451545.0"),
+        &card("TIMEUNIT", "'d'"),
+    ]))
+    .unwrap();
+    assert_eq!(reference.reference.scale, TimeScale::UTC);
+    assert!((reference.time(1.5).mjd() - 51546.0).abs() < 1e-9);
+
+    let header = header_from_cards(&[&card("SIMPLE", "T"), &card("DATE-OBS", "'2020-03-04T05:06:07.5'")]);
+    assert_eq!(date_obs(&header).unwrap().unwrap().to_iso(1), "2020-03-04T05:06:07.5");
+    let header = header_from_cards(&[
+        &card("SIMPLE", "T"),
+        &card("TIMESYS", "'TT'"),
+        &card("DATE-OBS", "'04/03/95'"),
+        &card("TIME-OBS", "'12:30:00'"),
+    ]);
+    let time = date_obs(&header).unwrap().unwrap();
+    assert_eq!(time.scale, TimeScale::TT);
+    assert_eq!(time.to_iso(0), "1995-03-04T12:30:00");
+    assert!(Time::parse("2020-13-01", TimeScale::UTC).is_err());
+}
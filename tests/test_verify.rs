@@ -0,0 +1,206 @@
+mod common;
+
+use common::card;
+use rustfits::verify::{verify, Issue, Severity};
+
+fn primary() -> Vec<u8> {
+    common::header_bytes(&[&card("SIMPLE", "T"), &card("BITPIX", "8"), &card("NAXIS", "0"), &card("EXTEND", "T")])
+}
+
+fn errors(issues: &[Issue]) -> Vec<String> {
+    issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .map(|issue| issue.to_string())
+        .collect()
+}
+
+#[test]
+fn test_conforming_file() {
+    let mut bytes = primary();
+    bytes.extend(common::event_list_bytes(&["X", "Y"], &[], &[vec![1.0, 2.0]])[2880..].to_vec());
+    bytes.extend(common::header_bytes(&[
+        "XTENSION= 'TABLE   '",
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "12"),
+        &card("NAXIS2", "1"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+        &card("TFIELDS", "2"),
+        &card("TFORM1", "'I4      '"),
+        &card("TBCOL1", "1"),
+        &card("TFORM2", "'F8.3    '"),
+        &card("TBCOL2", "5"),
+        "OBJECT  = 'O''Brien'          / quoted / slashed",
+        "COMMENT   free text, with 'quotes and / slashes",
+    ]));
+    let mut row = b"   1   2.500".to_vec();
+    row.resize(2880, b' ');
+    bytes.extend(row);
+    assert_eq!(verify(&bytes), vec![]);
+
+    // Extensions without EXTEND = T in the primary header only warrant a warning
+    let bytes = common::event_list_bytes(&["X"], &[], &[vec![1.0]]);
+    let issues = verify(&bytes);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].severity, Severity::Warning);
+    assert_eq!(issues[0].hdu, Some(0));
+}
+
+#[test]
+fn test_header_errors() {
+    let mut bytes = common::header_bytes(&[
+        &card("SIMPLE", "T"),
+        &card("NAXIS", "0"),
+        &card("BITPIX", "8"),
+        "EXTEND  = T",
+        "date-obs= '2020-01-01'",
+        "OBJECT  = 'unterminated",
+        "EXPTIME =              1.5e3",
+        &card("EXPTIME", "2"),
+    ]);
+    // Tab in the comment of card 2
+    bytes[80 + 40] = b'\t';
+    let issues = verify(&bytes);
+    assert_eq!(
+        errors(&issues),
+        vec![
+            "HDU 0, card 2: error: illegal character 0x09 in column 41",
+            "HDU 0, card 5: error: illegal character 'd' in keyword date-obs",
+            "HDU 0, card 6: error: OBJECT: string value has no closing quote",
+            "HDU 0, card 7: error: EXPTIME: invalid value 1.5e3",
+            "HDU 0, card 3: error: BITPIX must be card 2",
+            "HDU 0, card 2: error: NAXIS must be card 3",
+        ]
+    );
+    let duplicate = issues.iter().find(|issue| issue.severity == Severity::Warning).unwrap();
+    assert_eq!(duplicate.to_string(), "HDU 0, card 8: warning: EXPTIME repeats the keyword of card 7");
+
+    // Mandatory keywords must be in fixed format, the END card and fill must be blank
+    let mut bytes = common::header_bytes(&["SIMPLE  = T", &card("BITPIX", "16"), &card("NAXIS", "1"), &card("NAXIS1", "4")]);
+    bytes[4 * 80 + 20] = b'X';
+    bytes[5 * 80] = b'?';
+    bytes.extend(common::data_bytes(&[0, 1, 0, 2, 0, 3, 0, 4, 9]));
+    let issues = verify(&bytes);
+    assert_eq!(
+        errors(&issues),
+        vec![
+            "HDU 0, card 5: error: END card must be blank in columns 9-80",
+            "HDU 0: error: header fill after the END card must be ASCII spaces",
+            "HDU 0, card 1: error: SIMPLE is not in fixed format",
+            "HDU 0: error: data fill after the last data byte must be zeros",
+        ]
+    );
+}
+
+#[test]
+fn test_extension_errors() {
+    let mut bytes = primary();
+    bytes.extend(common::header_bytes(&[
+        "XTENSION= 'BINTABLE'",
+        &card("BITPIX", "16"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "12"),
+        &card("NAXIS2", "0"),
+        &card("GCOUNT", "1"),
+        &card("PCOUNT", "0"),
+        &card("TFIELDS", "2"),
+        &card("TFORM1", "'E'"),
+        &card("TFORM2", "'3I'"),
+        &card("TTYPE3", "'EXTRA'"),
+    ]));
+    bytes.extend(common::header_bytes(&[
+        "XTENSION= 'TABLE   '",
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "8"),
+        &card("NAXIS2", "0"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+        &card("TFIELDS", "1"),
+        &card("TFORM1", "'E10.4'"),
+        &card("TBCOL1", "2"),
+    ]));
+    bytes.extend(common::header_bytes(&["XTENSION= 'IMAGE   '", &card("BITPIX", "8"), &card("NAXIS", "1")]));
+    let issues = verify(&bytes);
+    assert_eq!(
+        errors(&issues),
+        vec![
+            "HDU 1, card 7: error: PCOUNT must be card 6",
+            "HDU 1, card 6: error: GCOUNT must be card 7",
+            "HDU 1, card 2: error: BITPIX must be 8 in a BINTABLE extension",
+            "HDU 1, card 4: error: NAXIS1 = 12 but the TFORMn fields add up to 10 bytes",
+            "HDU 2, card 10: error: column 1 (10 characters from TBCOL1 = 2) does not fit in NAXIS1 = 8",
+            "HDU 3: error: mandatory keyword NAXIS1 is missing",
+            "HDU 3: error: mandatory keyword PCOUNT is missing",
+            "HDU 3: error: mandatory keyword GCOUNT is missing",
+        ]
+    );
+    let warning = issues.iter().find(|issue| issue.severity == Severity::Warning).unwrap();
+    assert_eq!(warning.to_string(), "HDU 1, card 11: warning: TTYPE3 refers to column 3 but TFIELDS = 2");
+}
+
+#[test]
+fn test_malformed_input_does_not_panic() {
+    // A non-ASCII byte inside the value field of a mandatory card
+    let mut bytes = primary();
+    bytes[80 + 28] = 0xC3;
+    assert_eq!(
+        errors(&verify(&bytes)),
+        vec![
+            "HDU 0, card 2: error: illegal character 0xC3 in column 29",
+            "HDU 0, card 2: error: BITPIX must be an integer",
+        ]
+    );
+
+    let mut bytes = common::header_bytes(&[
+        &card("SIMPLE", "T"),
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "9223372036854775807"),
+        &card("NAXIS2", "4"),
+    ]);
+    bytes.extend(vec![0u8; 2880]);
+    assert_eq!(
+        errors(&verify(&bytes)),
+        vec!["HDU 0: error: data size given by BITPIX, NAXISn, PCOUNT and GCOUNT is too large"]
+    );
+
+    // A non-ASCII TFORM code in an ASCII table, and a binary table repeat count that
+    // overflows
+    let mut bytes = primary();
+    bytes.extend(common::header_bytes(&[
+        "XTENSION= 'TABLE   '",
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "4"),
+        &card("NAXIS2", "0"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+        &card("TFIELDS", "1"),
+        "TFORM1  = 'I4      '",
+        &card("TBCOL1", "1"),
+    ]));
+    bytes[2880 + 8 * 80 + 11] = 0xE9;
+    bytes.extend(common::header_bytes(&[
+        "XTENSION= 'BINTABLE'",
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "4"),
+        &card("NAXIS2", "0"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+        &card("TFIELDS", "2"),
+        &card("TFORM1", "'99999999999999999999999E'"),
+        &card("TFORM2", "'J'"),
+    ]));
+    assert_eq!(
+        errors(&verify(&bytes)),
+        vec![
+            "HDU 1, card 9: error: illegal character 0xE9 in column 12",
+            "HDU 1, card 9: error: TFORM1 = '\u{FFFD}4' is not a valid ASCII table format",
+            "HDU 2, card 9: error: TFORM1 = '99999999999999999999999E' makes the row too wide",
+        ]
+    );
+}
@@ -0,0 +1,565 @@
+mod common;
+
+use common::{card, event_list_bytes, header_from_cards};
+use ndarray::array;
+use rustfits::wcs::WCS;
+
+fn celestial_header(projection: &str, crval: (f64, f64), extra: &[&str]) -> rustfits::header::Header {
+    let mut cards = vec![
+        card("SIMPLE", "T"),
+        card("BITPIX", "-32"),
+        card("NAXIS", "2"),
+        card("NAXIS1", "100"),
+        card("NAXIS2", "100"),
+        card("CTYPE1", &format!("'RA---{}'", projection)),
+        card("CTYPE2", &format!("'DEC--{}'", projection)),
+        card("CRPIX1", "50.0"),
+        card("CRPIX2", "50.0"),
+        card("CRVAL1", &crval.0.to_string()),
+        card("CRVAL2", &crval.1.to_string()),
+        card("CDELT1", "-1.0"),
+        card("CDELT2", "1.0"),
+    ];
+    cards.extend(extra.iter().map(|card| card.to_string()));
+    header_from_cards(&cards.iter().map(|card| card.as_str()).collect::<Vec<&str>>())
+}
+
+fn assert_close(a: f64, b: f64, tolerance: f64) {
+    assert!((a - b).abs() < tolerance, "{} != {}", a, b);
+}
+
+#[test]
+fn test_analytic_values() {
+    let wcs = WCS::from_header(&celestial_header("TAN", (0.0, 0.0), &[])).unwrap();
+    let world = wcs.pix2world_point(&[51.0, 50.0]).unwrap();
+    assert_close(world[0], 360.0 - (std::f64::consts::PI / 180.0).atan().to_degrees(), 1e-10);
+    assert_close(world[1], 0.0, 1e-10);
+
+    let wcs = WCS::from_header(&celestial_header("ARC", (0.0, 90.0), &[])).unwrap();
+    let world = wcs.pix2world_point(&[50.0, 40.0]).unwrap();
+    assert_close(world[1], 80.0, 1e-10);
+
+    let wcs = WCS::from_header(&celestial_header("CAR", (0.0, 0.0), &[])).unwrap();
+    let world = wcs.pix2world_point(&[40.0, 70.0]).unwrap();
+    assert_close(world[0], 10.0, 1e-10);
+    assert_close(world[1], 20.0, 1e-10);
+
+    let wcs = WCS::from_header(&celestial_header("HPX", (0.0, 0.0), &[])).unwrap();
+    assert_close(wcs.pix2world_point(&[50.0, 95.0]).unwrap()[1], (2.0f64 / 3.0).asin().to_degrees(), 1e-10);
+
+    let wcs = WCS::from_header(&celestial_header(
+        "TAN",
+        (0.0, 0.0),
+        &[&card("CUNIT1", "'arcsec  '"), &card("CUNIT2", "'arcsec  '"), &card("CDELT1", "-3600.0"), &card("CDELT2", "3600.0")],
+    ))
+    .unwrap();
+    assert_close(wcs.pix2world_point(&[51.0, 50.0]).unwrap()[0], 360.0 - (std::f64::consts::PI / 180.0).atan().to_degrees(), 1e-10);
+
+    // CRVAL is in CUNIT too
+    let wcs = WCS::from_header(&celestial_header(
+        "TAN",
+        (36000.0, 7200.0),
+        &[&card("CUNIT1", "'arcsec  '"), &card("CUNIT2", "'arcsec  '"), &card("CDELT1", "-3600.0"), &card("CDELT2", "3600.0")],
+    ))
+    .unwrap();
+    let world = wcs.pix2world_point(&[50.0, 50.0]).unwrap();
+    assert_close(world[0], 10.0, 1e-10);
+    assert_close(world[1], 2.0, 1e-10);
+    let pixel = wcs.world2pix_point(&[10.0, 2.0]).unwrap();
+    assert_close(pixel[0], 50.0, 1e-10);
+    assert_close(pixel[1], 50.0, 1e-10);
+}
+
+#[test]
+fn test_crota_on_latitude_axis() {
+    // Latitude first: the rotation is CROTA1, and CROTA2 is ignored
+    let header = |crota: &str| {
+        header_from_cards(&[
+            &card("SIMPLE", "T"),
+            &card("NAXIS", "2"),
+            &card("CTYPE1", "'DEC--TAN'"),
+            &card("CTYPE2", "'RA---TAN'"),
+            &card("CRPIX1", "50.0"),
+            &card("CRPIX2", "50.0"),
+            &card("CDELT1", "1.0"),
+            &card("CDELT2", "-1.0"),
+            &card(crota, "90.0"),
+        ])
+    };
+    let step = (std::f64::consts::PI / 180.0).atan().to_degrees();
+    let world = WCS::from_header(&header("CROTA1")).unwrap().pix2world_point(&[51.0, 50.0]).unwrap();
+    assert_close(world[0], 0.0, 1e-10);
+    assert_close(world[1], 360.0 - step, 1e-10);
+    let world = WCS::from_header(&header("CROTA2")).unwrap().pix2world_point(&[51.0, 50.0]).unwrap();
+    assert_close(world[0], step, 1e-10);
+    assert_close(world[1] % 360.0, 0.0, 1e-10);
+}
+
+#[test]
+fn test_projection_round_trips() {
+    for projection in ["TAN", "SIN", "ARC", "STG", "ZEA", "CAR", "MER", "AIT", "MOL", "HPX"].iter() {
+        for crval in [(150.0, 30.0), (10.0, -60.0), (0.0, 0.0)].iter() {
+            let wcs = WCS::from_header(&celestial_header(projection, *crval, &[&card("CDELT1", "-0.2"), &card("CDELT2", "0.2")])).unwrap();
+            let reference = wcs.pix2world_point(&[50.0, 50.0]).unwrap();
+            assert_close(reference[0], crval.0, 1e-9);
+            assert_close(reference[1], crval.1, 1e-9);
+            for pixel in [[1.0, 1.0], [30.0, 77.0], [99.5, 12.25]].iter() {
+                let world = wcs.pix2world_point(pixel).unwrap();
+                let back = wcs.world2pix_point(&world).unwrap();
+                assert_close(back[0], pixel[0], 1e-7);
+                assert_close(back[1], pixel[1], 1e-7);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_reference_point_at_the_pole() {
+    for (projection, crval) in [("CAR", (30.0, 90.0)), ("CAR", (200.0, -90.0)), ("MER", (0.0, 90.0))].iter() {
+        let wcs = WCS::from_header(&celestial_header(projection, *crval, &[])).unwrap();
+        let reference = wcs.pix2world_point(&[50.0, 50.0]).unwrap();
+        assert!(reference[0].is_finite());
+        assert_close(reference[1], crval.1, 1e-9);
+        // The native pole lies on the celestial meridian of CRVAL1, towards increasing pixel y
+        let above = wcs.pix2world_point(&[50.0, 55.0]).unwrap();
+        assert_close(above[0], crval.0, 1e-9);
+        for pixel in [[50.0, 45.0], [50.0, 55.0], [43.0, 50.0], [30.0, 77.0]].iter() {
+            let world = wcs.pix2world_point(pixel).unwrap();
+            assert!(world[1].abs() < 90.0);
+            let back = wcs.world2pix_point(&world).unwrap();
+            assert_close(back[0], pixel[0], 1e-7);
+            assert_close(back[1], pixel[1], 1e-7);
+        }
+    }
+}
+
+#[test]
+fn test_arrays_and_invalid_points() {
+    let wcs = WCS::from_header(&celestial_header("SIN", (0.0, 0.0), &[&card("CDELT1", "-1.0"), &card("CDELT2", "1.0")])).unwrap();
+    let world = wcs.pix2world(array![[50.0, 50.0], [50.0, 200.0]].view());
+    assert_close(world[[0, 1]], 0.0, 1e-10);
+    assert!(world[[1, 0]].is_nan() && world[[1, 1]].is_nan());
+    let pixels = wcs.world2pix(world.slice(ndarray::s![0..1, ..]));
+    assert_close(pixels[[0, 0]], 50.0, 1e-10);
+
+    // Rows shorter than NAXIS give NaN instead of reading past the end
+    let world = wcs.pix2world(array![[50.0], [51.0]].view());
+    assert_eq!(world.dim(), (2, 2));
+    assert!(world.iter().all(|value| value.is_nan()));
+    assert!(wcs.world2pix_point(&[0.0]).is_none());
+
+    // Only the known celestial prefixes pair up, a VOLT axis is not a latitude
+    let header = header_from_cards(&[&card("SIMPLE", "T"), &card("NAXIS", "2"), &card("CTYPE1", "'VOLN'"), &card("CTYPE2", "'VOLT'")]);
+    assert!(WCS::from_header(&header).unwrap().celestial().is_none());
+
+    let header = header_from_cards(&[&card("SIMPLE", "T"), &card("NAXIS", "2"), &card("CTYPE1", "'RA---XYZ'"), &card("CTYPE2", "'DEC--XYZ'")]);
+    assert!(WCS::from_header(&header).is_err());
+}
+
+/// ACS/WFC style SIP header of tests/data/wcs_reference.py.
+fn sip_header() -> rustfits::header::Header {
+    header_from_cards(&[
+        &card("SIMPLE", "T"),
+        &card("NAXIS", "2"),
+        &card("CTYPE1", "'RA---TAN-SIP'"),
+        &card("CTYPE2", "'DEC--TAN-SIP'"),
+        &card("CRPIX1", "2048.0"),
+        &card("CRPIX2", "1024.0"),
+        &card("CRVAL1", "150.1163213"),
+        &card("CRVAL2", "2.200973097"),
+        &card("CD1_1", "8.1303E-06"),
+        &card("CD1_2", "1.1418E-05"),
+        &card("CD2_1", "1.148E-05"),
+        &card("CD2_2", "-8.3444E-06"),
+        &card("A_ORDER", "4"),
+        &card("B_ORDER", "4"),
+        &card("A_0_2", "2.16615E-06"),
+        &card("A_1_1", "-5.28229E-06"),
+        &card("A_2_0", "8.55893E-06"),
+        &card("A_0_3", "-5.86727E-11"),
+        &card("A_1_2", "-4.69853E-10"),
+        &card("A_2_1", "-2.81216E-11"),
+        &card("A_3_0", "-4.69377E-10"),
+        &card("A_0_4", "2.64016E-14"),
+        &card("A_1_3", "-1.63838E-14"),
+        &card("A_2_2", "3.36418E-14"),
+        &card("A_3_1", "7.91436E-15"),
+        &card("A_4_0", "2.58745E-14"),
+        &card("B_0_2", "-7.23588E-06"),
+        &card("B_1_1", "6.18459E-06"),
+        &card("B_2_0", "-1.74222E-06"),
+        &card("B_0_3", "9.50461E-12"),
+        &card("B_1_2", "-5.05553E-11"),
+        &card("B_2_1", "-4.36003E-10"),
+        &card("B_3_0", "9.94049E-11"),
+        &card("B_0_4", "-2.73101E-14"),
+        &card("B_1_3", "2.15939E-14"),
+        &card("B_2_2", "-5.90447E-15"),
+        &card("B_3_1", "3.10366E-14"),
+        &card("B_4_0", "1.34227E-15"),
+    ])
+}
+
+/// DECam style TPV header of tests/data/wcs_reference.py.
+fn tpv_header() -> rustfits::header::Header {
+    header_from_cards(&[
+        &card("SIMPLE", "T"),
+        &card("NAXIS", "2"),
+        &card("CTYPE1", "'RA---TPV'"),
+        &card("CTYPE2", "'DEC--TPV'"),
+        &card("CRPIX1", "-9120.8"),
+        &card("CRPIX2", "4177.667"),
+        &card("CRVAL1", "36.3765"),
+        &card("CRVAL2", "-4.4535"),
+        &card("CD1_1", "-1.4939E-07"),
+        &card("CD1_2", "7.2861E-05"),
+        &card("CD2_1", "-7.2854E-05"),
+        &card("CD2_2", "-1.2838E-07"),
+        &card("PV1_0", "0.00330386"),
+        &card("PV1_1", "1.010275"),
+        &card("PV1_2", "-0.01023213"),
+        &card("PV1_4", "-0.01061528"),
+        &card("PV1_5", "0.009189862"),
+        &card("PV1_6", "-0.004131189"),
+        &card("PV1_7", "-0.007853096"),
+        &card("PV1_8", "0.005980253"),
+        &card("PV1_9", "-0.005035698"),
+        &card("PV1_10", "0.002418946"),
+        &card("PV2_0", "-0.00634268"),
+        &card("PV2_1", "1.016405"),
+        &card("PV2_2", "-0.01460743"),
+        &card("PV2_4", "-0.02119478"),
+        &card("PV2_5", "0.01450355"),
+        &card("PV2_6", "-0.003955219"),
+        &card("PV2_7", "0.01229432"),
+        &card("PV2_8", "-0.005519004"),
+        &card("PV2_9", "0.007052133"),
+        &card("PV2_10", "-0.002092016"),
+    ])
+}
+
+/// Check pixel/world pairs printed by tests/data/wcs_reference.py, and the inverse.
+///
+/// The pairs in the tests below were printed by the plain-Python path of the script, as
+/// astropy was not available where they were generated. That path shares the SIP and TPV
+/// polynomial formulas with the Rust code, so they still need to be replaced by the output
+/// of `python3 tests/data/wcs_reference.py --astropy`.
+fn assert_pairs(wcs: &WCS, pairs: &[([f64; 2], [f64; 2])]) {
+    for (pixel, expected) in pairs.iter() {
+        let world = wcs.pix2world_point(pixel).unwrap();
+        assert_close(world[0], expected[0], 1e-12);
+        assert_close(world[1], expected[1], 1e-12);
+        let back = wcs.world2pix_point(expected).unwrap();
+        assert_close(back[0], pixel[0], 1e-8);
+        assert_close(back[1], pixel[1], 1e-8);
+    }
+}
+
+#[test]
+fn test_sip_distortion() {
+    let pairs = [
+        ([1.0, 1.0], [150.08823947296966, 2.1863921924492016]),
+        ([4096.0, 2048.0], [150.14483727406036, 2.216220858858007]),
+        ([100.0, 1900.0], [150.11058276348805, 2.172056513809648]),
+        ([3000.5, 500.25], [150.11809909852508, 2.2164522602467662]),
+    ];
+    assert_pairs(&WCS::from_header(&sip_header()).unwrap(), &pairs);
+
+    // Approximate inverse coefficients are only a starting point for the iteration
+    let mut header = sip_header();
+    header.set_card(&card("AP_ORDER", "2"));
+    header.set_card(&card("AP_2_0", "-8.55893E-06"));
+    header.set_card(&card("BP_ORDER", "2"));
+    header.set_card(&card("BP_0_2", "7.23588E-06"));
+    assert_pairs(&WCS::from_header(&header).unwrap(), &pairs);
+}
+
+#[test]
+fn test_tpv_distortion() {
+    let pairs = [
+        ([1.0, 1.0], [36.07546783548304, -5.140195331122404]),
+        ([2048.0, 4096.0], [36.3764754970369, -5.307236496105246]),
+        ([1024.5, 2048.5], [36.22642080262029, -5.223058927327926]),
+        ([500.0, 3500.0], [36.333019587653176, -5.185393342737853]),
+    ];
+    assert_pairs(&WCS::from_header(&tpv_header()).unwrap(), &pairs);
+}
+
+fn spectral_header(ctype: &str, crval: &str, cdelt: &str, cunit: &str, extra: &[&str]) -> rustfits::header::Header {
+    let mut cards = vec![
+        card("SIMPLE", "T"),
+        card("BITPIX", "-32"),
+        card("NAXIS", "1"),
+        card("NAXIS1", "100"),
+        card("CTYPE1", &format!("'{}'", ctype)),
+        card("CRPIX1", "1.0"),
+        card("CRVAL1", crval),
+        card("CDELT1", cdelt),
+    ];
+    if !cunit.is_empty() {
+        cards.push(card("CUNIT1", &format!("'{}'", cunit)));
+    }
+    cards.extend(extra.iter().map(|card| card.to_string()));
+    header_from_cards(&cards.iter().map(|card| card.as_str()).collect::<Vec<&str>>())
+}
+
+#[test]
+fn test_spectral_axes() {
+    use rustfits::wcs::spectral::{SpectralType, C};
+
+    let wcs = WCS::from_header(&spectral_header("WAVE", "500.0", "0.5", "nm", &[])).unwrap();
+    let wavelength = wcs.pix2world_point(&[11.0]).unwrap()[0];
+    assert_close(wavelength, 505.0, 1e-10);
+    let spectral = wcs.spectral().unwrap();
+    assert_close(spectral.convert(wavelength, SpectralType::FREQ, "GHz").unwrap(), C / 505e-9 / 1e9, 1e-6);
+
+    let wcs = WCS::from_header(&spectral_header("WAVE-F2W", "500.0", "0.5", "nm", &[])).unwrap();
+    assert_close(wcs.pix2world_point(&[11.0]).unwrap()[0], 500.0 / 0.99, 1e-6);
+    assert_close(wcs.world2pix_point(&[500.0 / 0.99]).unwrap()[0], 11.0, 1e-6);
+
+    let wcs = WCS::from_header(&spectral_header("WAVE-LOG", "500.0", "1.0", "nm", &[])).unwrap();
+    assert_close(wcs.pix2world_point(&[11.0]).unwrap()[0], 500.0 * 0.02f64.exp(), 1e-10);
+    assert_close(wcs.world2pix_point(&[500.0 * 0.02f64.exp()]).unwrap()[0], 11.0, 1e-10);
+
+    let wcs = WCS::from_header(&spectral_header("VRAD", "0.0", "1000.0", "m/s", &[&card("RESTFRQ", "1.0E9")])).unwrap();
+    let velocity = wcs.pix2world_point(&[2.0]).unwrap()[0];
+    assert_close(wcs.spectral().unwrap().convert(velocity, SpectralType::FREQ, "Hz").unwrap(), 1e9 * (1.0 - 1000.0 / C), 1e-3);
+
+    // Frequency axis sampled linearly in apparent radial velocity
+    let rest = card("RESTFRQ", "1.420405752E9");
+    let wcs = WCS::from_header(&spectral_header("FREQ-V2F", "1.42E9", "1.0E5", "Hz", &[&rest])).unwrap();
+    let spectral = wcs.spectral().unwrap();
+    let velocities: Vec<f64> = [1.0, 2.0, 3.0]
+        .iter()
+        .map(|pixel| spectral.convert(wcs.pix2world_point(&[*pixel]).unwrap()[0], SpectralType::VELO, "m/s").unwrap())
+        .collect();
+    assert_close(velocities[2] - velocities[1], velocities[1] - velocities[0], 1e-6);
+    assert_close(wcs.world2pix_point(&wcs.pix2world_point(&[42.5]).unwrap()).unwrap()[0], 42.5, 1e-6);
+
+    assert!(WCS::from_header(&spectral_header("WAVE-TAB", "0.0", "10.0", "nm", &[])).is_err());
+
+    // AIPS velocity frame suffixes are linear axes
+    for ctype in ["VELO-LSR", "FELO-HEL"].iter() {
+        let wcs = WCS::from_header(&spectral_header(ctype, "1000.0", "500.0", "m/s", &[])).unwrap();
+        assert_close(wcs.pix2world_point(&[3.0]).unwrap()[0], 2000.0, 1e-10);
+    }
+    assert!(WCS::from_header(&spectral_header("WAVE-W2F", "500.0", "0.5", "nm", &[])).is_err());
+}
+
+#[test]
+fn test_spectral_lookup_table() {
+    use common::{data_bytes, header_bytes};
+    use rustfits::fits::FITS;
+
+    let extra = [
+        card("EXTEND", "T"),
+        card("PS1_0", "'WCS-TAB '"),
+        card("PS1_1", "'COORDS  '"),
+        card("PS1_2", "'INDEX   '"),
+    ];
+    let mut buffer = spectral_header("WAVE-TAB", "0.0", "10.0", "nm", &extra.iter().map(|card| card.as_str()).collect::<Vec<&str>>())
+        .get_fitsblocks()
+        .as_flattened()
+        .to_vec();
+    buffer.extend(data_bytes(&vec![0u8; 400]));
+    buffer.extend(header_bytes(&[
+        &card("XTENSION", "'BINTABLE'"),
+        &card("BITPIX", "8"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "64"),
+        &card("NAXIS2", "1"),
+        &card("PCOUNT", "0"),
+        &card("GCOUNT", "1"),
+        &card("TFIELDS", "2"),
+        &card("TTYPE1", "'COORDS  '"),
+        &card("TFORM1", "'4D      '"),
+        &card("TTYPE2", "'INDEX   '"),
+        &card("TFORM2", "'4D      '"),
+        &card("EXTNAME", "'WCS-TAB '"),
+    ]));
+    let mut row: Vec<u8> = Vec::new();
+    for value in [400.0f64, 410.0, 430.0, 460.0, 0.0, 10.0, 20.0, 30.0].iter() {
+        row.extend_from_slice(&value.to_be_bytes());
+    }
+    buffer.extend(data_bytes(&row));
+
+    let fits = FITS::new_from_buffer(&buffer);
+    let wcs = WCS::from_fits(&fits, 0).unwrap();
+    assert_close(wcs.pix2world_point(&[2.5]).unwrap()[0], 420.0, 1e-10);
+    assert_close(wcs.pix2world_point(&[4.0]).unwrap()[0], 460.0, 1e-10);
+    assert_close(wcs.world2pix_point(&[445.0]).unwrap()[0], 3.5, 1e-10);
+}
+
+#[test]
+fn test_frame_conversions() {
+    use rustfits::wcs::frames::Frame;
+
+    // Galactic centre in ICRS
+    let (ra, dec) = Frame::Galactic.convert(0.0, 0.0, Frame::ICRS);
+    assert_close(ra, 266.404_988, 1e-5);
+    assert_close(dec, -28.936_178, 1e-5);
+    let (_, b) = Frame::FK5 { equinox: 2000.0 }.convert(192.859_48, 27.128_25, Frame::Galactic);
+    assert_close(b, 90.0, 1e-5);
+
+    // The galactic pole was defined at (192.25, 27.4) in FK4 B1950
+    let (_, b) = Frame::FK4 { equinox: 1950.0 }.convert(192.25, 27.4, Frame::Galactic);
+    assert_close(b, 90.0, 1e-3);
+
+    // North ecliptic pole
+    let (_, beta) = Frame::FK5 { equinox: 2000.0 }.convert(270.0, 66.560_708_9, Frame::Ecliptic { equinox: 2000.0 });
+    assert_close(beta, 90.0, 1e-5);
+
+    let frames = [
+        Frame::ICRS,
+        Frame::FK5 { equinox: 2050.0 },
+        Frame::FK4 { equinox: 1900.0 },
+        Frame::FK4NoETerms { equinox: 1950.0 },
+        Frame::Galactic,
+        Frame::Ecliptic { equinox: 2000.0 },
+    ];
+    for from in frames.iter() {
+        for to in frames.iter() {
+            let (lon, lat) = from.convert(123.4, -56.7, *to);
+            let (lon, lat) = to.convert(lon, lat, *from);
+            assert_close(lon, 123.4, 1e-8);
+            assert_close(lat, -56.7, 1e-8);
+        }
+    }
+}
+
+#[test]
+fn test_frame_from_header() {
+    use rustfits::wcs::frames::Frame;
+
+    let wcs = WCS::from_header(&celestial_header("TAN", (10.0, 20.0), &[&card("EQUINOX", "1950.0")])).unwrap();
+    assert_eq!(wcs.celestial().unwrap().frame, Some(Frame::FK4 { equinox: 1950.0 }));
+    let wcs = WCS::from_header(&celestial_header("TAN", (10.0, 20.0), &[&card("RADESYS", "'ICRS'")])).unwrap();
+    assert_eq!(wcs.celestial().unwrap().frame, Some(Frame::ICRS));
+
+    let wcs = WCS::from_header(&celestial_header("TAN", (266.404_988, -28.936_178), &[])).unwrap();
+    let galactic = wcs.pix2world_frame(&[50.0, 50.0], Frame::Galactic).unwrap();
+    assert_close(galactic[0].min(360.0 - galactic[0]), 0.0, 1e-5);
+    assert_close(galactic[1], 0.0, 1e-5);
+    let pixel = wcs.world2pix_frame(&galactic, Frame::Galactic).unwrap();
+    assert_close(pixel[0], 50.0, 1e-6);
+    assert_close(pixel[1], 50.0, 1e-6);
+
+    // Frames that cannot be converted still give world coordinates in their own frame
+    let wcs = WCS::from_header(&celestial_header("TAN", (10.0, 20.0), &[&card("RADESYS", "'GAPPT'")])).unwrap();
+    assert_eq!(wcs.celestial().unwrap().frame, None);
+    let world = wcs.pix2world_point(&[50.0, 50.0]).unwrap();
+    assert_close(world[0], 10.0, 1e-9);
+    assert_close(world[1], 20.0, 1e-9);
+    assert_eq!(wcs.pix2world_frame(&[50.0, 50.0], Frame::ICRS), None);
+    assert_eq!(wcs.world2pix_frame(&[10.0, 20.0], Frame::ICRS), None);
+    let helioprojective = header_from_cards(&[
+        &card("SIMPLE", "T"),
+        &card("BITPIX", "-32"),
+        &card("NAXIS", "2"),
+        &card("NAXIS1", "100"),
+        &card("NAXIS2", "100"),
+        &card("CTYPE1", "'HPLN-TAN'"),
+        &card("CTYPE2", "'HPLT-TAN'"),
+        &card("CRPIX1", "50.0"),
+        &card("CRPIX2", "50.0"),
+        &card("CDELT1", "0.001"),
+        &card("CDELT2", "0.001"),
+    ]);
+    let wcs = WCS::from_header(&helioprojective).unwrap();
+    assert_eq!(wcs.celestial().unwrap().frame, None);
+    assert_eq!(wcs.pix2world_frame(&[50.0, 50.0], Frame::ICRS), None);
+}
+
+#[test]
+fn test_alternate_descriptions() {
+    let header = celestial_header(
+        "TAN",
+        (10.0, 20.0),
+        &[
+            &card("WCSNAME", "'Sky'"),
+            &card("WCSNAMEA", "'Detector coords'"),
+            &card("CTYPE1A", "'DETX'"),
+            &card("CTYPE2A", "'DETY'"),
+            &card("CRPIX1A", "1.0"),
+            &card("CRPIX2A", "1.0"),
+            &card("CRVAL1A", "100.0"),
+            &card("CRVAL2A", "200.0"),
+            &card("CD1_1A", "0.5"),
+            &card("CD2_2A", "0.25"),
+            &card("CUNIT1B", "'mm'"),
+        ],
+    );
+    assert_eq!(WCS::alternates(&header), vec![' ', 'A', 'B']);
+
+    let sky = WCS::from_header(&header).unwrap();
+    assert_eq!(sky.name.as_deref(), Some("Sky"));
+    assert!(sky.celestial().is_some());
+
+    let detector = WCS::from_header_alt(&header, 'A').unwrap();
+    assert_eq!(detector.alt, "A");
+    assert!(detector.celestial().is_none());
+    let world = detector.pix2world_point(&[3.0, 5.0]).unwrap();
+    assert_close(world[0], 101.0, 1e-12);
+    assert_close(world[1], 201.0, 1e-12);
+
+    let named = WCS::from_header_name(&header, "detector coords").unwrap();
+    assert_eq!(named, detector);
+    let error = WCS::from_header_name(&header, "Focal plane").unwrap_err();
+    assert!(error.contains("Sky"), "{}", error);
+}
+
+#[test]
+fn test_table_wcs() {
+    use rustfits::fits::FITS;
+
+    let extra = vec![
+        card("TCTYP2", "'RA---TAN'"),
+        card("TCRPX2", "4096.5"),
+        card("TCRVL2", "83.6"),
+        card("TCDLT2", "-0.001"),
+        card("TCUNI2", "'deg'"),
+        card("TCTYP3", "'DEC--TAN'"),
+        card("TCRPX3", "4096.5"),
+        card("TCRVL3", "22.0"),
+        card("TCDLT3", "0.001"),
+        card("TCUNI3", "'deg'"),
+        card("TP2_3", "0.0"),
+        card("RADE2", "'FK5'"),
+        card("TCTY2A", "'DETX'"),
+        card("TCRP2A", "0.0"),
+        card("TCRV2A", "0.0"),
+        card("TCDE2A", "0.025"),
+        card("TCTY3A", "'DETY'"),
+        card("TCDE3A", "0.025"),
+    ];
+    let buffer = event_list_bytes(&["TIME", "X", "Y"], &extra, &[vec![0.0, 4096.5, 4096.5], vec![1.0, 4000.0, 4200.0]]);
+    let header = &FITS::new_from_buffer(&buffer).hdus[1].header;
+
+    let sky = WCS::from_columns(header, &["X", "Y"], ' ').unwrap();
+    assert_eq!(sky.ctype, vec!["RA---TAN".to_string(), "DEC--TAN".to_string()]);
+    assert_eq!(sky.celestial().unwrap().frame, Some(rustfits::wcs::frames::Frame::FK5 { equinox: 2000.0 }));
+    let world = sky.pix2world_point(&[4096.5, 4096.5]).unwrap();
+    assert_close(world[0], 83.6, 1e-10);
+    assert_close(world[1], 22.0, 1e-10);
+    let image = header_from_cards(&[
+        &card("SIMPLE", "T"),
+        &card("NAXIS", "0"),
+        &card("WCSAXES", "2"),
+        &card("CTYPE1", "'RA---TAN'"),
+        &card("CTYPE2", "'DEC--TAN'"),
+        &card("CRPIX1", "4096.5"),
+        &card("CRPIX2", "4096.5"),
+        &card("CRVAL1", "83.6"),
+        &card("CRVAL2", "22.0"),
+        &card("CDELT1", "-0.001"),
+        &card("CDELT2", "0.001"),
+    ]);
+    let expected = WCS::from_header(&image).unwrap().pix2world_point(&[4000.0, 4200.0]).unwrap();
+    let world = sky.pix2world_point(&[4000.0, 4200.0]).unwrap();
+    assert_close(world[0], expected[0], 1e-12);
+    assert_close(world[1], expected[1], 1e-12);
+
+    let detector = WCS::from_columns(header, &["X", "Y"], 'A').unwrap();
+    assert_close(detector.pix2world_point(&[40.0, 4.0]).unwrap()[0], 1.0, 1e-12);
+    assert!(WCS::from_columns(header, &["X", "Z"], ' ').is_err());
+}
@@ -7,7 +7,70 @@ use wasm_bindgen_test::*;
 
 wasm_bindgen_test_configure!(run_in_browser);
 
+mod common;
+
+use common::{card, image_bytes};
+use rustfits::wasm::FitsFile;
+use wasm_bindgen::JsValue;
+
+fn image_file() -> Vec<u8> {
+    let pixels: Vec<u8> = [-32768i16, -32767, 0, 1, 2, 32767].iter().flat_map(|value| value.to_be_bytes()).collect();
+    let extra = [
+        card("BZERO", "32768"),
+        "OBJECT  = 'NGC 4151'".to_string(),
+        "EXTNAME = '1       '".to_string(),
+        "FLAG    = 'T'".to_string(),
+    ];
+    image_bytes(16, &[3, 2], &extra, &pixels)
+}
+
+#[wasm_bindgen_test]
+fn header_object() {
+    let file = FitsFile::from_bytes(&image_file());
+    assert_eq!(file.hdu_count(), 1);
+    assert_eq!(file.hdu_type(0).unwrap(), "Primary");
+    let header = file.header(0).unwrap();
+    let get = |key: &str| js_sys::Reflect::get(&header, &JsValue::from_str(key)).unwrap();
+    assert_eq!(get("SIMPLE").as_bool(), Some(true));
+    assert_eq!(get("NAXIS1").as_f64(), Some(3.0));
+    // Quoted values stay strings with their inner spaces
+    assert_eq!(get("OBJECT").as_string(), Some("NGC 4151".to_string()));
+    assert_eq!(get("EXTNAME").as_string(), Some("1".to_string()));
+    assert_eq!(get("FLAG").as_string(), Some("T".to_string()));
+    assert!(file.header(1).is_err());
+}
+
+#[wasm_bindgen_test]
+fn image_typed_array() {
+    let file = FitsFile::from_bytes(&image_file());
+    let image = file.image_data(0).unwrap();
+    assert_eq!(image.shape().to_vec(), vec![3, 2]);
+    assert_eq!(image.bitpix(), 16);
+    let data = js_sys::Float32Array::new(&image.data());
+    assert_eq!(data.to_vec(), vec![0.0, 1.0, 32768.0, 32769.0, 32770.0, 65535.0]);
+}
+
+#[wasm_bindgen_test]
+fn table_columns_and_paging() {
+    let rows: Vec<Vec<f32>> = (0..10).map(|i| vec![i as f32]).collect();
+    let file = FitsFile::from_bytes(&common::event_list_bytes(&["TIME"], &[card("TUNIT1", "'s'")], &rows));
+    assert_eq!(file.row_count(1).unwrap(), 10);
+    let columns = file.table_columns(1).unwrap();
+    let first = columns.get(0);
+    let get = |key: &str| js_sys::Reflect::get(&first, &JsValue::from_str(key)).unwrap();
+    assert_eq!(get("name").as_string(), Some("TIME".to_string()));
+    assert_eq!(get("unit").as_string(), Some("s".to_string()));
+    assert_eq!(get("type").as_string(), Some("number".to_string()));
+    let page = js_sys::Float64Array::new(&file.column(1, "time", Some(4), Some(7)).unwrap());
+    assert_eq!(page.to_vec(), vec![4.0, 5.0, 6.0]);
+    assert!(file.column(1, "ENERGY", None, None).is_err());
+}
+
 #[wasm_bindgen_test]
-fn pass() {
-    assert_eq!(1 + 1, 2);
+fn render_to_canvas_pixels() {
+    let file = FitsFile::from_bytes(&image_file());
+    let rendered = file.render(0, "linear", "minmax", "gray", None, None, None).unwrap();
+    assert_eq!((rendered.width(), rendered.height()), (3, 2));
+    assert_eq!(rendered.pixels().length(), 24);
+    assert!(file.render(0, "linear", "manual", "gray", None, None, None).is_err());
 }